@@ -0,0 +1,311 @@
+use chrono::{DateTime, NaiveDate};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use std::error::Error;
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::path::Path;
+
+use super::{entry_offsets, parse_str, CHANGELOG_FILE};
+use crate::config::{encoding, Config};
+use crate::DebyError;
+
+const ARCHIVE_FILE: &str = "debian/changelog.old";
+const COMPRESSED_ARCHIVE_FILE: &str = "debian/changelog.old.gz";
+
+/// Which entries of _changelog_ file stay in it, the rest are archived
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Cutoff {
+    /// Entries dated before the date are archived
+    Before(NaiveDate),
+    /// Only the given number of the newest entries are kept
+    Keep(usize),
+}
+
+/// Returns byte offset of the first entry to be archived
+///
+/// # Arguments
+///
+/// - `contents` - _changelog_ file contents
+/// - `cutoff` - which entries are kept
+fn cutoff_offset(contents: &str, cutoff: Cutoff) -> Result<Option<usize>, DebyError> {
+    let offsets = entry_offsets(contents);
+    let before = match cutoff {
+        Cutoff::Keep(n) => return Ok(offsets.get(n).copied()),
+        Cutoff::Before(before) => before,
+    };
+
+    for (i, &offset) in offsets.iter().enumerate() {
+        let end = offsets.get(i + 1).copied().unwrap_or(contents.len());
+        let entry = match parse_str(&contents[offset..end]).pop() {
+            Some(e) => e,
+            None => continue,
+        };
+        let date = DateTime::parse_from_rfc2822(&entry.date)
+            .map_err(|_| DebyError::InvalidDate(entry.date.clone()))?;
+        if date.date_naive() < before {
+            return Ok(Some(offset));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Splits _changelog_ file contents into recent and archived parts
+///
+/// # Arguments
+///
+/// - `contents` - _changelog_ file contents
+/// - `cutoff` - which entries are kept
+fn split_at_cutoff(contents: &str, cutoff: Cutoff) -> Result<(String, String), DebyError> {
+    let offset = match cutoff_offset(contents, cutoff)? {
+        Some(o) => o,
+        None => return Ok((contents.to_string(), String::new())),
+    };
+
+    let mut recent = contents[..offset].trim_end().to_string();
+    if !recent.is_empty() {
+        recent.push('\n');
+    }
+
+    Ok((recent, contents[offset..].to_string()))
+}
+
+/// Splits _changelog_ file contents at every cutoff into recent part and archived entries,
+/// newer archived entries go first
+///
+/// # Arguments
+///
+/// - `contents` - _changelog_ file contents
+/// - `cutoffs` - which entries are kept
+pub(crate) fn split_at_cutoffs(
+    contents: &str,
+    cutoffs: &[Cutoff],
+) -> Result<(String, String), DebyError> {
+    let mut recent = contents.to_string();
+    let mut archived = String::new();
+    for &cutoff in cutoffs {
+        let (kept, split) = split_at_cutoff(&recent, cutoff)?;
+        if !split.is_empty() {
+            archived = match archived.is_empty() {
+                true => split,
+                false => merge_archive(&split, &archived),
+            };
+        }
+        recent = kept;
+    }
+
+    Ok((recent, archived))
+}
+
+/// Puts newly archived entries on top of previously archived ones
+///
+/// # Arguments
+///
+/// - `archived` - newly archived entries
+/// - `current_archive` - current archive contents
+fn merge_archive(archived: &str, current_archive: &str) -> String {
+    let mut contents = archived.to_string();
+    if !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    if !current_archive.is_empty() {
+        contents.push('\n');
+        contents.push_str(current_archive);
+    }
+
+    contents
+}
+
+/// Reads current archive contents, a missing archive is treated as an empty one
+///
+/// # Arguments
+///
+/// - `path` - path to archive
+/// - `compress` - read gzip-compressed archive
+fn read_archive(path: &Path, compress: bool) -> Result<String, Box<dyn Error>> {
+    if !compress {
+        return encoding::read_to_string(path);
+    }
+
+    let file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut bytes = vec![];
+    GzDecoder::new(file).read_to_end(&mut bytes)?;
+
+    Ok(encoding::decode(path, &bytes)?)
+}
+
+/// Writes a file via a temporary file, so a failed write never leaves a truncated file
+///
+/// # Arguments
+///
+/// - `path` - path to a file to be written
+/// - `contents` - file contents
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".deby-tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+
+    Ok(())
+}
+
+/// Moves entries of _changelog_ file beyond a cutoff into archive and returns the number of archived entries
+///
+/// # Arguments
+///
+/// - `root` - directory containing `debian` directory
+/// - `cutoff` - which entries are kept
+/// - `compress` - keep archived entries gzip-compressed
+fn rotate(root: &Path, cutoff: Cutoff, compress: bool) -> Result<usize, Box<dyn Error>> {
+    let contents = encoding::read_to_string(root.join(CHANGELOG_FILE))?;
+    let (recent, archived) = split_at_cutoff(&contents, cutoff)?;
+    if archived.is_empty() {
+        return Ok(0);
+    }
+
+    write_rotated(root, &recent, &archived, compress)
+}
+
+/// Writes _changelog_ file contents and puts archived entries on top of archive,
+/// returns the number of archived entries
+///
+/// # Arguments
+///
+/// - `root` - directory containing `debian` directory
+/// - `recent` - _changelog_ file contents
+/// - `archived` - entries split off _changelog_ file, nothing is archived when empty
+/// - `compress` - keep archived entries gzip-compressed
+pub(crate) fn write_rotated(
+    root: &Path,
+    recent: &str,
+    archived: &str,
+    compress: bool,
+) -> Result<usize, Box<dyn Error>> {
+    let changelog_file = root.join(CHANGELOG_FILE);
+    if archived.is_empty() {
+        write_atomic(&changelog_file, recent.as_bytes())?;
+        return Ok(0);
+    }
+
+    let archive_file = match compress {
+        true => root.join(COMPRESSED_ARCHIVE_FILE),
+        false => root.join(ARCHIVE_FILE),
+    };
+    let archive = merge_archive(archived, &read_archive(&archive_file, compress)?);
+    if compress {
+        let mut encoder = GzEncoder::new(vec![], Compression::best());
+        encoder.write_all(archive.as_bytes())?;
+        write_atomic(&archive_file, &encoder.finish()?)?;
+    } else {
+        write_atomic(&archive_file, archive.as_bytes())?;
+    }
+    // archive is written first, a failure below may duplicate entries but never lose them
+    write_atomic(&changelog_file, recent.as_bytes())?;
+
+    Ok(entry_offsets(archived).len())
+}
+
+/// Moves `debian/changelog` entries older than a date into `debian/changelog.old`
+/// (or `debian/changelog.old.gz`) and returns the number of archived entries
+///
+/// ## Arguments
+///
+/// - `before` - cutoff date in `YYYY-MM-DD` format, older entries are archived
+/// - `compress` - keep archived entries gzip-compressed
+pub fn archive(before: &str, compress: bool) -> Result<usize, DebyError> {
+    let cutoff = NaiveDate::parse_from_str(before, "%Y-%m-%d")
+        .map_err(|_| DebyError::InvalidDate(before.to_string()))?;
+
+    let root = Config::find_root().map_err(|e| crate::map_error(e, DebyError::ChangelogArchive))?;
+    if !root.join(CHANGELOG_FILE).exists() {
+        return Err(DebyError::ChangelogMissing);
+    }
+
+    rotate(&root, Cutoff::Before(cutoff), compress)
+        .map_err(|e| crate::map_error(e, DebyError::ChangelogArchive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAKE_CHANGELOG: &str = "foo (1.2.0) unstable; urgency=low
+
+  * change 3
+
+ -- John Doe <john@example.com>  Tue, 11 Jan 2022 10:00:00 +0300
+
+foo (1.1.0) unstable; urgency=low
+
+  * change 2
+
+ -- John Doe <john@example.com>  Sat, 10 Jul 2021 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * change 1  
+
+ -- John Doe <john@example.com>  Sun, 10 Jan 2021 10:00:00 +0300
+";
+
+    #[test]
+    fn test_split_at_cutoff() {
+        let fake_cutoff = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        let (recent, archived) =
+            split_at_cutoff(FAKE_CHANGELOG, Cutoff::Before(fake_cutoff)).unwrap();
+
+        assert!(recent.starts_with("foo (1.2.0)"));
+        assert!(recent.ends_with("+0300\n"));
+        assert!(archived.starts_with("foo (1.1.0)"));
+        assert_eq!(format!("{}\n{}", recent, archived), FAKE_CHANGELOG);
+    }
+
+    #[test]
+    fn test_split_at_cutoff_nothing_to_archive() {
+        let fake_cutoff = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let (recent, archived) =
+            split_at_cutoff(FAKE_CHANGELOG, Cutoff::Before(fake_cutoff)).unwrap();
+
+        assert_eq!(recent, FAKE_CHANGELOG);
+        assert_eq!(archived, "");
+    }
+
+    #[test]
+    fn test_split_at_cutoff_keep() {
+        let (recent, archived) = split_at_cutoff(FAKE_CHANGELOG, Cutoff::Keep(2)).unwrap();
+
+        assert!(recent.starts_with("foo (1.2.0)"));
+        assert!(archived.starts_with("foo (1.0.0)"));
+        assert_eq!(format!("{}\n{}", recent, archived), FAKE_CHANGELOG);
+
+        let (recent, archived) = split_at_cutoff(FAKE_CHANGELOG, Cutoff::Keep(3)).unwrap();
+
+        assert_eq!(recent, FAKE_CHANGELOG);
+        assert_eq!(archived, "");
+    }
+
+    #[test]
+    fn test_split_at_cutoffs() {
+        let fake_cutoff = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        let fake_cutoffs = [Cutoff::Keep(2), Cutoff::Before(fake_cutoff)];
+
+        let (recent, archived) = split_at_cutoffs(FAKE_CHANGELOG, &fake_cutoffs).unwrap();
+
+        assert!(recent.starts_with("foo (1.2.0)"));
+        assert!(archived.starts_with("foo (1.1.0)"));
+        assert_eq!(format!("{}\n{}", recent, archived), FAKE_CHANGELOG);
+    }
+
+    #[test]
+    fn test_merge_archive() {
+        assert_eq!(merge_archive("new", ""), "new\n");
+        assert_eq!(merge_archive("new\n", "old\n"), "new\n\nold\n");
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use super::notes::change_items;
+use super::{read_entries, ChangelogEntry};
+use crate::template;
+use crate::DebyError;
+
+const DEFAULT_SUBJECT: &str = "[ANNOUNCE] {package} {version} released";
+const DEFAULT_BODY: &str = "Hello,
+
+{package} {version} has been released to {distribution} (urgency={urgency}).
+
+Changes:
+
+{changes}
+
+--
+{maintainer}
+";
+
+/// Templates of release announcement email.
+///
+/// Available placeholders: `{package}`, `{version}`, `{distribution}`, `{urgency}`,
+/// `{changes}`, `{maintainer}`, `{date}` and `{context.<key>}` for every `context` value
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailTemplate {
+    pub subject: String,
+    pub body: String,
+    pub context: BTreeMap<String, String>,
+}
+
+impl Default for EmailTemplate {
+    fn default() -> Self {
+        Self {
+            subject: DEFAULT_SUBJECT.to_string(),
+            body: DEFAULT_BODY.to_string(),
+            context: BTreeMap::new(),
+        }
+    }
+}
+
+/// Release announcement email
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Formats release announcement email for a _changelog_ entry
+///
+/// # Arguments
+///
+/// - `entry` - parsed _changelog_ entry
+/// - `template` - email templates
+fn format_announcement(entry: &ChangelogEntry, template: &EmailTemplate) -> Announcement {
+    let changes: String = change_items(entry)
+        .iter()
+        .map(|item| format!("  * {}\n", item))
+        .collect();
+
+    let context = template::context_vars(&template.context);
+    let mut vars = vec![
+        ("package", entry.package.as_str()),
+        ("version", entry.version.as_str()),
+        ("distribution", entry.distribution.as_str()),
+        ("urgency", entry.urgency.as_str()),
+        ("changes", changes.trim_end()),
+        ("maintainer", entry.maintainer.as_str()),
+        ("date", entry.date.as_str()),
+    ];
+    vars.extend(context.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+    Announcement {
+        subject: template::render(&template.subject, &vars),
+        body: template::render(&template.body, &vars),
+    }
+}
+
+/// Creates release announcement email from the newest `debian/changelog` entry
+///
+/// ## Arguments
+///
+/// - `template` - email templates, use `EmailTemplate::default()` for a generic announcement
+pub fn announcement(template: &EmailTemplate) -> Result<Announcement, DebyError> {
+    let entries = read_entries()?;
+    let latest = entries.first().ok_or(DebyError::ChangelogEmpty)?;
+
+    Ok(format_announcement(latest, template))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::parse_str;
+
+    const FAKE_CHANGELOG: &str = "foo (1.2.0) unstable; urgency=medium
+
+  * change 1
+  * change 2
+
+ -- John Doe <john@example.com>  Tue, 11 Jan 2022 10:00:00 +0300
+";
+
+    #[test]
+    fn test_format_announcement_default() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_announcement(&entries[0], &EmailTemplate::default());
+
+        assert_eq!(actual.subject, "[ANNOUNCE] foo 1.2.0 released");
+        assert_eq!(
+            actual.body,
+            "Hello,
+
+foo 1.2.0 has been released to unstable (urgency=medium).
+
+Changes:
+
+  * change 1
+  * change 2
+
+--
+John Doe <john@example.com>
+"
+        );
+    }
+
+    #[test]
+    fn test_format_announcement_custom() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let mut fake_context = BTreeMap::new();
+        fake_context.insert("team".to_string(), "Platform".to_string());
+        let fake_template = EmailTemplate {
+            subject: "{package}/{version}".to_string(),
+            body: "{date} {context.team}".to_string(),
+            context: fake_context,
+        };
+        let actual = format_announcement(&entries[0], &fake_template);
+
+        assert_eq!(actual.subject, "foo/1.2.0");
+        assert_eq!(actual.body, "Tue, 11 Jan 2022 10:00:00 +0300 Platform");
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::DateTime;
+
+use super::{escape, read_entries, ChangelogEntry};
+use crate::DebyError;
+
+/// Converts RFC 2822 date of a _changelog_ entry into RFC 3339 date
+///
+/// # Arguments
+///
+/// - `date` - date string from _changelog_ entry trailer
+fn format_date(date: &str) -> Result<String, DebyError> {
+    let dt =
+        DateTime::parse_from_rfc2822(date).map_err(|_| DebyError::InvalidDate(date.to_string()))?;
+
+    Ok(dt.to_rfc3339())
+}
+
+/// Formats a single feed entry
+///
+/// # Arguments
+///
+/// - `entry` - parsed _changelog_ entry
+/// - `base_url` - URL used as feed id and link
+fn format_entry(entry: &ChangelogEntry, base_url: &str) -> Result<String, DebyError> {
+    let author = entry
+        .maintainer
+        .split('<')
+        .next()
+        .unwrap_or_default()
+        .trim();
+
+    let contents = format!(
+        "  <entry>
+    <title>{package} {version}</title>
+    <id>{url}#{package}-{version}</id>
+    <link href=\"{url}\"/>
+    <updated>{updated}</updated>
+    <author><name>{author}</name></author>
+    <content type=\"text\">{changes}</content>
+  </entry>
+",
+        package = escape(&entry.package),
+        version = escape(&entry.version),
+        url = escape(base_url),
+        updated = format_date(&entry.date)?,
+        author = escape(author),
+        changes = escape(&entry.changes.join("\n")),
+    );
+
+    Ok(contents)
+}
+
+/// Formats Atom feed of _changelog_ entries
+///
+/// # Arguments
+///
+/// - `entries` - parsed _changelog_ entries, newer entries go first
+/// - `base_url` - URL used as feed id and link
+fn format_feed(entries: &[ChangelogEntry], base_url: &str) -> Result<String, DebyError> {
+    let latest = entries.first().ok_or(DebyError::ChangelogEmpty)?;
+
+    let mut feed = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+<feed xmlns=\"http://www.w3.org/2005/Atom\">
+  <title>{package} changelog</title>
+  <id>{url}</id>
+  <link href=\"{url}\"/>
+  <updated>{updated}</updated>
+",
+        package = escape(&latest.package),
+        url = escape(base_url),
+        updated = format_date(&latest.date)?,
+    );
+    for entry in entries {
+        feed.push_str(&format_entry(entry, base_url)?);
+    }
+    feed.push_str("</feed>\n");
+
+    Ok(feed)
+}
+
+/// Creates Atom feed with an entry per version of `debian/changelog` file
+///
+/// ## Arguments
+///
+/// - `base_url` - URL of a page the feed is published for, used as feed id and link
+pub fn atom_feed(base_url: &str) -> Result<String, DebyError> {
+    let entries = read_entries()?;
+
+    format_feed(&entries, base_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::parse_str;
+
+    const FAKE_CHANGELOG: &str = "foo (1.1.0) unstable; urgency=low
+
+  * fix <tag> & more
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+";
+
+    #[test]
+    fn test_format_date() {
+        let actual = format_date("Mon, 10 Jan 2022 10:00:00 +0300").unwrap();
+
+        assert_eq!(actual, "2022-01-10T10:00:00+03:00");
+    }
+
+    #[test]
+    fn test_format_date_invalid() {
+        let actual = format_date("yesterday");
+
+        assert!(matches!(actual, Err(DebyError::InvalidDate(_))));
+    }
+
+    #[test]
+    fn test_format_feed() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_feed(&entries, "https://example.com/foo").unwrap();
+        let expected = "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+<feed xmlns=\"http://www.w3.org/2005/Atom\">
+  <title>foo changelog</title>
+  <id>https://example.com/foo</id>
+  <link href=\"https://example.com/foo\"/>
+  <updated>2022-01-10T10:00:00+03:00</updated>
+  <entry>
+    <title>foo 1.1.0</title>
+    <id>https://example.com/foo#foo-1.1.0</id>
+    <link href=\"https://example.com/foo\"/>
+    <updated>2022-01-10T10:00:00+03:00</updated>
+    <author><name>John Doe</name></author>
+    <content type=\"text\">* fix &lt;tag&gt; &amp; more</content>
+  </entry>
+</feed>
+";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_feed_empty() {
+        let actual = format_feed(&[], "https://example.com/foo");
+
+        assert!(matches!(actual, Err(DebyError::ChangelogEmpty)));
+    }
+}
@@ -0,0 +1,356 @@
+use regex::Regex;
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::config::{encoding, Config};
+use crate::DebyError;
+
+mod archive;
+mod email;
+mod feed;
+mod notes;
+mod render;
+mod search;
+
+pub use archive::archive;
+pub(crate) use archive::{split_at_cutoffs, write_rotated, Cutoff};
+pub use email::{announcement, Announcement, EmailTemplate};
+pub use feed::atom_feed;
+pub use notes::release_notes;
+pub use render::render;
+pub use search::{search, SearchMatch};
+
+const CHANGELOG_FILE: &str = "debian/changelog";
+const HEADER_PATTERN: &str = r"^(\S+) \(([^()\s]+)\) ([^;]+);(.*)$";
+
+/// Output format of documents generated from _changelog_ file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Markdown,
+    Html,
+}
+
+/// A single entry of _changelog_ file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangelogEntry {
+    pub package: String,
+    pub version: String,
+    pub distribution: String,
+    pub urgency: String,
+    pub changes: Vec<String>,
+    pub maintainer: String,
+    pub date: String,
+}
+
+/// A single entry of _changelog_ file together with its text as written in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryStanza {
+    pub entry: ChangelogEntry,
+    /// Text of the entry from its header line to its trailer line
+    pub text: String,
+}
+
+impl ChangelogEntry {
+    fn new(header: &regex::Captures) -> Self {
+        let urgency = header[4]
+            .split(',')
+            .find_map(|kv| kv.trim().strip_prefix("urgency="))
+            .unwrap_or_default();
+
+        Self {
+            package: header[1].to_string(),
+            version: header[2].to_string(),
+            distribution: header[3].trim().to_string(),
+            urgency: urgency.to_string(),
+            changes: vec![],
+            maintainer: String::new(),
+            date: String::new(),
+        }
+    }
+}
+
+/// Escapes special XML and HTML characters
+///
+/// # Arguments
+///
+/// - `s` - string value to be escaped
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escapes characters Markdown would treat as markup or inline HTML, e.g. `<tag>`
+///
+/// # Arguments
+///
+/// - `s` - string value to be escaped
+pub(crate) fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Parses _changelog_ file contents into entries, newer entries go first
+///
+/// # Arguments
+///
+/// - `contents` - _changelog_ file contents
+pub(crate) fn parse_str(contents: &str) -> Vec<ChangelogEntry> {
+    let header_re = Regex::new(HEADER_PATTERN).unwrap();
+    let trailer_re = Regex::new(r"^ -- (.+?)  (.+)$").unwrap();
+
+    let mut entries: Vec<ChangelogEntry> = vec![];
+    for line in contents.lines() {
+        if let Some(header) = header_re.captures(line) {
+            entries.push(ChangelogEntry::new(&header));
+            continue;
+        }
+
+        let entry = match entries.last_mut() {
+            Some(e) => e,
+            None => continue,
+        };
+        if let Some(trailer) = trailer_re.captures(line) {
+            entry.maintainer = trailer[1].to_string();
+            entry.date = trailer[2].trim().to_string();
+        } else if !line.trim().is_empty() {
+            let change = line.strip_prefix("  ").unwrap_or(line);
+            entry.changes.push(change.trim_end().to_string());
+        }
+    }
+
+    entries
+}
+
+/// Returns byte offsets of entry header lines in _changelog_ file contents
+///
+/// # Arguments
+///
+/// - `contents` - _changelog_ file contents
+pub(crate) fn entry_offsets(contents: &str) -> Vec<usize> {
+    let header_re = Regex::new(HEADER_PATTERN).unwrap();
+
+    let mut offsets = vec![];
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        if header_re.is_match(line.trim_end_matches(['\r', '\n'])) {
+            offsets.push(offset);
+        }
+        offset += line.len();
+    }
+
+    offsets
+}
+
+/// Finds an entry of a version in _changelog_ file contents
+///
+/// # Arguments
+///
+/// - `contents` - _changelog_ file contents
+/// - `version` - version of the entry
+fn find_stanza(contents: &str, version: &str) -> Result<EntryStanza, DebyError> {
+    let offsets = entry_offsets(contents);
+    for (i, &offset) in offsets.iter().enumerate() {
+        let end = offsets.get(i + 1).copied().unwrap_or(contents.len());
+        let text = contents[offset..end].trim_end();
+        let entry = match parse_str(text).pop() {
+            Some(e) if e.version == version => e,
+            _ => continue,
+        };
+
+        return Ok(EntryStanza {
+            entry,
+            text: format!("{}\n", text),
+        });
+    }
+
+    Err(DebyError::VersionNotFound(version.to_string()))
+}
+
+/// Returns path of `debian/changelog` file in the directory of config file,
+/// current directory is used when there's no config file
+pub(crate) fn path() -> Result<PathBuf, Box<dyn Error>> {
+    let path = Config::find_root()?.join(CHANGELOG_FILE);
+    if !path.exists() {
+        return Err(DebyError::ChangelogMissing.into());
+    }
+
+    Ok(path)
+}
+
+/// Reads and parses `debian/changelog` file
+pub(crate) fn read() -> Result<Vec<ChangelogEntry>, Box<dyn Error>> {
+    let contents = encoding::read_to_string(path()?)?;
+
+    Ok(parse_str(&contents))
+}
+
+/// Reads `debian/changelog` file keeping structured errors
+fn read_entries() -> Result<Vec<ChangelogEntry>, DebyError> {
+    read().map_err(|e| crate::map_error(e, DebyError::ChangelogRead))
+}
+
+/// Iterator over entries of _changelog_ file, newer entries go first.
+/// Use `rev` to walk from the oldest one
+#[derive(Debug, Clone)]
+pub struct Entries {
+    inner: std::vec::IntoIter<ChangelogEntry>,
+}
+
+impl Entries {
+    fn new(entries: Vec<ChangelogEntry>) -> Self {
+        Self {
+            inner: entries.into_iter(),
+        }
+    }
+}
+
+impl Iterator for Entries {
+    type Item = ChangelogEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Entries {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl ExactSizeIterator for Entries {}
+
+/// Reads `debian/changelog` file into structured entries, newer entries go first
+pub fn parse() -> Result<Entries, DebyError> {
+    read_entries().map(Entries::new)
+}
+
+/// Returns an entry of a version of `debian/changelog` file with its full text,
+/// e.g. to publish notes of a release
+///
+/// ## Arguments
+///
+/// - `version` - version of the entry
+pub fn entry_for(version: &str) -> Result<EntryStanza, DebyError> {
+    let contents = path()
+        .and_then(encoding::read_to_string)
+        .map_err(|e| crate::map_error(e, DebyError::ChangelogRead))?;
+
+    find_stanza(&contents, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAKE_CHANGELOG: &str = "foo (1.1.0) unstable experimental; urgency=high
+
+  * change 1
+    continued
+  * change 2
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * initial release
+
+ -- Jane Doe <jane@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+    #[test]
+    fn test_parse_str() {
+        let actual = parse_str(FAKE_CHANGELOG);
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(
+            actual[0],
+            ChangelogEntry {
+                package: "foo".to_string(),
+                version: "1.1.0".to_string(),
+                distribution: "unstable experimental".to_string(),
+                urgency: "high".to_string(),
+                changes: vec![
+                    "* change 1".to_string(),
+                    "  continued".to_string(),
+                    "* change 2".to_string()
+                ],
+                maintainer: "John Doe <john@example.com>".to_string(),
+                date: "Mon, 10 Jan 2022 10:00:00 +0300".to_string(),
+            }
+        );
+        assert_eq!(actual[1].version, "1.0.0");
+        assert_eq!(actual[1].changes, vec!["* initial release".to_string()]);
+    }
+
+    #[test]
+    fn test_entry_offsets() {
+        let actual = entry_offsets(FAKE_CHANGELOG);
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0], 0);
+        assert!(FAKE_CHANGELOG[actual[1]..].starts_with("foo (1.0.0)"));
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut actual = Entries::new(parse_str(FAKE_CHANGELOG));
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual.next_back().unwrap().version, "1.0.0");
+        assert_eq!(actual.next().unwrap().version, "1.1.0");
+        assert!(actual.next().is_none());
+    }
+
+    #[test]
+    fn test_find_stanza() {
+        let actual = find_stanza(FAKE_CHANGELOG, "1.0.0").unwrap();
+
+        assert_eq!(actual.entry.version, "1.0.0");
+        assert_eq!(
+            actual.text,
+            "foo (1.0.0) unstable; urgency=low
+
+  * initial release
+
+ -- Jane Doe <jane@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+"
+        );
+        assert!(matches!(
+            find_stanza(FAKE_CHANGELOG, "0.9.0"),
+            Err(DebyError::VersionNotFound(v)) if v == "0.9.0"
+        ));
+    }
+
+    #[test]
+    fn test_parse_str_empty() {
+        let actual = parse_str("");
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_escape_markdown() {
+        let actual = escape_markdown("fix <tag> in *foo_bar* [docs] & `code` \\");
+
+        assert_eq!(
+            actual,
+            "fix \\<tag\\> in \\*foo\\_bar\\* \\[docs\\] & \\`code\\` \\\\"
+        );
+    }
+}
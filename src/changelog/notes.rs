@@ -0,0 +1,192 @@
+use super::{escape, escape_markdown, read_entries, ChangelogEntry, Format};
+use crate::DebyError;
+
+/// Collects change items of an entry, continuation lines are joined to their items
+/// and `[ Author ]` group headers are skipped
+///
+/// # Arguments
+///
+/// - `entry` - parsed _changelog_ entry
+pub(crate) fn change_items(entry: &ChangelogEntry) -> Vec<String> {
+    let mut items: Vec<String> = vec![];
+    for line in &entry.changes {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix("* ") {
+            items.push(item.to_string());
+            continue;
+        }
+        match items.last_mut() {
+            Some(item) if line.starts_with(' ') => {
+                item.push(' ');
+                item.push_str(trimmed);
+            }
+            _ => items.push(trimmed.to_string()),
+        }
+    }
+
+    items
+}
+
+/// Formats release notes for entries newer than `from` up to `to` inclusive
+///
+/// # Arguments
+///
+/// - `entries` - parsed _changelog_ entries, newer entries go first
+/// - `from` - previous version, its changes are not included
+/// - `to` - target version
+/// - `format` - output format
+fn format_notes(
+    entries: &[ChangelogEntry],
+    from: &str,
+    to: &str,
+    format: Format,
+) -> Result<String, DebyError> {
+    let position = |v: &str| {
+        entries
+            .iter()
+            .position(|e| e.version == v)
+            .ok_or_else(|| DebyError::VersionNotFound(v.to_string()))
+    };
+    let start = position(to)?;
+    let end = position(from)?;
+    if start >= end {
+        return Err(DebyError::InvalidVersionRange(
+            from.to_string(),
+            to.to_string(),
+        ));
+    }
+    let range = &entries[start..end];
+
+    let package = range
+        .first()
+        .map(|e| e.package.as_str())
+        .unwrap_or_default();
+    let mut notes = match format {
+        Format::Text => format!("{} {} -> {}\n\n", package, from, to),
+        Format::Markdown => format!(
+            "# {} {} → {}\n\n",
+            escape_markdown(package),
+            escape_markdown(from),
+            escape_markdown(to)
+        ),
+        Format::Html => format!(
+            "<h1>{} {} → {}</h1>\n<ul>\n",
+            escape(package),
+            escape(from),
+            escape(to)
+        ),
+    };
+    for item in range.iter().flat_map(change_items) {
+        match format {
+            Format::Text => notes.push_str(&format!("  * {}\n", item)),
+            Format::Markdown => notes.push_str(&format!("- {}\n", escape_markdown(&item))),
+            Format::Html => notes.push_str(&format!("  <li>{}</li>\n", escape(&item))),
+        }
+    }
+    if format == Format::Html {
+        notes.push_str("</ul>\n");
+    }
+
+    Ok(notes)
+}
+
+/// Collects changes of all `debian/changelog` entries after `from` up to `to` version
+/// into a single document
+///
+/// ## Arguments
+///
+/// - `from` - previous version, its changes are not included
+/// - `to` - target version
+/// - `format` - output format
+pub fn release_notes(from: &str, to: &str, format: Format) -> Result<String, DebyError> {
+    let entries = read_entries()?;
+
+    format_notes(&entries, from, to, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::parse_str;
+
+    const FAKE_CHANGELOG: &str = "foo (1.2.0) unstable; urgency=low
+
+  [ Jane Doe ]
+  * change 3
+    continued
+
+ -- John Doe <john@example.com>  Tue, 11 Jan 2022 10:00:00 +0300
+
+foo (1.1.0) unstable; urgency=low
+
+  * change 2
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * change 1
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+    #[test]
+    fn test_format_notes_text() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_notes(&entries, "1.0.0", "1.2.0", Format::Text).unwrap();
+        let expected = "foo 1.0.0 -> 1.2.0
+
+  * change 3 continued
+  * change 2
+";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_notes_markdown() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_notes(&entries, "1.1.0", "1.2.0", Format::Markdown).unwrap();
+        let expected = "# foo 1.1.0 → 1.2.0
+
+- change 3 continued
+";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_notes_html() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_notes(&entries, "1.1.0", "1.2.0", Format::Html).unwrap();
+        let expected = "<h1>foo 1.1.0 → 1.2.0</h1>
+<ul>
+  <li>change 3 continued</li>
+</ul>
+";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_notes_unknown_version() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_notes(&entries, "0.9.0", "1.2.0", Format::Text);
+
+        assert!(matches!(actual, Err(DebyError::VersionNotFound(_))));
+    }
+
+    #[test]
+    fn test_format_notes_swapped_range() {
+        let entries = parse_str(FAKE_CHANGELOG);
+
+        for (from, to) in [("1.2.0", "1.0.0"), ("1.1.0", "1.1.0")] {
+            let actual = format_notes(&entries, from, to, Format::Text);
+
+            assert!(matches!(actual, Err(DebyError::InvalidVersionRange(_, _))));
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use super::notes::change_items;
+use super::{escape, escape_markdown, read_entries, ChangelogEntry, Format};
+use crate::DebyError;
+
+/// Formats a single _changelog_ entry as a document section
+///
+/// # Arguments
+///
+/// - `entry` - parsed _changelog_ entry
+/// - `format` - output format
+fn format_entry(entry: &ChangelogEntry, format: Format) -> String {
+    let items = change_items(entry);
+    let details = format!(
+        "{}, urgency {}, {}, {}",
+        entry.distribution, entry.urgency, entry.maintainer, entry.date
+    );
+
+    let mut section = match format {
+        Format::Text => format!("{} {}\n{}\n\n", entry.package, entry.version, details),
+        Format::Markdown => format!(
+            "## {} {}\n\n_{}_\n\n",
+            escape_markdown(&entry.package),
+            escape_markdown(&entry.version),
+            escape_markdown(&details)
+        ),
+        Format::Html => format!(
+            "<section>\n<h2>{} {}</h2>\n<p>{}</p>\n<ul>\n",
+            escape(&entry.package),
+            escape(&entry.version),
+            escape(&details)
+        ),
+    };
+    for item in items {
+        match format {
+            Format::Text => section.push_str(&format!("  * {}\n", item)),
+            Format::Markdown => section.push_str(&format!("- {}\n", escape_markdown(&item))),
+            Format::Html => section.push_str(&format!("  <li>{}</li>\n", escape(&item))),
+        }
+    }
+    if format == Format::Html {
+        section.push_str("</ul>\n</section>\n");
+    }
+
+    section
+}
+
+/// Formats all _changelog_ entries into a single document, newer entries go first
+///
+/// # Arguments
+///
+/// - `entries` - parsed _changelog_ entries
+/// - `format` - output format
+fn format_document(entries: &[ChangelogEntry], format: Format) -> String {
+    entries
+        .iter()
+        .map(|e| format_entry(e, format))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Renders all entries of `debian/changelog` file as a document for release pages
+///
+/// ## Arguments
+///
+/// - `format` - output format
+pub fn render(format: Format) -> Result<String, DebyError> {
+    let entries = read_entries()?;
+
+    Ok(format_document(&entries, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::parse_str;
+
+    const FAKE_CHANGELOG: &str = "foo (1.1.0) unstable; urgency=low
+
+  * fix <tag>
+    & more
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * initial release
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+    #[test]
+    fn test_format_document_markdown() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_document(&entries, Format::Markdown);
+        let expected = "## foo 1.1.0
+
+_unstable, urgency low, John Doe \\<john@example.com\\>, Mon, 10 Jan 2022 10:00:00 +0300_
+
+- fix \\<tag\\> & more
+
+## foo 1.0.0
+
+_unstable, urgency low, John Doe \\<john@example.com\\>, Sun, 09 Jan 2022 10:00:00 +0300_
+
+- initial release
+";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_entry_html() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = format_entry(&entries[0], Format::Html);
+        let expected = "<section>
+<h2>foo 1.1.0</h2>
+<p>unstable, urgency low, John Doe &lt;john@example.com&gt;, Mon, 10 Jan 2022 10:00:00 +0300</p>
+<ul>
+  <li>fix &lt;tag&gt; &amp; more</li>
+</ul>
+</section>
+";
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,94 @@
+use regex::Regex;
+
+use super::{read_entries, ChangelogEntry};
+use crate::DebyError;
+
+/// A _changelog_ entry matching a search pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    /// Matching entry
+    pub entry: ChangelogEntry,
+    /// Change lines of the entry matching a search pattern
+    pub lines: Vec<String>,
+}
+
+/// Finds change lines matching a pattern
+///
+/// # Arguments
+///
+/// - `entries` - parsed _changelog_ entries
+/// - `is_match` - predicate to test every change line
+fn find<F: Fn(&str) -> bool>(entries: Vec<ChangelogEntry>, is_match: F) -> Vec<SearchMatch> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let lines: Vec<String> = entry
+                .changes
+                .iter()
+                .filter(|line| is_match(line))
+                .cloned()
+                .collect();
+            if lines.is_empty() {
+                return None;
+            }
+            Some(SearchMatch { entry, lines })
+        })
+        .collect()
+}
+
+/// Searches change lines of `debian/changelog` file, newer entries go first
+///
+/// ## Arguments
+///
+/// - `pattern` - a substring or a regular expression to search for
+/// - `regex` - treat `pattern` as a regular expression
+pub fn search(pattern: &str, regex: bool) -> Result<Vec<SearchMatch>, DebyError> {
+    let entries = read_entries()?;
+
+    if !regex {
+        return Ok(find(entries, |line| line.contains(pattern)));
+    }
+
+    let re = Regex::new(pattern).map_err(|_| DebyError::InvalidPattern(pattern.to_string()))?;
+    Ok(find(entries, |line| re.is_match(line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::parse_str;
+
+    const FAKE_CHANGELOG: &str = "foo (1.1.0) unstable; urgency=low
+
+  * fix crash on start
+  * update docs
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * Fix CVE-2021-1234
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+    #[test]
+    fn test_find_substring() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let actual = find(entries, |line| line.contains("crash"));
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].entry.version, "1.1.0");
+        assert_eq!(actual[0].lines, vec!["* fix crash on start".to_string()]);
+    }
+
+    #[test]
+    fn test_find_regex() {
+        let entries = parse_str(FAKE_CHANGELOG);
+        let re = Regex::new(r"(?i)fix").unwrap();
+        let actual = find(entries, |line| re.is_match(line));
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[1].entry.version, "1.0.0");
+    }
+}
@@ -0,0 +1,124 @@
+/// CPU names of dpkg cputable, used by `any-<cpu>` wildcards
+const CPUS: &[&str] = &[
+    "alpha",
+    "amd64",
+    "arc",
+    "arm",
+    "arm64",
+    "armeb",
+    "avr32",
+    "hppa",
+    "i386",
+    "ia64",
+    "loong64",
+    "m32r",
+    "m68k",
+    "mips",
+    "mips64",
+    "mips64el",
+    "mips64r6",
+    "mips64r6el",
+    "mipsel",
+    "mipsr6",
+    "mipsr6el",
+    "nios2",
+    "or1k",
+    "powerpc",
+    "powerpcel",
+    "ppc64",
+    "ppc64el",
+    "riscv64",
+    "s390",
+    "s390x",
+    "sh3",
+    "sh3eb",
+    "sh4",
+    "sh4eb",
+    "sparc",
+    "sparc64",
+    "tilegx",
+];
+
+/// Architectures named after their ABI rather than CPU, e.g. `armhf`
+const ABIS: &[&str] = &[
+    "arm64ilp32",
+    "armel",
+    "armhf",
+    "mipsn32",
+    "mipsn32el",
+    "mipsn32r6",
+    "mipsn32r6el",
+    "powerpcspe",
+    "x32",
+];
+
+/// Operating systems of dpkg ostable, architectures of `linux` have no prefix
+const SYSTEMS: &[&str] = &[
+    "aix",
+    "darwin",
+    "dragonflybsd",
+    "freebsd",
+    "hurd",
+    "kfreebsd",
+    "knetbsd",
+    "kopensolaris",
+    "linux",
+    "mint",
+    "musl-linux",
+    "netbsd",
+    "openbsd",
+    "solaris",
+    "uclibc-linux",
+    "uclinux",
+];
+
+/// Checks that architecture is a known dpkg architecture, e.g. `amd64` or `hurd-i386`,
+/// or an architecture wildcard, e.g. `linux-any` or `any-i386`
+///
+/// # Arguments
+///
+/// - `arch` - architecture name
+pub(crate) fn is_known(arch: &str) -> bool {
+    let is_cpu = |cpu: &str| CPUS.contains(&cpu) || ABIS.contains(&cpu);
+
+    if is_cpu(arch) {
+        return true;
+    }
+    if let Some(cpu) = arch.strip_prefix("any-") {
+        return CPUS.contains(&cpu);
+    }
+    if let Some(os) = arch.strip_suffix("-any") {
+        return SYSTEMS.contains(&os);
+    }
+
+    SYSTEMS.iter().filter(|os| **os != "linux").any(|os| {
+        arch.strip_prefix(os)
+            .and_then(|cpu| cpu.strip_prefix('-'))
+            .is_some_and(is_cpu)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_known() {
+        assert!(is_known("amd64"));
+        assert!(is_known("armhf"));
+        assert!(is_known("hurd-i386"));
+        assert!(is_known("musl-linux-arm64"));
+        assert!(is_known("linux-any"));
+        assert!(is_known("kfreebsd-any"));
+        assert!(is_known("any-i386"));
+    }
+
+    #[test]
+    fn test_is_known_unknown() {
+        assert!(!is_known("x86_64"));
+        assert!(!is_known("linux-amd64"));
+        assert!(!is_known("any-armhf"));
+        assert!(!is_known("windows-any"));
+        assert!(!is_known("hurd-"));
+    }
+}
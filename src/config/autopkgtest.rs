@@ -0,0 +1,146 @@
+use serde::Deserialize;
+
+use std::error::Error;
+use std::fs;
+
+use super::{Config, Sign};
+use crate::deb822::{self, Paragraph};
+use crate::DebyError;
+
+pub(crate) const TESTS_CONTROL_FILE: &str = "debian/tests/control";
+
+const TESTS: &str = "Tests";
+const TEST_COMMAND: &str = "Test-Command";
+const DEPENDS: &str = "Depends";
+const RESTRICTIONS: &str = "Restrictions";
+
+/// A single stanza of `debian/tests/control` file, it runs either
+/// test scripts from `debian/tests` or a shell command
+#[derive(Deserialize, Debug)]
+pub(crate) struct Autopkgtest {
+    #[serde(default = "Autopkgtest::default_vec_value")]
+    tests: Vec<String>,
+    #[serde(
+        rename(deserialize = "testCommand"),
+        alias = "test_command",
+        default = "Autopkgtest::default_string_value"
+    )]
+    test_command: String,
+    /// Comma separated dependencies, `@` stands for all binary packages of the source
+    #[serde(default = "Autopkgtest::default_string_value")]
+    depends: String,
+    #[serde(default = "Autopkgtest::default_vec_value")]
+    restrictions: Vec<String>,
+}
+
+impl Autopkgtest {
+    /// Formats a single stanza, exactly one of `tests` and `testCommand` must be set
+    ///
+    /// # Arguments
+    ///
+    /// - `index` - position of the stanza in `tests` section
+    fn format_stanza(&self, index: usize) -> Result<Paragraph, DebyError> {
+        let mut stanza = Paragraph::new();
+        match (self.tests.is_empty(), self.test_command.trim().is_empty()) {
+            (false, true) => stanza.set(TESTS, &self.tests.join(", ")),
+            (true, false) => stanza.set(TEST_COMMAND, self.test_command.trim()),
+            _ => return Err(DebyError::InvalidTestStanza(index)),
+        }
+        if !self.depends.trim().is_empty() {
+            stanza.set(DEPENDS, self.depends.trim());
+        }
+        if !self.restrictions.is_empty() {
+            stanza.set(RESTRICTIONS, &self.restrictions.join(", "));
+        }
+
+        Ok(stanza)
+    }
+
+    /// Formats `debian/tests/control` file contents, stanzas are separated by blank lines
+    ///
+    /// # Arguments
+    ///
+    /// - `tests` - test stanzas from config file `.debyrc`
+    fn create_contents(tests: &[Autopkgtest]) -> Result<String, DebyError> {
+        let stanzas = tests
+            .iter()
+            .enumerate()
+            .map(|(i, t)| t.format_stanza(i))
+            .collect::<Result<Vec<Paragraph>, DebyError>>()?;
+
+        Ok(deb822::to_string(&stanzas))
+    }
+
+    /// Writes `debian/tests/control` file if `tests` section of config file is not empty
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    pub(crate) fn update(config: &Config) -> Result<Option<&'static str>, Box<dyn Error>> {
+        if config.tests.is_empty() {
+            return Ok(None);
+        }
+
+        let contents = config.interpolate(&Autopkgtest::create_contents(&config.tests)?);
+
+        let path = config.path(TESTS_CONTROL_FILE);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, contents)?;
+        Sign::sign_file(config, &path)?;
+
+        Ok(Some("Successfully created debian/tests/control file"))
+    }
+
+    pub(crate) fn default() -> Vec<Self> {
+        vec![]
+    }
+
+    fn default_string_value() -> String {
+        "".to_string()
+    }
+
+    fn default_vec_value() -> Vec<String> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_contents() {
+        let fake_tests: Vec<Autopkgtest> = serde_json::from_str(
+            r#"[{"tests": ["smoke", "upgrade"], "depends": "@, curl", "restrictions": ["needs-root", "allow-stderr"]},
+                {"test_command": "foo --version", "depends": "@"}]"#,
+        )
+        .unwrap();
+
+        let actual = Autopkgtest::create_contents(&fake_tests).unwrap();
+
+        assert_eq!(
+            actual,
+            "Tests: smoke, upgrade
+Depends: @, curl
+Restrictions: needs-root, allow-stderr
+
+Test-Command: foo --version
+Depends: @
+"
+        );
+    }
+
+    #[test]
+    fn test_create_contents_invalid_stanza() {
+        let fake_tests: Vec<Autopkgtest> = serde_json::from_str(
+            r#"[{"tests": ["smoke"]}, {"tests": ["smoke"], "testCommand": "true"}, {"depends": "@"}]"#,
+        )
+        .unwrap();
+
+        let actual = Autopkgtest::create_contents(&fake_tests);
+
+        assert!(matches!(actual, Err(DebyError::InvalidTestStanza(1))));
+    }
+}
@@ -0,0 +1,313 @@
+use serde_json::{json, Value};
+
+use super::{validate, Config};
+use crate::DebyError;
+
+/// Builds configuration in code, without any config file on disk.
+/// Setters use the same values as `.debyrc`, they are validated by `build`
+///
+/// Setting any _changelog_ or _control_ value enables update of the corresponding file,
+/// use `update_changelog` and `update_control` to turn it off again
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    value: Value,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self { value: json!({}) }
+    }
+
+    /// Returns `changelog` section, creates it with required settings if it's missing
+    fn changelog(&mut self) -> &mut Value {
+        self.value
+            .as_object_mut()
+            .unwrap()
+            .entry("changelog")
+            .or_insert_with(
+                || json!({"update": true, "package": "", "maintainer": {"name": "", "email": ""}}),
+            )
+    }
+
+    /// Returns `control` section, creates it with required settings if it's missing
+    fn control(&mut self) -> &mut Value {
+        self.value.as_object_mut().unwrap().entry("control").or_insert_with(|| {
+            json!({
+                "update": true,
+                "sourceControl": {"maintainer": {"name": "", "email": ""}, "priority": "optional"},
+                "binaryControl": []
+            })
+        })
+    }
+
+    /// Sets changelog package name and source package name
+    ///
+    /// # Arguments
+    ///
+    /// - `package` - source package name
+    pub fn package(mut self, package: &str) -> Self {
+        self.changelog()["package"] = json!(package);
+        self.control()["sourceControl"]["source"] = json!(package);
+        self
+    }
+
+    /// Sets maintainer of changelog entries and source package
+    ///
+    /// # Arguments
+    ///
+    /// - `name` - maintainer name
+    /// - `email` - maintainer email
+    pub fn maintainer(mut self, name: &str, email: &str) -> Self {
+        let maintainer = json!({"name": name, "email": email});
+        self.changelog()["maintainer"] = maintainer.clone();
+        self.control()["sourceControl"]["maintainer"] = maintainer;
+        self
+    }
+
+    /// Adds an uploader of source package
+    ///
+    /// # Arguments
+    ///
+    /// - `name` - uploader name
+    /// - `email` - uploader email
+    pub fn uploader(mut self, name: &str, email: &str) -> Self {
+        let uploader = json!({"name": name, "email": email});
+        let source = &mut self.control()["sourceControl"];
+        match source["uploaders"].as_array_mut() {
+            Some(uploaders) => uploaders.push(uploader),
+            None => source["uploaders"] = json!([uploader]),
+        }
+        self
+    }
+
+    /// Sets distribution of changelog entries
+    ///
+    /// # Arguments
+    ///
+    /// - `distribution` - distribution, e.g. `unstable`
+    pub fn distribution(mut self, distribution: &str) -> Self {
+        self.changelog()["distribution"] = json!(distribution);
+        self
+    }
+
+    /// Sets urgency of changelog entries
+    ///
+    /// # Arguments
+    ///
+    /// - `urgency` - urgency, e.g. `low`
+    pub fn urgency(mut self, urgency: &str) -> Self {
+        self.changelog()["urgency"] = json!(urgency);
+        self
+    }
+
+    /// Sets section of source package
+    ///
+    /// # Arguments
+    ///
+    /// - `section` - section, e.g. `utils`
+    pub fn section(mut self, section: &str) -> Self {
+        self.control()["sourceControl"]["section"] = json!(section);
+        self
+    }
+
+    /// Sets priority of source package
+    ///
+    /// # Arguments
+    ///
+    /// - `priority` - priority, e.g. `optional`
+    pub fn priority(mut self, priority: &str) -> Self {
+        self.control()["sourceControl"]["priority"] = json!(priority);
+        self
+    }
+
+    /// Sets build dependencies of source package
+    ///
+    /// # Arguments
+    ///
+    /// - `build_depends` - build dependencies, e.g. `debhelper (>= 12)`
+    pub fn build_depends(mut self, build_depends: &[&str]) -> Self {
+        self.control()["sourceControl"]["buildDepends"] = json!(build_depends);
+        self
+    }
+
+    /// Sets standards version of source package
+    ///
+    /// # Arguments
+    ///
+    /// - `standards_version` - Debian policy version, e.g. `4.5.1`
+    pub fn standards_version(mut self, standards_version: &str) -> Self {
+        self.control()["sourceControl"]["standardsVersion"] = json!(standards_version);
+        self
+    }
+
+    /// Sets homepage of source package
+    ///
+    /// # Arguments
+    ///
+    /// - `homepage` - homepage URL
+    pub fn homepage(mut self, homepage: &str) -> Self {
+        self.control()["sourceControl"]["homepage"] = json!(homepage);
+        self
+    }
+
+    /// Sets VCS browser URL of source package
+    ///
+    /// # Arguments
+    ///
+    /// - `vcs_browser` - repository URL
+    pub fn vcs_browser(mut self, vcs_browser: &str) -> Self {
+        self.control()["sourceControl"]["vcsBrowser"] = json!(vcs_browser);
+        self
+    }
+
+    /// Adds a binary package with `optional` priority
+    ///
+    /// # Arguments
+    ///
+    /// - `package` - binary package name
+    /// - `description` - binary package description
+    /// - `section` - binary package section
+    /// - `architecture` - binary package architecture, e.g. `any`
+    pub fn binary(
+        mut self,
+        package: &str,
+        description: &str,
+        section: &str,
+        architecture: &str,
+    ) -> Self {
+        let binary = json!({
+            "package": package,
+            "description": description,
+            "section": section,
+            "priority": "optional",
+            "architecture": architecture,
+        });
+        if let Some(binaries) = self.control()["binaryControl"].as_array_mut() {
+            binaries.push(binary);
+        }
+        self
+    }
+
+    /// Enables or disables update of _changelog_ file
+    ///
+    /// # Arguments
+    ///
+    /// - `update` - update _changelog_ file
+    pub fn update_changelog(mut self, update: bool) -> Self {
+        self.changelog()["update"] = json!(update);
+        self
+    }
+
+    /// Enables or disables update of _control_ file
+    ///
+    /// # Arguments
+    ///
+    /// - `update` - update _control_ file
+    pub fn update_control(mut self, update: bool) -> Self {
+        self.control()["update"] = json!(update);
+        self
+    }
+
+    /// Adds a value for `{context.<key>}` placeholders
+    ///
+    /// # Arguments
+    ///
+    /// - `key` - context key
+    /// - `value` - context value
+    pub fn context(mut self, key: &str, value: &str) -> Self {
+        let context = self
+            .value
+            .as_object_mut()
+            .unwrap()
+            .entry("context")
+            .or_insert_with(|| json!({}));
+        context[key] = json!(value);
+        self
+    }
+
+    /// Validates settings and creates configuration, package name and maintainer
+    /// are required once any _changelog_ or _control_ value is set
+    pub fn build(self) -> Result<Config, DebyError> {
+        let config = Config::from_value(self.value.clone())?;
+
+        let issues = validate::check(&self.value);
+        if !issues.is_empty() {
+            return Err(DebyError::ConfigInvalid(issues));
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let actual = ConfigBuilder::new()
+            .package("foo")
+            .maintainer("John Doe", "john@example.com")
+            .uploader("Jane Doe", "jane@example.com")
+            .urgency("high")
+            .build_depends(&["debhelper (>= 12)"])
+            .binary("foo", "foo tool", "utils", "any")
+            .context("buildId", "42")
+            .build()
+            .unwrap();
+
+        assert_eq!(actual.changelog.package(), "foo");
+        assert!(actual.changelog.is_enabled());
+        assert_eq!(actual.control.source_name(), "foo");
+        assert_eq!(actual.control.binary_names(), vec!["foo"]);
+        assert_eq!(actual.interpolate("{context.buildId}"), "42");
+    }
+
+    #[test]
+    fn test_build_changelog_only() {
+        let actual = ConfigBuilder::new()
+            .package("foo")
+            .maintainer("John Doe", "john@example.com")
+            .update_control(false)
+            .build()
+            .unwrap();
+
+        assert!(actual.changelog.is_enabled());
+        assert!(!actual.control.is_enabled());
+    }
+
+    #[test]
+    fn test_build_missing_maintainer() {
+        let actual = ConfigBuilder::new().package("foo").build();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigInvalid(issues)) if issues[0].path == "changelog.maintainer.name"
+        ));
+
+        let actual = ConfigBuilder::new()
+            .maintainer("John Doe", "john@example.com")
+            .build();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigInvalid(issues)) if issues[0].path == "changelog.package"
+        ));
+    }
+
+    #[test]
+    fn test_build_invalid_value() {
+        let actual = ConfigBuilder::new().urgency("whenever").build();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigParse(e)) if e.path == "changelog.urgency"
+        ));
+    }
+}
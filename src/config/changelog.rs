@@ -1,46 +1,407 @@
 use chrono::prelude::*;
-use serde::Deserialize;
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::de::value::SeqAccessDeserializer;
+use serde::de::{SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
 
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::env;
 use std::error::Error;
 use std::fmt::Display;
 use std::fs;
-use std::fs::OpenOptions;
+#[cfg(feature = "dpkg-verify")]
+use std::io::ErrorKind;
+#[cfg(feature = "dpkg-verify")]
 use std::io::Write;
+use std::path::PathBuf;
+#[cfg(feature = "dpkg-verify")]
+use std::process::{Command, Stdio};
+#[cfg(feature = "dpkg-verify")]
+use std::thread;
 
-use super::{Config, Maintainer};
+use super::{encoding, git, markdown, wrap, Config, Maintainer, Sign};
+use crate::changelog::{entry_offsets, parse_str, split_at_cutoffs, write_rotated, Cutoff};
+use crate::version;
+use crate::DebyError;
+
+pub(crate) const CHANGELOG_FILE: &str = "debian/changelog";
+/// English day names required by Debian policy regardless of locale
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+/// English month names required by Debian policy regardless of locale
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct Changelog {
     update: bool,
     package: String,
-    #[serde(default = "Changelog::default_distribution")]
-    distribution: Distribution,
+    #[serde(default = "Changelog::default_vendor")]
+    vendor: Vendor,
+    #[serde(
+        default = "Changelog::default_distribution",
+        deserialize_with = "Changelog::deserialize_distribution"
+    )]
+    distribution: Vec<String>,
+    #[serde(
+        rename(deserialize = "allowedDistributions"),
+        alias = "allowed_distributions",
+        default = "Changelog::default_allowed_distributions"
+    )]
+    allowed_distributions: Vec<String>,
     #[serde(default = "Changelog::default_urgency")]
     urgency: Urgency,
+    #[serde(default = "Changelog::default_timezone")]
+    timezone: String,
+    #[serde(
+        default = "Changelog::default_maintainer",
+        deserialize_with = "Changelog::deserialize_maintainer"
+    )]
     maintainer: Maintainer,
+    #[serde(
+        rename(deserialize = "maintainerFromGit"),
+        alias = "maintainer_from_git",
+        default = "Changelog::default_maintainer_from_git"
+    )]
+    maintainer_from_git: bool,
+    #[serde(
+        rename(deserialize = "allowEmptyChanges"),
+        alias = "allow_empty_changes",
+        default = "Changelog::default_allow_empty_changes"
+    )]
+    allow_empty_changes: bool,
+    #[serde(
+        rename(deserialize = "maxLineWidth"),
+        alias = "max_line_width",
+        default = "Changelog::default_max_line_width"
+    )]
+    max_line_width: usize,
+    #[serde(
+        rename(deserialize = "urgencyRules"),
+        alias = "urgency_rules",
+        default = "Changelog::default_urgency_rules"
+    )]
+    urgency_rules: Vec<UrgencyRule>,
+    #[serde(
+        rename(deserialize = "duplicateVersion"),
+        alias = "duplicate_version",
+        default = "Changelog::default_duplicate_version"
+    )]
+    duplicate_version: DuplicateVersion,
+    #[serde(
+        rename(deserialize = "enforceVersionOrder"),
+        alias = "enforce_version_order",
+        default = "Changelog::default_enforce_version_order"
+    )]
+    enforce_version_order: bool,
+    #[serde(
+        rename(deserialize = "closesPattern"),
+        alias = "closes_pattern",
+        default = "Changelog::default_closes_pattern"
+    )]
+    closes_pattern: Option<String>,
+    #[serde(
+        rename(deserialize = "launchpadPattern"),
+        alias = "launchpad_pattern",
+        default = "Changelog::default_launchpad_pattern"
+    )]
+    launchpad_pattern: Option<String>,
+    #[serde(
+        rename(deserialize = "ticketPattern"),
+        alias = "ticket_pattern",
+        default = "Changelog::default_ticket_pattern"
+    )]
+    ticket_pattern: Option<TicketPattern>,
+    #[serde(
+        rename(deserialize = "conventionalCommits"),
+        alias = "conventional_commits",
+        default = "Changelog::default_conventional_commits"
+    )]
+    conventional_commits: bool,
+    #[serde(
+        rename(deserialize = "commitTypes"),
+        alias = "commit_types",
+        default = "Changelog::default_commit_types"
+    )]
+    commit_types: Vec<CommitType>,
+    #[serde(
+        rename(deserialize = "markdownChangelog"),
+        alias = "markdown_changelog",
+        default = "Changelog::default_markdown_changelog"
+    )]
+    markdown_changelog: Option<String>,
+    #[serde(default = "Changelog::default_rotate")]
+    rotate: Option<Rotate>,
+    #[serde(
+        rename(deserialize = "strictLayout"),
+        alias = "strict_layout",
+        default = "Changelog::default_strict_layout"
+    )]
+    strict_layout: bool,
+}
+
+/// Options of a single _changelog_ update overriding config file settings
+#[derive(Debug, Clone, Default)]
+pub struct ChangelogOptions {
+    /// Distributions of the entry, e.g. `["unstable", "experimental"]`,
+    /// distribution from config file is used when empty
+    pub distribution: Vec<String>,
+    /// Base urgency of the entry, urgency rules can still raise it.
+    /// Urgency from config file is used for `None`
+    pub urgency: Option<Urgency>,
+    /// RFC 2822 date of the entry, current date is used for `None`
+    pub date: Option<String>,
+    /// Maintainer of the entry as `Name <email>`, maintainer from config file is used for `None`
+    pub maintainer: Option<String>,
+    /// Skip version order check enabled by `enforceVersionOrder` setting
+    pub force: bool,
+    /// Marks the entry as a binary-only upload with `binary-only=yes` flag
+    pub binary_only: bool,
+    /// Kind of the upload, special uploads derive version and distribution of the entry
+    pub upload: Upload,
+    /// Changes grouped by author under `[ Author Name ]` headers, written after other changes
+    pub authors: Vec<(String, Vec<String>)>,
+    /// Path to config file read instead of `.debyrc`, e.g. `ci/deby.json`
+    pub config_path: Option<PathBuf>,
+    /// Profile from `profiles` map of config file to be applied, e.g. `bookworm`
+    pub profile: Option<String>,
+    /// Values for `{context.<key>}` placeholders, they override `context` from config file
+    pub context: BTreeMap<String, String>,
+}
+
+/// Kind of a _changelog_ entry upload
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Upload {
+    /// Regular upload, the version is written as it's given
+    #[default]
+    Regular,
+    /// Backport to a Debian release, e.g. `12`: the version gets `~bpo12+1` suffix,
+    /// distribution defaults to backports suite of the release
+    Backport(u32),
+    /// Security upload to a Debian release, e.g. `12`: the version must end with `+deb12u<n>`,
+    /// distribution defaults to security suite of the release and urgency to `high`
+    Security(u32),
+    /// Binary-only non-maintainer upload for an architecture, e.g. `amd64`: the version
+    /// of the latest entry gets `+b<n>` suffix, the given version is ignored
+    BinNmu(String),
+    /// Snapshot of git repo: the given version is the upstream version the snapshot is based on,
+    /// e.g. `1.2.3` becomes `1.2.3+git20240511.abc1234-1`
+    Snapshot,
+}
+
+impl ChangelogOptions {
+    /// Returns version, changes and options of an entry written by these options:
+    /// authors' changes are joined to changes, version and distribution of special uploads
+    /// are derived
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - selected config, binNMU version is derived from its _changelog_ file
+    /// - `version` - version given by a user
+    /// - `changes` - changes given by a user
+    pub(crate) fn entry(
+        &self,
+        config: &Config,
+        version: &str,
+        changes: &str,
+    ) -> Result<(String, String, ChangelogOptions), DebyError> {
+        let mut options = self.clone();
+        let mut changes = changes.to_string();
+        if !self.authors.is_empty() {
+            if !changes.trim().is_empty() {
+                changes = format!("{}\n", changes.trim_end());
+            }
+            changes.push_str(&Changelog::join_author_changes(&self.authors));
+        }
+
+        let version = match &self.upload {
+            Upload::Regular => version.to_string(),
+            Upload::Backport(release) => {
+                let (version, distribution) = version::backport(version, *release)?;
+                if options.distribution.is_empty() {
+                    options.distribution = vec![distribution];
+                }
+                version
+            }
+            Upload::Security(release) => {
+                let distribution = version::security(version, *release)?;
+                if options.distribution.is_empty() {
+                    options.distribution = vec![distribution];
+                }
+                options.urgency = options.urgency.or(Some(Urgency::High));
+                version.to_string()
+            }
+            Upload::BinNmu(architecture) => {
+                changes = format!(
+                    "Binary-only non-maintainer upload for {}; no source changes.\n{}",
+                    architecture, changes
+                );
+                options.binary_only = true;
+                Changelog::binnmu_version(config, version)?
+            }
+            Upload::Snapshot => version::snapshot(version)?,
+        };
+
+        Ok((version, changes, options))
+    }
+}
+
+/// Maintainer of _changelog_ entries, missing values are taken
+/// from `DEBFULLNAME` and `DEBEMAIL` environment variables
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ChangelogMaintainer {
+    name: String,
+    email: String,
+}
+
+/// Visits `distribution` value, a single distribution or an array of distributions
+struct DistributionVisitor;
+
+impl<'de> Visitor<'de> for DistributionVisitor {
+    type Value = Vec<String>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a distribution or an array of distributions")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(vec![v.to_string()])
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        Vec::deserialize(SeqAccessDeserializer::new(seq))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct UrgencyRule {
+    pattern: String,
+    urgency: Urgency,
+}
+
+#[derive(Deserialize, Debug)]
+struct TicketPattern {
+    pattern: String,
+    /// Replacement of a matched ticket id, `$1` refers to the first capture group
+    replacement: String,
+}
+
+/// Limits of _changelog_ file, older entries are moved into `debian/changelog.old` on update
+#[derive(Deserialize, Debug)]
+struct Rotate {
+    #[serde(rename(deserialize = "keepEntries"), alias = "keep_entries", default)]
+    keep_entries: Option<usize>,
+    /// Entries dated before this `YYYY-MM-DD` date are archived
+    #[serde(default)]
+    before: Option<String>,
+    #[serde(default)]
+    compress: bool,
+}
+
+impl Rotate {
+    /// Returns cutoffs of all limits set
+    fn cutoffs(&self) -> Result<Vec<Cutoff>, DebyError> {
+        let mut cutoffs = vec![];
+        if let Some(n) = self.keep_entries {
+            // the new entry is always kept
+            cutoffs.push(Cutoff::Keep(n.max(1)));
+        }
+        if let Some(before) = &self.before {
+            let date = NaiveDate::parse_from_str(before, "%Y-%m-%d")
+                .map_err(|_| DebyError::InvalidDate(before.clone()))?;
+            cutoffs.push(Cutoff::Before(date));
+        }
+
+        Ok(cutoffs)
+    }
+}
+
+/// Timezone of `timezone` setting
+pub(crate) enum Timezone {
+    Local,
+    Fixed(FixedOffset),
+    /// IANA timezone name, e.g. `Europe/Berlin`, its offset depends on a date
+    Named(Tz),
+}
+
+impl Timezone {
+    /// Returns current date and time in the timezone
+    fn now(&self) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Local => Local::now().fixed_offset(),
+            Timezone::Fixed(offset) => Utc::now().with_timezone(offset),
+            Timezone::Named(tz) => Utc::now().with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// Returns a local date and time in the timezone, `None` if it doesn't exist there
+    ///
+    /// # Arguments
+    ///
+    /// - `local` - local date and time
+    fn at(&self, local: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Timezone::Local => Local
+                .from_local_datetime(local)
+                .earliest()
+                .map(|d| d.fixed_offset()),
+            Timezone::Fixed(offset) => offset.from_local_datetime(local).single(),
+            Timezone::Named(tz) => tz
+                .from_local_datetime(local)
+                .earliest()
+                .map(|d| d.fixed_offset()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CommitType {
+    #[serde(rename(deserialize = "type"))]
+    commit_type: String,
+    /// Heading of a section, commits of a type without a section are excluded
+    section: Option<String>,
+}
+
+impl CommitType {
+    fn new(commit_type: &str, section: &str) -> Self {
+        Self {
+            commit_type: commit_type.to_string(),
+            section: Some(section.to_string()),
+        }
+    }
 }
 
+/// Section of commits which types are not listed in `commitTypes`
+const OTHER_CHANGES: &str = "Other changes";
+
 impl Changelog {
     /// Formats contents of _changelog_ file.
-    /// Newer entries will go first
+    /// Newer entries will go first, previous entries are kept byte for byte.
+    /// Only a missing final newline is added to previous entries
     ///
     /// # Arguments
     ///
     /// - `entry`- a single _changelog_ entry to be added to _changelog_ file
     /// - `current_file_contents` - previous entries of _changelog_ file
     fn format_contents(entry: &str, current_file_contents: &str) -> String {
-        let contents = format!(
-            "
-{entry}
+        if current_file_contents.is_empty() {
+            let mut s = entry.trim().to_string();
+            s.push('\n');
+            return s;
+        }
 
-{current}
-",
-            entry = entry,
+        let mut s = format!(
+            "{entry}\n\n{current}",
+            entry = entry.trim_start(),
             current = current_file_contents
         );
 
-        let mut s = contents.trim().to_string();
-        s.push('\n');
+        if !s.ends_with('\n') {
+            s.push('\n');
+        }
         s
     }
 
@@ -51,209 +412,2065 @@ impl Changelog {
     /// - `config` - data from config file `.debyrc`
     /// - `version` - version string value to be included in _changelog_ entry
     /// - `changes` - changes string value to be included in _changelog_ entry
-    fn format_changelog_entry(config: &Config, version: &str, changes: &str) -> String {
-        let date = Changelog::format_date();
-
+    /// - `urgency` - urgency of _changelog_ entry
+    /// - `distribution` - distribution of _changelog_ entry
+    /// - `options` - options of the update
+    fn format_changelog_entry(
+        config: &Config,
+        version: &str,
+        changes: &str,
+        urgency: Urgency,
+        distribution: &str,
+        options: &ChangelogOptions,
+    ) -> Result<String, DebyError> {
         let contents = format!(
             "
-{package} ({version}) {distribution}; urgency={urgency}
+{package} ({version}) {distribution}; urgency={urgency}{flags}
 
   {changes}
 
- -- {name} <{email}>  {date}",
+{trailer}",
             package = config.changelog.package,
-            email = config.changelog.maintainer.email,
-            name = config.changelog.maintainer.name,
-            distribution = config.changelog.distribution,
-            urgency = config.changelog.urgency,
-            date = date,
+            distribution = distribution,
+            urgency = urgency,
+            flags = if options.binary_only {
+                ", binary-only=yes"
+            } else {
+                ""
+            },
+            trailer = Changelog::format_trailer(config, options)?,
             version = version,
             changes = changes,
         );
 
-        contents
+        Ok(contents)
+    }
+
+    /// Brings a formatted entry to the exact layout `dpkg-parsechangelog` expects and checks it:
+    /// header line, blank line, change lines indented two spaces, blank line
+    /// and trailer line ` -- Name <email>  date`. Tabs are replaced with spaces
+    /// and trailing whitespace is removed
+    ///
+    /// # Arguments
+    ///
+    /// - `entry` - formatted _changelog_ entry
+    fn strict_layout(entry: &str) -> Result<String, DebyError> {
+        let lines: Vec<String> = entry
+            .trim()
+            .lines()
+            .map(|l| l.replace('\t', "  ").trim_end().to_string())
+            .collect();
+
+        let header = Regex::new(r"^[a-z0-9][a-z0-9+.-]+ \([^()\s]+\)( [^\s;]+)+; \S.*$").unwrap();
+        let trailer = Regex::new(r"^ -- [^<>\s][^<>]* <[^<>\s]+>  \S.*$").unwrap();
+        let invalid = |line: &str| Err(DebyError::InvalidLayout(line.to_string()));
+
+        let (first, rest) = match lines.split_first() {
+            Some(l) => l,
+            None => return invalid(""),
+        };
+        if !header.is_match(first) {
+            return invalid(first);
+        }
+        let (last, body) = match rest.split_last() {
+            Some(l) => l,
+            None => return invalid(first),
+        };
+        if !trailer.is_match(last) {
+            return invalid(last);
+        }
+        let changes = match body {
+            [blank, changes @ .., end] if blank.is_empty() && end.is_empty() => changes,
+            _ => return invalid(first),
+        };
+        if let Some(line) = changes
+            .iter()
+            .find(|l| !l.is_empty() && !l.starts_with("  "))
+        {
+            return invalid(line);
+        }
+
+        Ok(format!("\n{}", lines.join("\n")))
+    }
+
+    /// Formats trailer line of a _changelog_ entry, maintainer and date of options take precedence
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `options` - options of the update
+    fn format_trailer(config: &Config, options: &ChangelogOptions) -> Result<String, DebyError> {
+        let maintainer = match &options.maintainer {
+            Some(m) => m.clone(),
+            None => {
+                let (env_name, env_email) = Changelog::env_maintainer(|k| env::var(k).ok());
+                let from_git = |key| match config.changelog.maintainer_from_git {
+                    true => git::config_value(key),
+                    false => None,
+                };
+                let from_config = |value: &String| Some(value.clone()).filter(|v| !v.is_empty());
+
+                let name = from_git("user.name")
+                    .or_else(|| from_config(&config.changelog.maintainer.name))
+                    .unwrap_or(env_name);
+                let email = from_git("user.email")
+                    .or_else(|| from_config(&config.changelog.maintainer.email))
+                    .unwrap_or(env_email);
+                if name.is_empty() || email.is_empty() {
+                    return Err(DebyError::MaintainerMissing);
+                }
+                format!("{} <{}>", name, email)
+            }
+        };
+        let date = match &options.date {
+            Some(d) => d.clone(),
+            None => Changelog::format_date(config),
+        };
+
+        Ok(format!(" -- {}  {}", maintainer, date))
+    }
+
+    /// Returns maintainer name and email from environment variables the way devscripts does:
+    /// `DEBFULLNAME` or `NAME` for a name, `DEBEMAIL` or `EMAIL` for an email.
+    /// `DEBEMAIL` can also be `Name <email>`
+    ///
+    /// # Arguments
+    ///
+    /// - `var` - returns a value of environment variable
+    fn env_maintainer(var: impl Fn(&str) -> Option<String>) -> (String, String) {
+        let non_empty = |k: &str| var(k).filter(|v| !v.trim().is_empty());
+        let mut name = non_empty("DEBFULLNAME").or_else(|| non_empty("NAME"));
+        let mut email = non_empty("DEBEMAIL").or_else(|| non_empty("EMAIL"));
+
+        let re = Regex::new(r"^\s*(.*?)\s*<([^<>]+)>\s*$").unwrap();
+        if let Some(caps) = email.as_deref().and_then(|e| re.captures(e)) {
+            if name.is_none() && !caps[1].is_empty() {
+                name = Some(caps[1].to_string());
+            }
+            email = Some(caps[2].to_string());
+        }
+
+        (
+            name.unwrap_or_default().trim().to_string(),
+            email.unwrap_or_default().trim().to_string(),
+        )
+    }
+
+    /// Checks maintainer and date overrides of options
+    ///
+    /// # Arguments
+    ///
+    /// - `options` - options of the update
+    fn check_options(options: &ChangelogOptions) -> Result<(), DebyError> {
+        if let Some(date) = &options.date {
+            Changelog::check_date(date)?;
+        }
+        if let Some(maintainer) = &options.maintainer {
+            let re = Regex::new(r"^[^<>]+ <[^<>\s]+@[^<>\s]+>$").unwrap();
+            if !re.is_match(maintainer) {
+                return Err(DebyError::InvalidMaintainer(maintainer.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a line of changes is an author header like `[ Jane Doe ]`
+    ///
+    /// # Arguments
+    ///
+    /// - `line` - line of changes
+    fn is_author_header(line: &str) -> bool {
+        let line = line.trim();
+        line.len() > 4 && line.starts_with("[ ") && line.ends_with(" ]")
+    }
+
+    /// Formats a single line of changes, author headers are kept as is
+    ///
+    /// # Arguments
+    ///
+    /// - `line` - line of changes
+    /// - `width` - max width of _changelog_ lines, `0` disables wrapping
+    fn format_change_line(line: &str, width: usize) -> String {
+        if Changelog::is_author_header(line) {
+            return line.trim().to_string();
+        }
+        match line.strip_prefix("  - ") {
+            Some(item) => format!("  - {}", Changelog::wrap_change(item, width, 6)),
+            None => format!("* {}", Changelog::wrap_change(line, width, 4)),
+        }
+    }
+
+    /// Wraps a change at word boundaries, continuation lines are indented
+    /// to line up under the text after `*` or `-`. Words longer than a line are kept whole
+    ///
+    /// # Arguments
+    ///
+    /// - `change` - a single change
+    /// - `width` - max width of _changelog_ lines, `0` disables wrapping
+    /// - `indent` - column where text of the change starts
+    fn wrap_change(change: &str, width: usize, indent: usize) -> String {
+        if width == 0 {
+            return change.to_string();
+        }
+        let lines = wrap::fill(change, width.saturating_sub(indent));
+
+        lines.join(&format!("\n{}", " ".repeat(indent)))
     }
 
-    /// Formats changes section
+    /// Formats changes section, author headers like `[ Jane Doe ]` start a new group of changes
     ///
     /// # Arguments
     ///
     /// - `changes` - string value of changes
-    fn format_changes(changes: &str) -> String {
+    /// - `width` - max width of _changelog_ lines, `0` disables wrapping
+    fn format_changes(changes: &str, width: usize) -> String {
         if changes.is_empty() {
             return "".to_string();
         }
         let mut formatted_changes = String::new();
         for line in changes.lines() {
-            formatted_changes.push_str(&format!("  * {}\n", line));
+            if Changelog::is_author_header(line) && !formatted_changes.is_empty() {
+                formatted_changes.push('\n');
+            }
+            formatted_changes.push_str(&format!(
+                "  {}\n",
+                Changelog::format_change_line(line, width)
+            ));
         }
 
         formatted_changes.trim().to_string()
     }
 
-    /// Formats current date value according to RFC 2822
-    fn format_date() -> String {
-        let dt = Local::now();
-        dt.to_rfc2822()
+    /// Joins changes grouped by author into changes with author headers
+    ///
+    /// # Arguments
+    ///
+    /// - `changes` - author names with their changes
+    fn join_author_changes(changes: &[(String, Vec<String>)]) -> String {
+        let mut joined = vec![];
+        for (author, author_changes) in changes {
+            joined.push(format!("[ {} ]", author.trim()));
+            joined.extend(author_changes.iter().map(|c| c.to_string()));
+        }
+
+        joined.join("\n")
     }
 
-    /// Updates _changelog_ file and writes its contents to `debian/changelog` file
+    /// Returns the highest urgency of base urgency and urgency rules matching changes
     ///
     /// # Arguments
     ///
     /// - `config` - data from config file `.debyrc`
-    /// - `version` - version string to be included in _changelog_ file
-    /// - `changes` - changes string value to be included in _changelog_ file
-    pub(crate) fn update<'a>(
+    /// - `changes` - string value of changes
+    /// - `urgency` - base urgency
+    fn escalate_urgency(
         config: &Config,
-        version: &str,
         changes: &str,
-    ) -> Result<&'a str, Box<dyn Error>> {
-        if !config.changelog.update {
-            return Ok("debian/changelog file not updated due to config file setting");
+        mut urgency: Urgency,
+    ) -> Result<Urgency, DebyError> {
+        for rule in &config.changelog.urgency_rules {
+            let re = Regex::new(&rule.pattern)
+                .map_err(|_| DebyError::InvalidPattern(rule.pattern.clone()))?;
+            if re.is_match(changes) && rule.urgency.rank() > urgency.rank() {
+                urgency = rule.urgency;
+            }
         }
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open("debian/changelog")?;
-
-        let current_file = fs::read_to_string("debian/changelog")?;
+        Ok(urgency)
+    }
 
-        let formatted_changes = Changelog::format_changes(changes);
-        let changelog_entry =
-            Changelog::format_changelog_entry(&config, &version, &formatted_changes);
-        let contents = Changelog::format_contents(&changelog_entry, &current_file);
+    /// Converts markdown lists into plain change lines: bullets and numbers of list items are stripped,
+    /// nested items become separate changes and links `[text](url)` become `text (url)`.
+    /// Blank lines are dropped, author headers are kept as is
+    ///
+    /// # Arguments
+    ///
+    /// - `changes` - string value of changes
+    fn normalize_markdown(changes: &str) -> String {
+        let bullet = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+").unwrap();
 
-        file.write_all(contents.as_bytes())?;
+        let lines: Vec<String> = changes
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|line| {
+                if Changelog::is_author_header(line) {
+                    return line.trim().to_string();
+                }
+                markdown::collapse_links(bullet.replace(line, "").trim())
+            })
+            .filter(|l| !l.is_empty())
+            .collect();
 
-        Ok("Successfully created a new entry in debian/changelog file")
+        lines.join("\n")
     }
 
-    pub(crate) fn default() -> Self {
-        Self {
-            update: false,
-            package: "".to_string(),
-            distribution: Distribution::Unstable,
-            urgency: Urgency::Low,
-            maintainer: Maintainer {
-                name: "".to_string(),
-                email: "".to_string(),
-            },
-        }
-    }
+    /// Groups commit subjects like `feat(parser): add option` into sections by commit type.
+    /// Each section is a change with a heading followed by its commits, e.g. `Bug fixes:`.
+    /// Sections follow the order of `commitTypes`, subjects of unlisted types
+    /// and non-conventional subjects go to `Other changes` section
+    ///
+    /// # Arguments
+    ///
+    /// - `changes` - commit subjects, one per line
+    /// - `commit_types` - mapping of commit types to section headings
+    fn group_commits(changes: &str, commit_types: &[CommitType]) -> String {
+        let re = Regex::new(r"^(\w+)(?:\(([^)]*)\))?!?:\s*(.+)$").unwrap();
 
-    fn default_distribution() -> Distribution {
-        Distribution::Unstable
-    }
+        let mut sections: Vec<(&str, Vec<String>)> = vec![];
+        for line in changes.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (section, subject) = match re.captures(line) {
+                Some(caps) => {
+                    let commit_type = caps[1].to_lowercase();
+                    let subject = match caps.get(2).filter(|s| !s.as_str().is_empty()) {
+                        Some(scope) => format!("{}: {}", scope.as_str(), &caps[3]),
+                        None => caps[3].to_string(),
+                    };
+                    match commit_types.iter().find(|t| t.commit_type == commit_type) {
+                        Some(t) => match &t.section {
+                            Some(section) => (section.as_str(), subject),
+                            None => continue,
+                        },
+                        None => (OTHER_CHANGES, subject),
+                    }
+                }
+                None => (OTHER_CHANGES, line.to_string()),
+            };
 
-    fn default_urgency() -> Urgency {
-        Urgency::Low
-    }
-}
+            match sections.iter_mut().find(|(s, _)| *s == section) {
+                Some((_, subjects)) => subjects.push(subject),
+                None => sections.push((section, vec![subject])),
+            }
+        }
 
-#[derive(Deserialize, Debug, PartialEq)]
-enum Urgency {
-    #[serde(rename(deserialize = "low"))]
-    Low,
-    #[serde(rename(deserialize = "medium"))]
-    Medium,
-    #[serde(rename(deserialize = "high"))]
-    High,
-    #[serde(rename(deserialize = "emergency"))]
-    Emergency,
-    #[serde(rename(deserialize = "critical"))]
-    Critical,
-}
+        let order = |section: &str| {
+            commit_types
+                .iter()
+                .position(|t| t.section.as_deref() == Some(section))
+                .unwrap_or(commit_types.len())
+        };
+        sections.sort_by_key(|(section, _)| order(section));
 
-impl Display for Urgency {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Urgency::Low => write!(f, "low"),
-            Urgency::Medium => write!(f, "medium"),
-            Urgency::High => write!(f, "high"),
-            Urgency::Emergency => write!(f, "emergency"),
-            Urgency::Critical => write!(f, "critical"),
+        let mut lines = vec![];
+        for (section, subjects) in sections {
+            lines.push(format!("{}:", section));
+            lines.extend(subjects.iter().map(|s| format!("  - {}", s)));
         }
+
+        lines.join("\n")
     }
-}
 
-#[derive(Deserialize, Debug, PartialEq)]
-enum Distribution {
-    #[serde(rename(deserialize = "unstable"))]
-    Unstable,
-    #[serde(rename(deserialize = "experimental"))]
-    Experimental,
-}
+    /// Rewrites issue tracker ids in changes, e.g. `proj-42` into `PROJ-42` or a URL
+    ///
+    /// # Arguments
+    ///
+    /// - `changes` - string value of changes
+    /// - `ticket` - pattern of ticket ids and its replacement
+    fn rewrite_tickets(changes: &str, ticket: &TicketPattern) -> Result<String, DebyError> {
+        let re = Regex::new(&ticket.pattern)
+            .map_err(|_| DebyError::InvalidPattern(ticket.pattern.clone()))?;
 
-impl Display for Distribution {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Distribution::Unstable => write!(f, "unstable"),
-            Distribution::Experimental => write!(f, "experimental"),
-        }
+        Ok(re
+            .replace_all(changes, ticket.replacement.as_str())
+            .to_string())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    #[test]
-    fn test_default() {
-        let default = Changelog::default();
-        let empty_str = String::new();
+    /// Appends bug references found in a change line by a pattern, e.g. `(Closes: #12345)`.
+    /// The first capture group of the pattern is a bug number, existing `Closes:` and `LP:` references
+    /// are never matched, lines which already have references with the same label are kept as is
+    ///
+    /// # Arguments
+    ///
+    /// - `changes` - string value of changes
+    /// - `pattern` - regular expression of a bug reference, e.g. `(?i)(?:fixes |#)(\d+)`
+    /// - `label` - label of references, e.g. `Closes`
+    fn add_references(changes: &str, pattern: &str, label: &str) -> Result<String, DebyError> {
+        let re = Regex::new(pattern).map_err(|_| DebyError::InvalidPattern(pattern.to_string()))?;
+        let existing = format!("{}:", label);
+        let references =
+            Regex::new(r"(?i)\b(?:closes|lp):\s*(?:bug)?#?\d+(?:,\s*(?:bug)?#?\d+)*").unwrap();
 
-        assert_eq!(default.update, false);
+        let lines: Vec<String> = changes
+            .lines()
+            .map(|line| {
+                let unreferenced = references.replace_all(line, "");
+                let bugs: Vec<String> = re
+                    .captures_iter(&unreferenced)
+                    .filter_map(|c| c.get(1))
+                    .map(|m| format!("#{}", m.as_str()))
+                    .collect();
+                match bugs.is_empty() || line.contains(&existing) {
+                    true => line.to_string(),
+                    false => format!("{} ({} {})", line.trim_end(), existing, bugs.join(", ")),
+                }
+            })
+            .collect();
 
-        assert_eq!(default.package, empty_str);
-        assert_eq!(default.distribution, Distribution::Unstable);
-        assert_eq!(default.urgency, Urgency::Low);
-        assert_eq!(default.maintainer.name, empty_str);
-        assert_eq!(default.maintainer.email, empty_str);
+        Ok(lines.join("\n"))
     }
 
-    #[test]
-    fn test_format_contents() {
-        let fake_entry = "entry";
-        let fake_current_file = "current file contents";
-        let actual = Changelog::format_contents(fake_entry, fake_current_file);
+    /// Checks that changes contain at least one non-whitespace line
+    ///
+    /// # Arguments
+    ///
+    /// - `changes` - string value of changes
+    fn has_changes(changes: &str) -> bool {
+        changes.lines().any(|line| !line.trim().is_empty())
+    }
 
-        let expected = format!(
-            "{entry}
+    /// Adds formatted change lines to the end of changes of an existing entry,
+    /// header line of the entry is kept. Changes of an entry without a trailer line
+    /// are added to the end of the entry
+    ///
+    /// # Arguments
+    ///
+    /// - `current_file_contents` - contents of _changelog_ file
+    /// - `index` - index of the entry, newer entries go first
+    /// - `added` - formatted change lines, each one starts with a new line
+    /// - `trailer` - new trailer line, the existing one is kept for `None`
+    fn insert_changes(
+        current_file_contents: &str,
+        index: usize,
+        added: &str,
+        trailer: Option<&str>,
+    ) -> String {
+        let offsets = entry_offsets(current_file_contents);
+        let start = offsets[index];
+        let end = offsets
+            .get(index + 1)
+            .copied()
+            .unwrap_or(current_file_contents.len());
+        let entry = &current_file_contents[start..end];
 
-{current}
-",
-            entry = fake_entry,
-            current = fake_current_file
-        );
+        let trailer_start = entry.rfind("\n -- ").unwrap_or(entry.len());
+        let current_trailer = &entry[trailer_start..];
+        let separator = match end < current_file_contents.len() {
+            true => "\n",
+            false => "",
+        };
+        let trailer = match trailer {
+            Some(t) if current_trailer.is_empty() => format!("\n{}\n{}", t, separator),
+            None if current_trailer.is_empty() => separator.to_string(),
+            Some(t) => {
+                let line_end = current_trailer[1..]
+                    .find('\n')
+                    .map(|i| i + 1)
+                    .unwrap_or(current_trailer.len());
+                format!("\n{}{}", t, &current_trailer[line_end..])
+            }
+            None => current_trailer.to_string(),
+        };
 
-        assert_eq!(actual, expected);
+        format!(
+            "{before}{changes_before}{added}\n{trailer}{after}",
+            before = &current_file_contents[..start],
+            changes_before = entry[..trailer_start].trim_end(),
+            added = added,
+            trailer = trailer,
+            after = &current_file_contents[end..],
+        )
     }
 
-    #[test]
-    fn test_format_contents_whitespace_new_line() {
-        let fake_entry = "entry     \n\n";
-        let fake_current_file = "current file contents";
-        let actual = Changelog::format_contents(fake_entry, fake_current_file);
-
-        let expected = format!(
-            "{entry}
+    /// Returns changes of an entry unwrapped: continuation lines are joined to their change
+    /// and whitespace is collapsed, e.g. `* long` and `  change` lines become `* long change`
+    ///
+    /// # Arguments
+    ///
+    /// - `lines` - change lines of an entry
+    fn unwrap_changes(lines: &[String]) -> Vec<String> {
+        let mut changes: Vec<String> = vec![];
+        for line in lines {
+            let text = line.trim();
+            let starts_change = text.starts_with("* ")
+                || text.starts_with("- ")
+                || Changelog::is_author_header(text);
+            match changes.last_mut() {
+                Some(change) if !starts_change => {
+                    change.push(' ');
+                    change.push_str(text);
+                }
+                _ => changes.push(text.to_string()),
+            }
+        }
 
-{current}
-",
-            entry = fake_entry,
-            current = fake_current_file
-        );
+        changes
+            .iter()
+            .map(|c| c.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .collect()
+    }
 
+    /// Adds change lines missing from an existing entry to the end of its changes, changes are
+    /// compared unwrapped, so that a rerun with the same changes adds nothing. Appending refreshes
+    /// trailer line of the entry the way `dch -a` does, merging keeps it
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `current_file_contents` - contents of _changelog_ file
+    /// - `index` - index of the entry, newer entries go first
+    /// - `changes` - string value of changes
+    /// - `options` - options of the update
+    /// - `append` - refresh trailer line of the entry
+    fn add_changes(
+        config: &Config,
+        current_file_contents: &str,
+        index: usize,
+        changes: &str,
+        options: &ChangelogOptions,
+        append: bool,
+    ) -> Result<String, DebyError> {
+        let entries = parse_str(current_file_contents);
+        let existing = Changelog::unwrap_changes(&entries[index].changes);
+
+        let mut added = String::new();
+        for line in changes.lines().filter(|l| !l.trim().is_empty()) {
+            let change = Changelog::format_change_line(line, 0);
+            let unwrapped = change.split_whitespace().collect::<Vec<&str>>().join(" ");
+            if !existing.contains(&unwrapped) {
+                let change = Changelog::format_change_line(line, config.changelog.max_line_width);
+                added.push_str(&format!("\n  {}", change));
+            }
+        }
+        let trailer = match append {
+            true => Some(Changelog::format_trailer(config, options)?),
+            false => None,
+        };
+
+        Ok(Changelog::insert_changes(
+            current_file_contents,
+            index,
+            &added,
+            trailer.as_deref(),
+        ))
+    }
+
+    /// Checks that a new version is higher than the latest version according to Debian version ordering
+    ///
+    /// # Arguments
+    ///
+    /// - `latest` - version of the latest _changelog_ entry, `None` for an empty file
+    /// - `version` - version of a new entry
+    fn check_version_order(latest: Option<&str>, version: &str) -> Result<(), DebyError> {
+        match latest {
+            Some(latest) if version::compare(version, latest) != Ordering::Greater => Err(
+                DebyError::VersionNotIncreasing(version.to_string(), latest.to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns binNMU version of the latest entry of _changelog_ file of a config,
+    /// a version given by a user must be empty or match it
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `version` - version given by a user
+    fn binnmu_version(config: &Config, version: &str) -> Result<String, DebyError> {
+        let changelog_file = config.path(CHANGELOG_FILE);
+        if !changelog_file.exists() {
+            return Err(DebyError::ChangelogMissing);
+        }
+        let contents = encoding::read_to_string(&changelog_file)
+            .map_err(|e| crate::map_error(e, DebyError::ChangelogRead))?;
+        let latest = parse_str(&contents)
+            .into_iter()
+            .next()
+            .ok_or(DebyError::ChangelogEmpty)?;
+
+        let binnmu = version::next(&latest.version, &version::Bump::BinNmu);
+        match version.is_empty() || version == binnmu {
+            true => Ok(binnmu),
+            false => Err(DebyError::InvalidVersion(
+                version.to_string(),
+                format!("binNMU of the latest entry is {}", binnmu),
+            )),
+        }
+    }
+
+    /// Returns timezone of `timezone` setting
+    fn timezone(&self) -> Result<Timezone, DebyError> {
+        Changelog::parse_timezone(&self.timezone)
+    }
+
+    /// Parses a timezone value: `local`, `UTC`, a fixed offset or an IANA name
+    ///
+    /// # Arguments
+    ///
+    /// - `timezone` - value of `timezone` setting
+    pub(crate) fn parse_timezone(timezone: &str) -> Result<Timezone, DebyError> {
+        match timezone {
+            "local" => Ok(Timezone::Local),
+            tz if tz.eq_ignore_ascii_case("utc") => {
+                Ok(Timezone::Fixed(FixedOffset::east_opt(0).unwrap()))
+            }
+            tz => tz
+                .parse::<FixedOffset>()
+                .map(Timezone::Fixed)
+                .or_else(|_| tz.parse::<Tz>().map(Timezone::Named))
+                .map_err(|_| DebyError::InvalidTimezone(tz.to_string())),
+        }
+    }
+
+    /// Formats date value according to RFC 2822 with English day and month names,
+    /// locale of a build machine is never used
+    ///
+    /// # Arguments
+    ///
+    /// - `dt` - date value
+    fn rfc2822(dt: &DateTime<FixedOffset>) -> String {
+        let offset = dt.offset().local_minus_utc() / 60;
+        format!(
+            "{}, {:02} {} {} {:02}:{:02}:{:02} {}{:02}{:02}",
+            WEEKDAYS[dt.weekday().num_days_from_monday() as usize],
+            dt.day(),
+            MONTHS[dt.month0() as usize],
+            dt.year(),
+            dt.hour(),
+            dt.minute(),
+            dt.second(),
+            if offset < 0 { '-' } else { '+' },
+            offset.abs() / 60,
+            offset.abs() % 60,
+        )
+    }
+
+    /// Formats current date value according to RFC 2822 in timezone of config file
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    fn format_date(config: &Config) -> String {
+        let now = config.changelog.timezone().unwrap_or(Timezone::Local).now();
+        Changelog::rfc2822(&now)
+    }
+
+    /// Checks that a date value is exactly in the format required by Debian policy:
+    /// `day-of-week, dd month yyyy hh:mm:ss +zzzz` with English names
+    ///
+    /// # Arguments
+    ///
+    /// - `date` - date value of _changelog_ entry
+    fn check_date(date: &str) -> Result<(), DebyError> {
+        let re = Regex::new(
+            r"^(Mon|Tue|Wed|Thu|Fri|Sat|Sun), ([1-9]|0[1-9]|[12]\d|3[01]) (Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec) \d{4} ([01]\d|2[0-3]):[0-5]\d:[0-5]\d [+-]\d{4}$",
+        )
+        .unwrap();
+        if !re.is_match(date) || DateTime::parse_from_rfc2822(date).is_err() {
+            return Err(DebyError::InvalidDate(date.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Returns date of a new _changelog_ entry, the date of options takes precedence.
+    /// The date is checked before anything is written
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `options` - options of the update
+    fn entry_date(config: &Config, options: &ChangelogOptions) -> Result<String, DebyError> {
+        config.changelog.timezone()?;
+        let date = match &options.date {
+            Some(d) => d.clone(),
+            None => Changelog::format_date(config),
+        };
+        Changelog::check_date(&date)?;
+
+        Ok(date)
+    }
+
+    /// Updates _changelog_ file and writes its contents to `debian/changelog` file
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `version` - version string to be included in _changelog_ file
+    /// - `changes` - changes string value to be included in _changelog_ file
+    /// - `options` - options of the update
+    pub(crate) fn update(
+        config: &Config,
+        version: &str,
+        changes: &str,
+        options: &ChangelogOptions,
+    ) -> Result<String, Box<dyn Error>> {
+        if !config.changelog.update {
+            return Ok("debian/changelog file not updated due to config file setting".to_string());
+        }
+
+        version::validate(version)?;
+        let distribution = match options.distribution.is_empty() {
+            true => config.changelog.distribution(),
+            false => options.distribution.join(" "),
+        };
+        config.changelog.check_distribution(&distribution)?;
+        Changelog::check_options(options)?;
+        let options = &ChangelogOptions {
+            date: Some(Changelog::entry_date(config, options)?),
+            ..options.clone()
+        };
+
+        let changes = Changelog::normalize_markdown(changes);
+        let changes = match config.changelog.conventional_commits {
+            true => Changelog::group_commits(&changes, &config.changelog.commit_types),
+            false => changes,
+        };
+        if !config.changelog.allow_empty_changes && !Changelog::has_changes(&changes) {
+            return Err(DebyError::EmptyChanges.into());
+        }
+        let changes = match &config.changelog.ticket_pattern {
+            Some(ticket) => Changelog::rewrite_tickets(&changes, ticket)?,
+            None => changes,
+        };
+        let changes = match &config.changelog.closes_pattern {
+            Some(pattern) => Changelog::add_references(&changes, pattern, "Closes")?,
+            None => changes,
+        };
+        let changes = match config.changelog.launchpad_pattern() {
+            Some(pattern) => Changelog::add_references(&changes, pattern, "LP")?,
+            None => changes,
+        };
+        let changes = changes.as_str();
+
+        let changelog_file = config.path(CHANGELOG_FILE);
+        let current_file = encoding::read_to_string(&changelog_file)?;
+
+        let entries = parse_str(&current_file);
+        let existing = entries.iter().position(|e| e.version == version);
+        if let Some(index) = existing {
+            match config.changelog.duplicate_version {
+                DuplicateVersion::Error => {
+                    return Err(DebyError::VersionExists(version.to_string()).into())
+                }
+                DuplicateVersion::Skip => {
+                    return Ok(
+                        "debian/changelog file not updated, version already exists".to_string()
+                    )
+                }
+                DuplicateVersion::Append if index > 0 => {
+                    return Err(DebyError::VersionExists(version.to_string()).into())
+                }
+                mode => {
+                    let append = mode == DuplicateVersion::Append;
+                    let changes = config.interpolate(changes);
+                    let contents = Changelog::add_changes(
+                        config,
+                        &current_file,
+                        index,
+                        &changes,
+                        options,
+                        append,
+                    )?;
+                    Changelog::verify(&contents)?;
+                    fs::write(&changelog_file, contents)?;
+                    Sign::sign_file(config, &changelog_file)?;
+                    Changelog::update_markdown(config, version, &changes, options)?;
+
+                    return Ok(match append {
+                        true => "Successfully appended changes to the latest entry in debian/changelog file",
+                        false => "Successfully merged changes into an existing entry in debian/changelog file",
+                    }
+                    .to_string());
+                }
+            }
+        }
+
+        if config.changelog.enforce_version_order && !options.force {
+            let latest = entries.first().map(|e| e.version.as_str());
+            Changelog::check_version_order(latest, version)?;
+        }
+
+        let (changelog_entry, escalated) =
+            Changelog::new_entry(config, version, changes, &distribution, options)?;
+        let contents = Changelog::format_contents(&changelog_entry, &current_file);
+
+        let (contents, archived) = Changelog::rotate(config, &contents)?;
+        Changelog::verify(&contents)?;
+        let compress = config.changelog.rotate.as_ref().is_some_and(|r| r.compress);
+        let archived = write_rotated(&config.root, &contents, &archived, compress)?;
+        Sign::sign_file(config, &changelog_file)?;
+        Changelog::update_markdown(config, version, &config.interpolate(changes), options)?;
+
+        Ok(Changelog::created_message(escalated, archived))
+    }
+
+    /// Formats a new _changelog_ entry, base urgency is raised by urgency rules matching changes.
+    /// Returns the entry and urgency raised by the rules, if any
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `version` - version of the entry
+    /// - `changes` - string value of changes
+    /// - `distribution` - distribution of the entry
+    /// - `options` - options of the update
+    fn new_entry(
+        config: &Config,
+        version: &str,
+        changes: &str,
+        distribution: &str,
+        options: &ChangelogOptions,
+    ) -> Result<(String, Option<Urgency>), Box<dyn Error>> {
+        let base_urgency = options.urgency.unwrap_or(config.changelog.urgency);
+        let urgency = Changelog::escalate_urgency(config, changes, base_urgency)?;
+        let formatted_changes = Changelog::format_changes(changes, config.changelog.max_line_width);
+        let changelog_entry = Changelog::format_changelog_entry(
+            config,
+            version,
+            &formatted_changes,
+            urgency,
+            distribution,
+            options,
+        )?;
+        let mut changelog_entry = config.interpolate(&changelog_entry);
+        if config.changelog.strict_layout {
+            changelog_entry = Changelog::strict_layout(&changelog_entry)?;
+        }
+
+        Ok((
+            changelog_entry,
+            Some(urgency).filter(|u| *u != base_urgency),
+        ))
+    }
+
+    /// Formats result message of a new entry
+    ///
+    /// # Arguments
+    ///
+    /// - `escalated` - urgency raised by urgency rules
+    /// - `archived` - number of archived entries
+    fn created_message(escalated: Option<Urgency>, archived: usize) -> String {
+        let mut msg = "Successfully created a new entry in debian/changelog file".to_string();
+        if let Some(urgency) = escalated {
+            msg.push_str(&format!(
+                " with urgency {} raised by urgency rules",
+                urgency
+            ));
+        }
+        if archived > 0 {
+            msg.push_str(&format!(", {} old entries archived", archived));
+        }
+
+        msg
+    }
+
+    /// Checks output of `dpkg-parsechangelog`, warnings are treated as errors
+    ///
+    /// # Arguments
+    ///
+    /// - `success` - exit status of the tool is successful
+    /// - `stderr` - error output of the tool
+    #[cfg(feature = "dpkg-verify")]
+    fn check_dpkg_output(success: bool, stderr: &str) -> Result<(), DebyError> {
+        let stderr = stderr.trim();
+        match success && !stderr.contains("warning") {
+            true => Ok(()),
+            false => Err(DebyError::ChangelogVerify(stderr.to_string())),
+        }
+    }
+
+    /// Parses _changelog_ file contents with `dpkg-parsechangelog` before they are written,
+    /// verification is skipped when the tool is missing
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - _changelog_ file contents
+    #[cfg(feature = "dpkg-verify")]
+    fn verify(contents: &str) -> Result<(), DebyError> {
+        let child = Command::new("dpkg-parsechangelog")
+            .args(["-l", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(c) => c,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(DebyError::ChangelogVerify(e.to_string())),
+        };
+        let verify_error = |e: std::io::Error| DebyError::ChangelogVerify(e.to_string());
+        // stdin is written from another thread, warnings printed while the tool is still reading
+        // could fill stderr pipe otherwise and block both processes. The tool stops reading
+        // after the entries it checks, so a closed pipe is not an error
+        let stdin = child.stdin.take();
+        let contents = contents.to_string();
+        let writer = thread::spawn(move || match stdin {
+            Some(mut stdin) => match stdin.write_all(contents.as_bytes()) {
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+                written => written,
+            },
+            None => Ok(()),
+        });
+        let output = child.wait_with_output().map_err(verify_error)?;
+        let written = writer
+            .join()
+            .unwrap_or_else(|_| Err(std::io::Error::other("stdin writer panicked")));
+
+        Changelog::check_dpkg_output(
+            output.status.success(),
+            &String::from_utf8_lossy(&output.stderr),
+        )?;
+        written.map_err(verify_error)
+    }
+
+    /// Verification of _changelog_ file is disabled without `dpkg-verify` feature
+    #[cfg(not(feature = "dpkg-verify"))]
+    fn verify(_contents: &str) -> Result<(), DebyError> {
+        Ok(())
+    }
+
+    /// Splits entries beyond limits of `rotate` setting off _changelog_ file contents,
+    /// returns remaining contents and entries to be archived
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `contents` - _changelog_ file contents
+    fn rotate(config: &Config, contents: &str) -> Result<(String, String), DebyError> {
+        match &config.changelog.rotate {
+            Some(settings) => split_at_cutoffs(contents, &settings.cutoffs()?),
+            None => Ok((contents.to_string(), String::new())),
+        }
+    }
+
+    /// Adds changes of an entry to markdown changelog set by `markdownChangelog`,
+    /// a new entry gets a section on top of released versions
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `version` - version of an entry
+    /// - `changes` - string value of changes
+    /// - `options` - options of the update with a date of the entry
+    fn update_markdown(
+        config: &Config,
+        version: &str,
+        changes: &str,
+        options: &ChangelogOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        let markdown_file = match &config.changelog.markdown_changelog {
+            Some(f) => config.path(f),
+            None => return Ok(()),
+        };
+        let date = options.date.as_deref().unwrap_or_default();
+        let date = DateTime::parse_from_rfc2822(date)
+            .map_err(|_| DebyError::InvalidDate(date.to_string()))?;
+
+        let contents = encoding::read_to_string(&markdown_file)?;
+        let contents = markdown::extend(&contents, version, date.date_naive(), changes);
+        fs::write(&markdown_file, contents)?;
+
+        Ok(())
+    }
+
+    /// Formats date of an imported release at midnight in timezone of config file,
+    /// releases without a date get current date
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `date` - date of a release
+    fn release_date(config: &Config, date: Option<NaiveDate>) -> Result<String, DebyError> {
+        let timezone = config.changelog.timezone()?;
+        let midnight = date
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .and_then(|d| timezone.at(&d));
+
+        Ok(match midnight {
+            Some(d) => Changelog::rfc2822(&d),
+            None => Changelog::format_date(config),
+        })
+    }
+
+    /// Backfills _changelog_ file with releases of a keep-a-changelog formatted `CHANGELOG.md`.
+    /// Releases newer than the latest entry are added oldest first, sections become headings
+    /// followed by their items. Existing entries are kept byte for byte
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `markdown_contents` - contents of `CHANGELOG.md`
+    pub(crate) fn import(
+        config: &Config,
+        markdown_contents: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        if !config.changelog.update {
+            return Ok("debian/changelog file not updated due to config file setting".to_string());
+        }
+
+        let changelog_file = config.path(CHANGELOG_FILE);
+        let mut contents = encoding::read_to_string(&changelog_file)?;
+        let latest = parse_str(&contents).first().map(|e| e.version.clone());
+        let distribution = config.changelog.distribution();
+
+        let releases = markdown::parse(markdown_contents);
+        let mut imported = vec![];
+        for release in releases.iter().rev() {
+            let is_newer = match &latest {
+                Some(latest) => version::compare(&release.version, latest) == Ordering::Greater,
+                None => true,
+            };
+            if !is_newer {
+                continue;
+            }
+            version::validate(&release.version)?;
+
+            let options = ChangelogOptions {
+                date: Some(Changelog::release_date(config, release.date)?),
+                ..Default::default()
+            };
+            let changes =
+                Changelog::format_changes(&release.changes(), config.changelog.max_line_width);
+            let entry = Changelog::format_changelog_entry(
+                config,
+                &release.version,
+                &changes,
+                config.changelog.urgency,
+                &distribution,
+                &options,
+            )?;
+            contents = Changelog::format_contents(&config.interpolate(&entry), &contents);
+            imported.push((release, options));
+        }
+
+        if imported.is_empty() {
+            return Ok("No new releases to import into debian/changelog file".to_string());
+        }
+        Changelog::verify(&contents)?;
+        fs::write(&changelog_file, contents)?;
+        Sign::sign_file(config, &changelog_file)?;
+        for (release, options) in &imported {
+            let changes = config.interpolate(&release.changes());
+            Changelog::update_markdown(config, &release.version, &changes, options)?;
+        }
+
+        Ok(format!(
+            "Successfully imported {} releases into debian/changelog file",
+            imported.len()
+        ))
+    }
+
+    /// Returns distribution of new entries, vendor default is used when it's not set:
+    /// `unstable` for Debian, the newest series for Ubuntu
+    fn distribution(&self) -> String {
+        match (self.distribution.is_empty(), self.vendor) {
+            (false, _) => self.distribution.join(" "),
+            (true, Vendor::Debian) => "unstable".to_string(),
+            (true, Vendor::Ubuntu) => version::latest_ubuntu_series().to_string(),
+        }
+    }
+
+    /// Returns pattern of Launchpad bug references, Ubuntu vendor matches `LP #123` by default
+    fn launchpad_pattern(&self) -> Option<&str> {
+        match (&self.launchpad_pattern, self.vendor) {
+            (Some(pattern), _) => Some(pattern),
+            (None, Vendor::Ubuntu) => Some(UBUNTU_LAUNCHPAD_PATTERN),
+            (None, Vendor::Debian) => None,
+        }
+    }
+
+    /// Checks that distribution is a valid suite name and is allowed by `allowedDistributions` setting.
+    /// Empty setting allows any distribution for Debian vendor and known series for Ubuntu vendor
+    ///
+    /// # Arguments
+    ///
+    /// - `distribution` - distribution of _changelog_ entry
+    fn check_distribution(&self, distribution: &str) -> Result<(), DebyError> {
+        let valid =
+            !distribution.trim().is_empty() && !distribution.contains([';', '(', ')', '\n']);
+        let allowed = match (self.allowed_distributions.is_empty(), self.vendor) {
+            (false, _) => distribution
+                .split_whitespace()
+                .all(|d| self.allowed_distributions.iter().any(|a| a == d)),
+            (true, Vendor::Ubuntu) => distribution
+                .split_whitespace()
+                .all(|d| d == "UNRELEASED" || version::is_ubuntu_series(d)),
+            (true, Vendor::Debian) => true,
+        };
+
+        match valid && allowed {
+            true => Ok(()),
+            false => Err(DebyError::UnknownDistribution(distribution.to_string())),
+        }
+    }
+
+    /// Sets distribution of the latest entry and refreshes its trailer line
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `current_file_contents` - contents of _changelog_ file
+    /// - `distribution` - distribution of a release, e.g. `unstable`
+    /// - `options` - options of the release, its maintainer and date are used in trailer line
+    fn format_release(
+        config: &Config,
+        current_file_contents: &str,
+        distribution: &str,
+        options: &ChangelogOptions,
+    ) -> Result<String, DebyError> {
+        let header_end = current_file_contents
+            .find('\n')
+            .unwrap_or(current_file_contents.len());
+        let header = &current_file_contents[..header_end];
+        let (start, end) = match (header.find(") "), header.find(';')) {
+            (Some(start), Some(end)) if start < end => (start + 2, end),
+            _ => return Err(DebyError::ChangelogEmpty),
+        };
+
+        let contents = format!(
+            "{}{}{}",
+            &current_file_contents[..start],
+            distribution,
+            &current_file_contents[end..]
+        );
+        let trailer = Changelog::format_trailer(config, options)?;
+
+        Ok(Changelog::insert_changes(&contents, 0, "", Some(&trailer)))
+    }
+
+    /// Releases the latest entry of _changelog_ file, the way `dch -r` does:
+    /// its distribution is replaced and date is refreshed
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `distribution` - distribution of a release, e.g. `unstable`
+    /// - `options` - options of the release, its maintainer and date are used in trailer line
+    pub(crate) fn finalize<'a>(
+        config: &Config,
+        distribution: &str,
+        options: &ChangelogOptions,
+    ) -> Result<&'a str, Box<dyn Error>> {
+        let changelog_file = config.path(CHANGELOG_FILE);
+        let current_file = encoding::read_to_string(&changelog_file)?;
+        if entry_offsets(&current_file).first() != Some(&0) {
+            return Err(DebyError::ChangelogEmpty.into());
+        }
+
+        config.changelog.check_distribution(distribution)?;
+        Changelog::check_options(options)?;
+        let options = &ChangelogOptions {
+            date: Some(Changelog::entry_date(config, options)?),
+            ..options.clone()
+        };
+        let contents = Changelog::format_release(config, &current_file, distribution, options)?;
+        Changelog::verify(&contents)?;
+        fs::write(&changelog_file, contents)?;
+        Sign::sign_file(config, &changelog_file)?;
+
+        Ok("Successfully released the latest entry in debian/changelog file")
+    }
+
+    /// Returns package name of _changelog_ entries
+    pub(crate) fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Returns `true` if _changelog_ file is updated according to config file setting
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.update
+    }
+
+    pub(crate) fn default() -> Self {
+        Self {
+            update: false,
+            package: "".to_string(),
+            vendor: Vendor::Debian,
+            distribution: Changelog::default_distribution(),
+            allowed_distributions: vec![],
+            urgency: Urgency::Low,
+            timezone: Changelog::default_timezone(),
+            maintainer: Maintainer {
+                name: "".to_string(),
+                email: "".to_string(),
+            },
+            maintainer_from_git: false,
+            allow_empty_changes: false,
+            max_line_width: Changelog::default_max_line_width(),
+            urgency_rules: vec![],
+            duplicate_version: DuplicateVersion::Error,
+            enforce_version_order: false,
+            closes_pattern: None,
+            launchpad_pattern: None,
+            ticket_pattern: None,
+            conventional_commits: false,
+            commit_types: Changelog::default_commit_types(),
+            markdown_changelog: None,
+            rotate: None,
+            strict_layout: false,
+        }
+    }
+
+    /// Deserializes `distribution` value which can be a single distribution or an array of distributions
+    fn deserialize_distribution<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DistributionVisitor)
+    }
+
+    /// Deserializes `maintainer` value, either name or email can be omitted
+    fn deserialize_maintainer<'de, D>(deserializer: D) -> Result<Maintainer, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let maintainer = ChangelogMaintainer::deserialize(deserializer)?;
+
+        Ok(Maintainer {
+            name: maintainer.name,
+            email: maintainer.email,
+        })
+    }
+
+    fn default_distribution() -> Vec<String> {
+        vec![]
+    }
+
+    fn default_vendor() -> Vendor {
+        Vendor::Debian
+    }
+
+    fn default_allowed_distributions() -> Vec<String> {
+        vec![]
+    }
+
+    fn default_maintainer() -> Maintainer {
+        Maintainer::default()
+    }
+
+    fn default_maintainer_from_git() -> bool {
+        false
+    }
+
+    fn default_timezone() -> String {
+        "local".to_string()
+    }
+
+    fn default_urgency() -> Urgency {
+        Urgency::Low
+    }
+
+    fn default_allow_empty_changes() -> bool {
+        false
+    }
+
+    fn default_max_line_width() -> usize {
+        80
+    }
+
+    fn default_urgency_rules() -> Vec<UrgencyRule> {
+        vec![]
+    }
+
+    fn default_duplicate_version() -> DuplicateVersion {
+        DuplicateVersion::Error
+    }
+
+    fn default_enforce_version_order() -> bool {
+        false
+    }
+
+    fn default_closes_pattern() -> Option<String> {
+        None
+    }
+
+    fn default_launchpad_pattern() -> Option<String> {
+        None
+    }
+
+    fn default_ticket_pattern() -> Option<TicketPattern> {
+        None
+    }
+
+    fn default_strict_layout() -> bool {
+        false
+    }
+
+    fn default_rotate() -> Option<Rotate> {
+        None
+    }
+
+    fn default_markdown_changelog() -> Option<String> {
+        None
+    }
+
+    fn default_conventional_commits() -> bool {
+        false
+    }
+
+    fn default_commit_types() -> Vec<CommitType> {
+        vec![
+            CommitType::new("feat", "New features"),
+            CommitType::new("fix", "Bug fixes"),
+            CommitType::new("perf", "Performance improvements"),
+            CommitType::new("chore", "Maintenance"),
+        ]
+    }
+}
+
+/// Distribution vendor of a package, it sets defaults of distribution and bug references
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+enum Vendor {
+    #[serde(rename(deserialize = "debian"))]
+    Debian,
+    #[serde(rename(deserialize = "ubuntu"))]
+    Ubuntu,
+}
+
+/// Launchpad bug references of Ubuntu vendor, e.g. `LP #123` or `lp#123`
+const UBUNTU_LAUNCHPAD_PATTERN: &str = r"(?i)\blp\s*#(\d+)";
+
+/// What to do when _changelog_ file already has an entry for a version
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+enum DuplicateVersion {
+    #[serde(rename(deserialize = "error"))]
+    Error,
+    #[serde(rename(deserialize = "skip"))]
+    Skip,
+    #[serde(rename(deserialize = "merge"))]
+    Merge,
+    #[serde(rename(deserialize = "append"), alias = "append-to-existing-version")]
+    Append,
+}
+
+/// Urgency of a _changelog_ entry
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum Urgency {
+    #[serde(rename(deserialize = "low"))]
+    Low,
+    #[serde(rename(deserialize = "medium"))]
+    Medium,
+    #[serde(rename(deserialize = "high"))]
+    High,
+    #[serde(rename(deserialize = "emergency"))]
+    Emergency,
+    #[serde(rename(deserialize = "critical"))]
+    Critical,
+}
+
+impl Urgency {
+    /// Returns urgency rank, a higher rank means a more urgent upload
+    fn rank(&self) -> u8 {
+        match self {
+            Urgency::Low => 0,
+            Urgency::Medium => 1,
+            Urgency::High => 2,
+            Urgency::Critical => 3,
+            Urgency::Emergency => 4,
+        }
+    }
+}
+
+impl Display for Urgency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Urgency::Low => write!(f, "low"),
+            Urgency::Medium => write!(f, "medium"),
+            Urgency::High => write!(f, "high"),
+            Urgency::Emergency => write!(f, "emergency"),
+            Urgency::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let default = Changelog::default();
+        let empty_str = String::new();
+
+        assert!(!default.update);
+
+        assert_eq!(default.package, empty_str);
+        assert_eq!(default.vendor, Vendor::Debian);
+        assert!(default.distribution.is_empty());
+        assert_eq!(default.distribution(), "unstable");
+        assert!(default.allowed_distributions.is_empty());
+        assert_eq!(default.urgency, Urgency::Low);
+        assert_eq!(default.timezone, "local");
+        assert_eq!(default.maintainer.name, empty_str);
+        assert_eq!(default.maintainer.email, empty_str);
+        assert!(!default.maintainer_from_git);
+        assert!(!default.allow_empty_changes);
+        assert_eq!(default.max_line_width, 80);
+        assert!(default.urgency_rules.is_empty());
+        assert_eq!(default.duplicate_version, DuplicateVersion::Error);
+        assert!(!default.enforce_version_order);
+        assert!(default.closes_pattern.is_none());
+        assert!(default.launchpad_pattern.is_none());
+        assert!(default.ticket_pattern.is_none());
+        assert!(!default.conventional_commits);
+        assert!(default.markdown_changelog.is_none());
+        assert!(default.rotate.is_none());
+        assert!(!default.strict_layout);
+        assert_eq!(default.commit_types.len(), 4);
+    }
+
+    fn fake_config_with_rules(rules: Vec<UrgencyRule>) -> Config {
+        let mut config: Config = serde_json::from_str("{}").unwrap();
+        config.changelog.urgency_rules = rules;
+        config
+    }
+
+    #[test]
+    fn test_escalate_urgency() {
+        let fake_config = fake_config_with_rules(vec![
+            UrgencyRule {
+                pattern: "security|CVE".to_string(),
+                urgency: Urgency::High,
+            },
+            UrgencyRule {
+                pattern: "hotfix".to_string(),
+                urgency: Urgency::Critical,
+            },
+        ]);
+
+        let high =
+            Changelog::escalate_urgency(&fake_config, "fix CVE-2022-1", Urgency::Low).unwrap();
+        let critical =
+            Changelog::escalate_urgency(&fake_config, "hotfix for CVE", Urgency::Low).unwrap();
+        let low = Changelog::escalate_urgency(&fake_config, "update docs", Urgency::Low).unwrap();
+        let base = Changelog::escalate_urgency(&fake_config, "update docs", Urgency::High).unwrap();
+
+        assert_eq!(high, Urgency::High);
+        assert_eq!(critical, Urgency::Critical);
+        assert_eq!(low, Urgency::Low);
+        assert_eq!(base, Urgency::High);
+    }
+
+    #[test]
+    fn test_new_entry_escalated_urgency() {
+        let mut fake_config = fake_config_with_rules(vec![UrgencyRule {
+            pattern: "CVE".to_string(),
+            urgency: Urgency::High,
+        }]);
+        fake_config.changelog.package = "foo".to_string();
+        fake_config.changelog.maintainer = Maintainer {
+            name: "John Doe".to_string(),
+            email: "john@example.com".to_string(),
+        };
+        let fake_options = ChangelogOptions {
+            date: Some("Mon, 10 Jan 2022 10:00:00 +0300".to_string()),
+            ..Default::default()
+        };
+
+        let (entry, escalated) = Changelog::new_entry(
+            &fake_config,
+            "1.0.1",
+            "fix CVE-2022-1",
+            "unstable",
+            &fake_options,
+        )
+        .unwrap();
+
+        assert!(entry.starts_with("\nfoo (1.0.1) unstable; urgency=high\n"));
+        assert_eq!(escalated, Some(Urgency::High));
+        assert_eq!(
+            Changelog::created_message(escalated, 0),
+            "Successfully created a new entry in debian/changelog file with urgency high raised by urgency rules"
+        );
+
+        let (entry, escalated) = Changelog::new_entry(
+            &fake_config,
+            "1.0.1",
+            "update docs",
+            "unstable",
+            &fake_options,
+        )
+        .unwrap();
+
+        assert!(entry.starts_with("\nfoo (1.0.1) unstable; urgency=low\n"));
+        assert_eq!(escalated, None);
+        assert_eq!(
+            Changelog::created_message(escalated, 2),
+            "Successfully created a new entry in debian/changelog file, 2 old entries archived"
+        );
+    }
+
+    #[test]
+    fn test_escalate_urgency_invalid_pattern() {
+        let fake_config = fake_config_with_rules(vec![UrgencyRule {
+            pattern: "(".to_string(),
+            urgency: Urgency::High,
+        }]);
+
+        let actual = Changelog::escalate_urgency(&fake_config, "change", Urgency::Low);
+
+        assert!(matches!(actual, Err(DebyError::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn test_rewrite_tickets() {
+        let fake_ticket = TicketPattern {
+            pattern: r"(?i)\bproj-(\d+)\b".to_string(),
+            replacement: "[PROJ-$1](https://jira.example.com/browse/PROJ-$1)".to_string(),
+        };
+
+        let actual =
+            Changelog::rewrite_tickets("fix login, proj-42\nPROJ-7 docs", &fake_ticket).unwrap();
+
+        assert_eq!(
+            actual,
+            "fix login, [PROJ-42](https://jira.example.com/browse/PROJ-42)\n[PROJ-7](https://jira.example.com/browse/PROJ-7) docs"
+        );
+    }
+
+    #[test]
+    fn test_add_references() {
+        let fake_changes = "fix crash, fixes 12345 and #678\nupdate docs\nfix typo #9 (Closes: #9)";
+
+        let actual =
+            Changelog::add_references(fake_changes, r"(?i)(?:fixes |#)(\d+)", "Closes").unwrap();
+
+        assert_eq!(
+            actual,
+            "fix crash, fixes 12345 and #678 (Closes: #12345, #678)\nupdate docs\nfix typo #9 (Closes: #9)"
+        );
+        assert_eq!(
+            Changelog::add_references("fix crash (LP: #1234567)", r"#(\d+)", "Closes").unwrap(),
+            "fix crash (LP: #1234567)"
+        );
+        assert_eq!(
+            Changelog::add_references("fix crash UBUNTU-42 (Closes: #1)", r"UBUNTU-(\d+)", "LP")
+                .unwrap(),
+            "fix crash UBUNTU-42 (Closes: #1) (LP: #42)"
+        );
+        assert!(matches!(
+            Changelog::add_references(fake_changes, "(", "Closes"),
+            Err(DebyError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_has_changes() {
+        assert!(Changelog::has_changes("\n  change\n"));
+        assert!(!Changelog::has_changes(""));
+        assert!(!Changelog::has_changes(" \n\t\n"));
+    }
+
+    #[test]
+    fn test_format_contents() {
+        let fake_entry = "entry";
+        let fake_current_file = "current file contents";
+        let actual = Changelog::format_contents(fake_entry, fake_current_file);
+
+        let expected = format!(
+            "{entry}
+
+{current}
+",
+            entry = fake_entry,
+            current = fake_current_file
+        );
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_contents_whitespace_new_line() {
+        let fake_entry = "entry     \n\n";
+        let fake_current_file = "current file contents";
+        let actual = Changelog::format_contents(fake_entry, fake_current_file);
+
+        let expected = format!(
+            "{entry}
+
+{current}
+",
+            entry = fake_entry,
+            current = fake_current_file
+        );
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_contents_preserves_current_file() {
+        let fake_entry = "entry";
+        let fake_current_file = "\n  current  \n\n  * change   \n\n\n";
+        let actual = Changelog::format_contents(fake_entry, fake_current_file);
+
+        let expected = format!(
+            "{entry}
+
+{current}",
+            entry = fake_entry,
+            current = fake_current_file
+        );
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_contents_empty_current_file() {
+        let fake_entry = "\nentry\n";
+        let actual = Changelog::format_contents(fake_entry, "");
+
+        assert_eq!(actual, "entry\n");
+    }
+
+    #[test]
+    fn test_merge_changes() {
+        let fake_current_file = "foo (1.1.0) unstable; urgency=low
+
+  * change 1
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * initial release
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+        let fake_config: Config = serde_json::from_str("{}").unwrap();
+
+        let actual = Changelog::add_changes(
+            &fake_config,
+            fake_current_file,
+            1,
+            "initial release\nchange 2",
+            &ChangelogOptions::default(),
+            false,
+        )
+        .unwrap();
+
+        let expected = "foo (1.1.0) unstable; urgency=low
+
+  * change 1
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * initial release
+  * change 2
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_append_changes() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.maintainer = Maintainer {
+            name: "Jane Doe".to_string(),
+            email: "jane@example.com".to_string(),
+        };
+        let fake_current_file = "foo (1.1.0) unstable; urgency=low
+
+  * change 1
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) unstable; urgency=low
+
+  * initial release
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+        let actual = Changelog::add_changes(
+            &fake_config,
+            fake_current_file,
+            0,
+            "change 1\nchange 2",
+            &ChangelogOptions::default(),
+            true,
+        )
+        .unwrap();
+
+        let entries = parse_str(&actual);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].changes, vec!["* change 1", "* change 2"]);
+        assert_eq!(entries[0].maintainer, "Jane Doe <jane@example.com>");
+        assert_ne!(entries[0].date, "Mon, 10 Jan 2022 10:00:00 +0300");
+        assert!(actual.ends_with(&fake_current_file[entry_offsets(fake_current_file)[1]..]));
+    }
+
+    #[test]
+    fn test_add_changes_wrapped() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.max_line_width = 30;
+        let fake_current_file = "foo (1.0.0) unstable; urgency=low
+
+  * a rather long change which
+    is wrapped
+  * short
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+        let actual = Changelog::add_changes(
+            &fake_config,
+            fake_current_file,
+            0,
+            "a rather long change which is wrapped\nshort\nanother long change to be wrapped",
+            &ChangelogOptions::default(),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            actual,
+            fake_current_file.replace(
+                "  * short\n",
+                "  * short\n  * another long change to be\n    wrapped\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_append_changes_without_trailer() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.maintainer = Maintainer {
+            name: "Jane Doe".to_string(),
+            email: "jane@example.com".to_string(),
+        };
+        let fake_options = ChangelogOptions {
+            date: Some("Mon, 10 Jan 2022 10:00:00 +0300".to_string()),
+            ..Default::default()
+        };
+        let fake_current_file = "foo (1.0.0) UNRELEASED; urgency=low\n\n  * wip\n";
+
+        let actual = Changelog::add_changes(
+            &fake_config,
+            fake_current_file,
+            0,
+            "wip\nfix a",
+            &fake_options,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            actual,
+            "foo (1.0.0) UNRELEASED; urgency=low
+
+  * wip
+  * fix a
+
+ -- Jane Doe <jane@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+"
+        );
+
+        let actual = Changelog::insert_changes(
+            "foo (1.1.0) UNRELEASED; urgency=low\n\n  * wip\n\nfoo (1.0.0) unstable; urgency=low\n\n  * a\n",
+            0,
+            "\n  * b",
+            None,
+        );
+        assert_eq!(
+            actual,
+            "foo (1.1.0) UNRELEASED; urgency=low\n\n  * wip\n  * b\n\nfoo (1.0.0) unstable; urgency=low\n\n  * a\n"
+        );
+    }
+
+    #[test]
+    fn test_format_trailer() {
+        let fake_config: Config = serde_json::from_str("{}").unwrap();
+        let fake_options = ChangelogOptions {
+            date: Some("Mon, 10 Jan 2022 10:00:00 +0300".to_string()),
+            maintainer: Some("Jane Doe <jane@example.com>".to_string()),
+            ..Default::default()
+        };
+
+        let actual = Changelog::format_trailer(&fake_config, &fake_options).unwrap();
+
+        assert_eq!(
+            actual,
+            " -- Jane Doe <jane@example.com>  Mon, 10 Jan 2022 10:00:00 +0300"
+        );
+        assert!(Changelog::check_options(&fake_options).is_ok());
+    }
+
+    #[test]
+    fn test_rfc2822() {
+        let fake_date = FixedOffset::west_opt(5 * 3600 + 30 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2022, 1, 3, 9, 5, 7)
+            .unwrap();
+
+        let actual = Changelog::rfc2822(&fake_date);
+
+        assert_eq!(actual, "Mon, 03 Jan 2022 09:05:07 -0530");
+        assert!(Changelog::check_date(&actual).is_ok());
+    }
+
+    #[test]
+    fn test_check_date() {
+        assert!(Changelog::check_date("Mon, 3 Jan 2022 09:05:07 +0000").is_ok());
+        assert!(Changelog::check_date("Mo, 03 Jan 2022 09:05:07 +0000").is_err());
+        assert!(Changelog::check_date("Mon, 03 Jän 2022 09:05:07 +0000").is_err());
+        assert!(Changelog::check_date("Mon, 03 Jan 2022 09:05:07 GMT").is_err());
+        assert!(Changelog::check_date("Tue, 03 Jan 2022 09:05:07 +0000").is_err());
+    }
+
+    #[test]
+    fn test_format_date_timezone() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+
+        fake_config.changelog.timezone = "UTC".to_string();
+        assert!(Changelog::format_date(&fake_config).ends_with(" +0000"));
+
+        fake_config.changelog.timezone = "+03:00".to_string();
+        assert!(Changelog::format_date(&fake_config).ends_with(" +0300"));
+
+        fake_config.changelog.timezone = "Europe/Moscow".to_string();
+        assert!(Changelog::format_date(&fake_config).ends_with(" +0300"));
+
+        fake_config.changelog.timezone = "Mars/Olympus".to_string();
+        assert!(matches!(
+            fake_config.changelog.timezone(),
+            Err(DebyError::InvalidTimezone(tz)) if tz == "Mars/Olympus"
+        ));
+    }
+
+    #[test]
+    fn test_env_maintainer() {
+        let fake_env = |vars: &'static [(&'static str, &'static str)]| {
+            move |k: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == k)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        assert_eq!(
+            Changelog::env_maintainer(fake_env(&[
+                ("DEBFULLNAME", "Jane Doe"),
+                ("DEBEMAIL", "jane@example.com"),
+                ("NAME", "Other"),
+            ])),
+            ("Jane Doe".to_string(), "jane@example.com".to_string())
+        );
+        assert_eq!(
+            Changelog::env_maintainer(fake_env(&[("DEBEMAIL", "Jane Doe <jane@example.com>")])),
+            ("Jane Doe".to_string(), "jane@example.com".to_string())
+        );
+        assert_eq!(
+            Changelog::env_maintainer(fake_env(&[("NAME", "John"), ("EMAIL", "john@example.com")])),
+            ("John".to_string(), "john@example.com".to_string())
+        );
+        assert_eq!(
+            Changelog::env_maintainer(fake_env(&[])),
+            (String::new(), String::new())
+        );
+    }
+
+    #[test]
+    fn test_check_options() {
+        let invalid_date = ChangelogOptions {
+            date: Some("2022-01-10".to_string()),
+            ..Default::default()
+        };
+        let invalid_maintainer = ChangelogOptions {
+            maintainer: Some("jane@example.com".to_string()),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            Changelog::check_options(&invalid_date),
+            Err(DebyError::InvalidDate(_))
+        ));
+        assert!(matches!(
+            Changelog::check_options(&invalid_maintainer),
+            Err(DebyError::InvalidMaintainer(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_version_order() {
+        assert!(Changelog::check_version_order(None, "1.0.0").is_ok());
+        assert!(Changelog::check_version_order(Some("1.0.0"), "1.0.1~rc1").is_ok());
+        assert!(Changelog::check_version_order(Some("1.0.0"), "1:0.1").is_ok());
+        assert!(matches!(
+            Changelog::check_version_order(Some("1.0.0"), "1.0.0~rc1"),
+            Err(DebyError::VersionNotIncreasing(v, latest)) if v == "1.0.0~rc1" && latest == "1.0.0"
+        ));
+        assert!(Changelog::check_version_order(Some("1.0.0"), "1.0.0").is_err());
+    }
+
+    #[test]
+    fn test_format_release() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.maintainer = Maintainer {
+            name: "Jane Doe".to_string(),
+            email: "jane@example.com".to_string(),
+        };
+        let fake_current_file = "foo (1.1.0) UNRELEASED; urgency=low
+
+  * change 1
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300
+
+foo (1.0.0) UNRELEASED; urgency=low
+
+  * initial release
+
+ -- John Doe <john@example.com>  Sun, 09 Jan 2022 10:00:00 +0300
+";
+
+        let actual = Changelog::format_release(
+            &fake_config,
+            fake_current_file,
+            "unstable",
+            &ChangelogOptions::default(),
+        )
+        .unwrap();
+
+        let entries = parse_str(&actual);
+        assert!(
+            actual.starts_with("foo (1.1.0) unstable; urgency=low\n\n  * change 1\n\n -- Jane Doe")
+        );
+        assert_eq!(entries[0].changes, vec!["* change 1"]);
+        assert_eq!(entries[1].distribution, "UNRELEASED");
+        assert_eq!(entries[1].maintainer, "John Doe <john@example.com>");
+
+        let fake_options = ChangelogOptions {
+            maintainer: Some("Release Manager <rm@example.com>".to_string()),
+            ..Default::default()
+        };
+        let actual =
+            Changelog::format_release(&fake_config, fake_current_file, "unstable", &fake_options)
+                .unwrap();
+        assert_eq!(
+            parse_str(&actual)[0].maintainer,
+            "Release Manager <rm@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_distribution() {
+        let single: Changelog = serde_json::from_str(
+            r#"{"update": true, "package": "foo", "distribution": "unstable",
+                "maintainer": {"name": "John", "email": "john@example.com"}}"#,
+        )
+        .unwrap();
+        let many: Changelog = serde_json::from_str(
+            r#"{"update": true, "package": "foo", "distribution": ["unstable", "experimental"],
+                "maintainer": {"name": "John", "email": "john@example.com"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(single.distribution, vec!["unstable"]);
+        assert_eq!(many.distribution.join(" "), "unstable experimental");
+    }
+
+    #[test]
+    fn test_check_distribution() {
+        let mut fake_changelog = Changelog::default();
+
+        assert!(fake_changelog.check_distribution("bookworm").is_ok());
+        assert!(fake_changelog.check_distribution("").is_err());
+        assert!(fake_changelog.check_distribution("foo; bar").is_err());
+
+        fake_changelog.allowed_distributions = vec!["unstable".to_string(), "focal".to_string()];
+        assert!(fake_changelog.check_distribution("focal").is_ok());
+        assert!(fake_changelog.check_distribution("unstable focal").is_ok());
+        assert!(matches!(
+            fake_changelog.check_distribution("bullseye-backports"),
+            Err(DebyError::UnknownDistribution(d)) if d == "bullseye-backports"
+        ));
+    }
+
+    #[test]
+    fn test_options_entry() {
+        let fake_config: Config = serde_json::from_str("{}").unwrap();
+        let fake_options = ChangelogOptions {
+            authors: vec![("Jane Doe".to_string(), vec!["fix crash".to_string()])],
+            ..Default::default()
+        };
+        let (version, changes, _) = fake_options
+            .entry(&fake_config, "1.0.0", "update docs\n")
+            .unwrap();
+        assert_eq!(version, "1.0.0");
+        assert_eq!(changes, "update docs\n[ Jane Doe ]\nfix crash");
+
+        let fake_options = ChangelogOptions {
+            upload: Upload::Backport(12),
+            ..Default::default()
+        };
+        let (version, _, options) = fake_options
+            .entry(&fake_config, "1.2.3-1", "rebuild")
+            .unwrap();
+        assert_eq!(version, "1.2.3-1~bpo12+1");
+        assert_eq!(options.distribution, vec!["bookworm-backports"]);
+
+        let fake_options = ChangelogOptions {
+            upload: Upload::Security(12),
+            distribution: vec!["bookworm".to_string()],
+            ..Default::default()
+        };
+        let (_, _, options) = fake_options
+            .entry(&fake_config, "1.2.3-1+deb12u1", "fix")
+            .unwrap();
+        assert_eq!(options.distribution, vec!["bookworm"]);
+        assert_eq!(options.urgency, Some(Urgency::High));
+        assert!(fake_options.entry(&fake_config, "1.2.3-1", "fix").is_err());
+    }
+
+    #[test]
+    fn test_options_entry_binnmu() {
+        let fake_root = env::temp_dir().join(format!("deby-binnmu-{}", std::process::id()));
+        fs::create_dir_all(fake_root.join("debian")).unwrap();
+        fs::write(
+            fake_root.join(CHANGELOG_FILE),
+            "foo (1.2.3-1) unstable; urgency=low\n\n  * change\n\n -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300\n",
+        )
+        .unwrap();
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.root = fake_root.clone();
+        let fake_options = ChangelogOptions {
+            upload: Upload::BinNmu("amd64".to_string()),
+            ..Default::default()
+        };
+
+        let derived = fake_options.entry(&fake_config, "", "rebuild");
+        let matching = fake_options.entry(&fake_config, "1.2.3-1+b1", "rebuild");
+        let mismatched = fake_options.entry(&fake_config, "1.2.3-2", "rebuild");
+        fs::remove_dir_all(&fake_root).unwrap();
+
+        let (version, changes, options) = derived.unwrap();
+        assert_eq!(version, "1.2.3-1+b1");
+        assert!(changes.starts_with("Binary-only non-maintainer upload for amd64"));
+        assert!(options.binary_only);
+        assert_eq!(matching.unwrap().0, "1.2.3-1+b1");
+        assert!(matches!(
+            mismatched,
+            Err(DebyError::InvalidVersion(v, _)) if v == "1.2.3-2"
+        ));
+    }
+
+    #[test]
+    fn test_format_changes_grouped_by_author() {
+        let fake_changes = Changelog::join_author_changes(&[
+            (
+                "Jane Doe".to_string(),
+                vec!["change 1".to_string(), "change 2".to_string()],
+            ),
+            ("John Doe".to_string(), vec!["change 3".to_string()]),
+        ]);
+
+        let actual = Changelog::format_changes(&fake_changes, 80);
+
+        assert_eq!(
+            actual,
+            "[ Jane Doe ]\n  * change 1\n  * change 2\n\n  [ John Doe ]\n  * change 3"
+        );
+    }
+
     #[test]
     fn test_format_changes() {
         let fake_changes = "change1\nchange2\nchange3\n";
 
-        let actual = Changelog::format_changes(fake_changes);
+        let actual = Changelog::format_changes(fake_changes, 80);
         let expected = "  * change1
   * change2
   * change3
@@ -263,4 +2480,323 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_format_changes_wrapped() {
+        let fake_changes =
+            "fix a rather long description of a bug which doesn't fit into a single line";
+
+        let actual = Changelog::format_changes(fake_changes, 40);
+        let expected = "* fix a rather long description of a
+    bug which doesn't fit into a single
+    line";
+
+        assert_eq!(actual, expected);
+        assert_eq!(
+            Changelog::format_changes(fake_changes, 0),
+            format!("* {}", fake_changes)
+        );
+    }
+
+    #[test]
+    fn test_normalize_markdown() {
+        let fake_changes = "- fix: crash on start
+* feat: new [option](https://example.com/docs)
+
+  - nested change
+1. numbered change
+[ Jane Doe ]
++ see [https://example.com](https://example.com)";
+
+        let actual = Changelog::normalize_markdown(fake_changes);
+        let expected = "fix: crash on start
+feat: new option (https://example.com/docs)
+nested change
+numbered change
+[ Jane Doe ]
+see https://example.com";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_group_commits() {
+        let mut fake_types = Changelog::default_commit_types();
+        fake_types.push(CommitType {
+            commit_type: "ci".to_string(),
+            section: None,
+        });
+        let fake_changes = "fix(parser): handle empty input
+ci: bump runner
+feat!: new config layout
+docs: update readme
+fix: crash on start
+update dependencies";
+
+        let actual = Changelog::group_commits(fake_changes, &fake_types);
+        let expected = "New features:
+  - new config layout
+Bug fixes:
+  - parser: handle empty input
+  - crash on start
+Other changes:
+  - update readme
+  - update dependencies";
+
+        assert_eq!(actual, expected);
+        assert_eq!(
+            Changelog::format_changes(&actual, 80),
+            "* New features:
+    - new config layout
+  * Bug fixes:
+    - parser: handle empty input
+    - crash on start
+  * Other changes:
+    - update readme
+    - update dependencies"
+        );
+    }
+
+    #[test]
+    fn test_release_date() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.timezone = "+03:00".to_string();
+
+        let actual = Changelog::release_date(&fake_config, NaiveDate::from_ymd_opt(2022, 1, 10));
+
+        assert_eq!(actual.unwrap(), "Mon, 10 Jan 2022 00:00:00 +0300");
+    }
+
+    #[test]
+    fn test_release_date_named_timezone() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.timezone = "Europe/Berlin".to_string();
+
+        let winter = Changelog::release_date(&fake_config, NaiveDate::from_ymd_opt(2022, 1, 10));
+        let summer = Changelog::release_date(&fake_config, NaiveDate::from_ymd_opt(2022, 7, 11));
+
+        assert_eq!(winter.unwrap(), "Mon, 10 Jan 2022 00:00:00 +0100");
+        assert_eq!(summer.unwrap(), "Mon, 11 Jul 2022 00:00:00 +0200");
+    }
+
+    #[test]
+    fn test_rotate_cutoffs() {
+        let fake_rotate: Rotate =
+            serde_json::from_str(r#"{"keepEntries": 100, "before": "2020-01-01"}"#).unwrap();
+
+        assert_eq!(
+            fake_rotate.cutoffs().unwrap(),
+            vec![
+                Cutoff::Keep(100),
+                Cutoff::Before(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap())
+            ]
+        );
+
+        let fake_rotate: Rotate = serde_json::from_str(r#"{"before": "01.01.2020"}"#).unwrap();
+
+        assert!(matches!(
+            fake_rotate.cutoffs(),
+            Err(DebyError::InvalidDate(_))
+        ));
+    }
+
+    /// Output of `dpkg-parsechangelog -l -` (dpkg 1.21.22) for the entry of `fake_strict_entry`
+    const FAKE_DPKG_PARSECHANGELOG: &str =
+        include_str!("fixtures/strict_layout.dpkg-parsechangelog");
+
+    /// Formats an entry in strict layout
+    fn fake_strict_entry() -> String {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.package = "foo".to_string();
+        fake_config.changelog.max_line_width = 0;
+        let fake_options = ChangelogOptions {
+            maintainer: Some("John Doe <john@example.com>".to_string()),
+            date: Some("Mon, 10 Jan 2022 10:00:00 +0300".to_string()),
+            ..Default::default()
+        };
+        let fake_changes = Changelog::format_changes(
+            "[ Jane Doe ]\nfix crash on start\t \nNew features:\n  - option",
+            0,
+        );
+
+        let entry = Changelog::format_changelog_entry(
+            &fake_config,
+            "1.2.0-1",
+            &fake_changes,
+            Urgency::Medium,
+            "unstable",
+            &fake_options,
+        )
+        .unwrap();
+
+        Changelog::strict_layout(&entry).unwrap()
+    }
+
+    #[test]
+    fn test_strict_layout_matches_dpkg_parsechangelog() {
+        let actual = fake_strict_entry();
+
+        let field = |name: &str| {
+            FAKE_DPKG_PARSECHANGELOG
+                .lines()
+                .find_map(|l| l.strip_prefix(&format!("{}: ", name)))
+                .unwrap()
+        };
+        let parsed = parse_str(&actual).pop().unwrap();
+        assert_eq!(parsed.package, field("Source"));
+        assert_eq!(parsed.version, field("Version"));
+        assert_eq!(parsed.distribution, field("Distribution"));
+        assert_eq!(parsed.urgency, field("Urgency"));
+        assert_eq!(parsed.maintainer, field("Maintainer"));
+        assert_eq!(parsed.date, field("Date"));
+
+        let dpkg_changes: Vec<&str> = FAKE_DPKG_PARSECHANGELOG
+            .lines()
+            .skip_while(|l| *l != "Changes:")
+            .skip(1)
+            .map(|l| match l {
+                " ." => "",
+                l => &l[1..],
+            })
+            .collect();
+        let changes: Vec<&str> = actual.trim().lines().take(dpkg_changes.len()).collect();
+        assert_eq!(changes, dpkg_changes);
+    }
+
+    #[cfg(feature = "dpkg-verify")]
+    #[test]
+    fn test_strict_layout_dpkg_parsechangelog_fixture() {
+        let child = Command::new("dpkg-parsechangelog")
+            .args(["-l", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        // the fixture is checked without the tool
+        let mut child = match child {
+            Ok(c) => c,
+            Err(e) if e.kind() == ErrorKind::NotFound => return,
+            Err(e) => panic!("{}", e),
+        };
+        let fake_entry = fake_strict_entry();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(fake_entry.trim_start().as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            FAKE_DPKG_PARSECHANGELOG
+        );
+    }
+
+    #[test]
+    fn test_strict_layout_invalid() {
+        let single_space_trailer = "foo (1.0.0) unstable; urgency=low
+
+  * fix
+
+ -- John Doe <john@example.com> Mon, 10 Jan 2022 10:00:00 +0300";
+        let unindented_change = "foo (1.0.0) unstable; urgency=low
+
+* fix
+
+ -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300";
+
+        assert!(matches!(
+            Changelog::strict_layout(single_space_trailer),
+            Err(DebyError::InvalidLayout(l)) if l.starts_with(" -- ")
+        ));
+        assert!(matches!(
+            Changelog::strict_layout(unindented_change),
+            Err(DebyError::InvalidLayout(l)) if l == "* fix"
+        ));
+    }
+
+    #[test]
+    fn test_ubuntu_vendor() {
+        let fake_changelog: Changelog =
+            serde_json::from_str(r#"{"update": true, "package": "foo", "vendor": "ubuntu"}"#)
+                .unwrap();
+
+        assert_eq!(fake_changelog.distribution(), "resolute");
+        assert!(fake_changelog.check_distribution("jammy-updates").is_ok());
+        assert!(fake_changelog.check_distribution("UNRELEASED").is_ok());
+        assert!(matches!(
+            fake_changelog.check_distribution("unstable"),
+            Err(DebyError::UnknownDistribution(_))
+        ));
+
+        let pattern = fake_changelog.launchpad_pattern().unwrap();
+        let actual = Changelog::add_references("fix crash LP #123", pattern, "LP").unwrap();
+        assert_eq!(actual, "fix crash LP #123 (LP: #123)");
+    }
+
+    #[cfg(feature = "dpkg-verify")]
+    #[test]
+    fn test_check_dpkg_output() {
+        let fake_warning =
+            "dpkg-parsechangelog: warning:     debian/changelog(l5): badly formatted trailer line";
+
+        assert!(Changelog::check_dpkg_output(true, "").is_ok());
+        assert!(matches!(
+            Changelog::check_dpkg_output(true, fake_warning),
+            Err(DebyError::ChangelogVerify(e)) if e == fake_warning
+        ));
+        assert!(Changelog::check_dpkg_output(false, "error").is_err());
+    }
+
+    #[cfg(feature = "dpkg-verify")]
+    #[test]
+    fn test_verify_long_changelog() {
+        let fake_entry = |i: usize, changes: &str| {
+            format!(
+                "foo (1.0.{}) unstable; urgency=low\n\n{}\n -- John Doe <john@example.com>  Mon, 10 Jan 2022 10:00:00 +0300\n",
+                i, changes
+            )
+        };
+        let valid: Vec<String> = (1..3000)
+            .rev()
+            .map(|i| fake_entry(i, "  * change\n"))
+            .collect();
+        // every unindented line is reported while the tool is still reading the entry,
+        // so its warnings outgrow stderr pipe before the whole entry is written
+        let bad_lines: String = (0..20000).map(|i| format!("bad line {}\n", i)).collect();
+        let malformed = fake_entry(1, &bad_lines);
+
+        assert!(Changelog::verify(&valid.join("\n")).is_ok());
+        assert!(matches!(
+            Changelog::verify(&malformed),
+            Err(DebyError::ChangelogVerify(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_changelog_entry_binary_only() {
+        let mut fake_config: Config = serde_json::from_str("{}").unwrap();
+        fake_config.changelog.package = "foo".to_string();
+        let fake_options = ChangelogOptions {
+            maintainer: Some("John Doe <john@example.com>".to_string()),
+            date: Some("Mon, 10 Jan 2022 10:00:00 +0300".to_string()),
+            binary_only: true,
+            ..Default::default()
+        };
+
+        let actual = Changelog::format_changelog_entry(
+            &fake_config,
+            "1.2.3-1+b1",
+            "* Rebuild against libfoo2",
+            Urgency::Low,
+            "unstable",
+            &fake_options,
+        )
+        .unwrap();
+
+        assert!(actual.starts_with("\nfoo (1.2.3-1+b1) unstable; urgency=low, binary-only=yes\n"));
+        assert!(Changelog::strict_layout(&actual).is_ok());
+    }
 }
@@ -1,32 +1,282 @@
-use serde::Deserialize;
+use regex::Regex;
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Map, Value};
 
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt::Display;
+use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use super::{Config, Maintainer};
+use super::arch;
+use super::graph::{self, DependencyIssue, Node};
+use super::merge;
+use super::relation::Relation;
+use super::wrap;
+use super::{encoding, Config, Maintainer, Sign, ValidationIssue};
+use crate::deb822::{self, Paragraph};
+use crate::lint::Lint;
+use crate::DebyError;
+
+pub(crate) const CONTROL_FILE: &str = "debian/control";
 
 const PACKAGE: &str = "Package";
+const PACKAGE_TYPE: &str = "Package-Type";
+const ESSENTIAL: &str = "Essential";
+const PROTECTED: &str = "Protected";
 const PRIORITY: &str = "Priority";
 const HOME_PAGE: &str = "Homepage";
 const SECTION: &str = "Section";
 const MAINTAINER: &str = "Maintainer";
+const UPLOADERS: &str = "Uploaders";
 const PRE_DEPENDS: &str = "Pre-Depends";
+const DEPENDS: &str = "Depends";
+const RECOMMENDS: &str = "Recommends";
+const SUGGESTS: &str = "Suggests";
+const ENHANCES: &str = "Enhances";
+const BREAKS: &str = "Breaks";
+const CONFLICTS: &str = "Conflicts";
+const REPLACES: &str = "Replaces";
+const PROVIDES: &str = "Provides";
+const BUILT_USING: &str = "Built-Using";
 const BUILD_DEPENDS: &str = "Build-Depends";
+const BUILD_DEPENDS_ARCH: &str = "Build-Depends-Arch";
+const BUILD_DEPENDS_INDEP: &str = "Build-Depends-Indep";
+const BUILD_CONFLICTS: &str = "Build-Conflicts";
 const ARCH: &str = "Architecture";
 const DESC: &str = "Description";
 const SOURCE: &str = "Source";
 const STD_VER: &str = "Standards-Version";
 const VCS_BROWSER: &str = "Vcs-Browser";
+const VCS_GIT: &str = "Vcs-Git";
+const VCS_SVN: &str = "Vcs-Svn";
+const VCS_HG: &str = "Vcs-Hg";
+const TESTSUITE: &str = "Testsuite";
+const ORIGIN: &str = "Origin";
+const BUGS: &str = "Bugs";
+const MISC_DEPENDS: &str = "${misc:Depends}";
+/// Prefix of user-defined fields copied to source package only
+const SOURCE_FIELD_PREFIX: &str = "XS-";
+/// Conventional field order of source paragraph
+const SOURCE_FIELD_ORDER: &[&str] = &[
+    SOURCE,
+    SECTION,
+    PRIORITY,
+    MAINTAINER,
+    UPLOADERS,
+    BUILD_DEPENDS,
+    BUILD_DEPENDS_ARCH,
+    BUILD_DEPENDS_INDEP,
+    BUILD_CONFLICTS,
+    STD_VER,
+    HOME_PAGE,
+    VCS_BROWSER,
+    VCS_GIT,
+    VCS_SVN,
+    VCS_HG,
+    TESTSUITE,
+    ORIGIN,
+    BUGS,
+];
+/// Conventional field order of binary paragraph
+const BINARY_FIELD_ORDER: &[&str] = &[
+    PACKAGE,
+    PACKAGE_TYPE,
+    ARCH,
+    SECTION,
+    PRIORITY,
+    ESSENTIAL,
+    PROTECTED,
+    PRE_DEPENDS,
+    DEPENDS,
+    RECOMMENDS,
+    SUGGESTS,
+    ENHANCES,
+    BREAKS,
+    CONFLICTS,
+    REPLACES,
+    PROVIDES,
+    BUILT_USING,
+    DESC,
+];
+/// Fields written one relation per line when sorting is enabled
+const LIST_FIELDS: &[&str] = &[
+    BUILD_DEPENDS,
+    BUILD_DEPENDS_ARCH,
+    BUILD_DEPENDS_INDEP,
+    BUILD_CONFLICTS,
+    PRE_DEPENDS,
+    DEPENDS,
+    RECOMMENDS,
+    SUGGESTS,
+    ENHANCES,
+    BREAKS,
+    CONFLICTS,
+    REPLACES,
+    PROVIDES,
+    BUILT_USING,
+];
+/// Maximum width of folded extended description lines, including the leading space
+const DESC_WIDTH: usize = 79;
+/// Maximum length of description synopsis
+const SYNOPSIS_MAX_LENGTH: usize = 80;
+/// Latest Debian policy version known to this crate
+pub(crate) const LATEST_STANDARDS_VERSION: &str = "4.7.2";
+/// Policy versions of older major releases are considered very old
+const ANCIENT_STANDARDS_MAJOR: u32 = 4;
+/// Sections of Debian archive
+const SECTIONS: &[&str] = &[
+    "admin",
+    "cli-mono",
+    "comm",
+    "database",
+    "debian-installer",
+    "debug",
+    "devel",
+    "doc",
+    "editors",
+    "education",
+    "electronics",
+    "embedded",
+    "fonts",
+    "games",
+    "gnome",
+    "gnu-r",
+    "gnustep",
+    "graphics",
+    "hamradio",
+    "haskell",
+    "httpd",
+    "interpreters",
+    "introspection",
+    "java",
+    "javascript",
+    "kde",
+    "kernel",
+    "libdevel",
+    "libs",
+    "lisp",
+    "localization",
+    "mail",
+    "math",
+    "metapackages",
+    "misc",
+    "net",
+    "news",
+    "ocaml",
+    "oldlibs",
+    "otherosfs",
+    "perl",
+    "php",
+    "python",
+    "ruby",
+    "rust",
+    "science",
+    "shells",
+    "sound",
+    "tasks",
+    "tex",
+    "text",
+    "utils",
+    "vcs",
+    "video",
+    "web",
+    "x11",
+    "xfce",
+    "zope",
+];
+/// Archive areas a section can be prefixed with, `main` area has no prefix
+const AREAS: [&str; 3] = ["contrib", "non-free", "non-free-firmware"];
+/// Articles a synopsis shouldn't start with
+const ARTICLES: [&str; 3] = ["a", "an", "the"];
+/// Default section of installer components
+const UDEB_SECTION: &str = "debian-installer";
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct Control {
     update: bool,
-    #[serde(rename(deserialize = "sourceControl"))]
+    #[serde(rename(deserialize = "sourceControl"), alias = "source_control")]
     source_control: SourceControl,
-    #[serde(rename(deserialize = "binaryControl"))]
-    binary_control: BinaryControl,
+    #[serde(
+        rename(deserialize = "binaryControl"),
+        alias = "binary_control",
+        deserialize_with = "Control::deserialize_binaries"
+    )]
+    binary_control: Vec<BinaryControl>,
+    #[serde(
+        rename(deserialize = "miscDepends"),
+        alias = "misc_depends",
+        default = "Control::default_bool_value"
+    )]
+    misc_depends: bool,
+    /// Allow sections which are not Debian archive sections, e.g. internal ones
+    #[serde(
+        rename(deserialize = "allowCustomSections"),
+        alias = "allow_custom_sections",
+        default = "Control::default_bool_value"
+    )]
+    allow_custom_sections: bool,
+    /// Use the latest known policy version when `standardsVersion` is omitted
+    #[serde(
+        rename(deserialize = "fillStandardsVersion"),
+        alias = "fill_standards_version",
+        default = "Control::default_bool_value"
+    )]
+    fill_standards_version: bool,
+    /// Write deprecated `extra` priority as `optional`
+    #[serde(
+        rename(deserialize = "rewriteExtraPriority"),
+        alias = "rewrite_extra_priority",
+        default = "Control::default_bool_value"
+    )]
+    rewrite_extra_priority: bool,
+    /// Accept only `https` URLs in `homepage` and `vcsBrowser`
+    #[serde(
+        rename(deserialize = "requireHttps"),
+        alias = "require_https",
+        default = "Control::default_bool_value"
+    )]
+    require_https: bool,
+    /// Paragraphs written on update, the other ones are kept from existing _control_ file
+    #[serde(default = "Control::default_paragraphs")]
+    paragraphs: Paragraphs,
+    /// Order fields and sort relationship fields the same way `wrap-and-sort` does
+    #[serde(
+        rename(deserialize = "wrapAndSort"),
+        alias = "wrap_and_sort",
+        default = "Control::default_bool_value"
+    )]
+    wrap_and_sort: bool,
+    /// Update only generated fields of existing _control_ file, keeping other fields,
+    /// paragraphs and comments instead of rewriting the whole file
+    #[serde(default = "Control::default_bool_value")]
+    merge: bool,
+}
+
+/// Visits `binaryControl` value, unlike an untagged enum it keeps unknown keys
+/// of binary packages visible to strict config parsing
+struct BinariesVisitor;
+
+impl<'de> Visitor<'de> for BinariesVisitor {
+    type Value = Vec<BinaryControl>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a binary package or an array of binary packages")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        Ok(vec![BinaryControl::deserialize(
+            MapAccessDeserializer::new(map),
+        )?])
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        Vec::deserialize(SeqAccessDeserializer::new(seq))
+    }
 }
 
 impl Control {
@@ -35,28 +285,40 @@ impl Control {
     /// # Arguments
     ///
     /// - `config` - data from config file `.debyrc`
-    /// - `user_defined_fields` - dynamic field values provided by a user
-    fn create_contents(config: &Config, user_defined_fields: Vec<&str>) -> String {
-        let additional = Control::format_additional_fields(user_defined_fields);
-
-        let source = Control::format_source_contents(&config);
-        let binary = Control::format_binary_contents(&config);
-
-        let contents = format!(
-            "
-{source_data}
+    /// - `custom_fields` - user-defined fields appended to their paragraphs
+    ///   after fields declared in config file
+    fn create_contents(
+        config: &Config,
+        custom_fields: &[CustomField],
+    ) -> Result<String, DebyError> {
+        let mut fields = config.control.custom_fields();
+        fields.extend_from_slice(custom_fields);
+        let custom_fields = fields.as_slice();
+
+        let unknown = custom_fields.iter().find_map(|f| match &f.placement {
+            Placement::Binary(p) if !config.control.binary_names().contains(&p.as_str()) => Some(p),
+            _ => None,
+        });
+        if let Some(package) = unknown {
+            return Err(DebyError::UnknownBinaryPackage(package.to_string()));
+        }
 
-{binary_data}
-{additional}
-",
-            source_data = source,
-            binary_data = binary,
-            additional = additional,
-        );
+        let mut paragraphs = vec![Control::with_custom_fields(
+            Control::format_source_contents(config),
+            custom_fields,
+            &Placement::Source,
+        )?];
+        for binary in &config.control.binary_control {
+            paragraphs.push(Control::with_custom_fields(
+                Control::format_binary_contents(binary, Control::adds_misc_depends(config)),
+                custom_fields,
+                &Placement::Binary(binary.package.clone()),
+            )?);
+        }
 
-        let mut s = contents.trim().to_string();
+        let mut s = paragraphs.join("\n\n").trim().to_string();
         s.push('\n');
-        s
+        Ok(s)
     }
 
     /// Formats _string_ value
@@ -119,45 +381,49 @@ impl Control {
     ///
     /// # Arguments
     ///
-    /// - `config` - data from config file `.debyrc`
-    fn format_binary_contents(config: &Config) -> String {
+    /// - `binary` - binary package data from config file `.debyrc`
+    /// - `misc_depends` - add `${misc:Depends}` to `Depends` field
+    fn format_binary_contents(binary: &BinaryControl, misc_depends: bool) -> String {
         let mut binary_data = String::new();
 
-        Control::format_str(
-            PACKAGE,
-            &config.control.binary_control.package,
-            &mut binary_data,
-        );
+        Control::format_str(PACKAGE, &binary.package, &mut binary_data);
 
-        Control::format_str(
-            SECTION,
-            &config.control.binary_control.section,
-            &mut binary_data,
-        );
+        if binary.package_type == PackageType::Udeb {
+            Control::format_custom_data(PACKAGE_TYPE, &binary.package_type, &mut binary_data);
+        }
 
-        Control::format_custom_data(
-            PRIORITY,
-            &config.control.binary_control.priority,
-            &mut binary_data,
-        );
+        let section = match binary.section.is_empty() && binary.package_type == PackageType::Udeb {
+            true => UDEB_SECTION,
+            false => &binary.section,
+        };
+        Control::format_str(SECTION, section, &mut binary_data);
 
-        Control::format_str(
-            PRE_DEPENDS,
-            &config.control.binary_control.pre_depends,
-            &mut binary_data,
-        );
+        Control::format_custom_data(PRIORITY, &binary.priority, &mut binary_data);
 
-        Control::format_custom_data(
-            ARCH,
-            &config.control.binary_control.architecture,
-            &mut binary_data,
-        );
+        if binary.essential {
+            Control::format_str(ESSENTIAL, "yes", &mut binary_data);
+        }
+        if binary.protected {
+            Control::format_str(PROTECTED, "yes", &mut binary_data);
+        }
 
-        Control::format_str(
-            DESC,
-            &config.control.binary_control.description,
-            &mut binary_data,
-        );
+        for (key, val) in binary.relations() {
+            let val = match key == DEPENDS && misc_depends && !val.contains(MISC_DEPENDS) {
+                true if val.trim().is_empty() => MISC_DEPENDS.to_string(),
+                true => format!("{}, {}", MISC_DEPENDS, val),
+                false => val.to_string(),
+            };
+            Control::format_str(key, &val, &mut binary_data);
+        }
+
+        Control::format_vec(BUILT_USING, &binary.built_using, &mut binary_data);
+
+        Control::format_custom_data(ARCH, &binary.architecture, &mut binary_data);
+
+        let description = binary.description.format();
+        if !description.is_empty() {
+            binary_data.push_str(&format!("{}\n", deb822::Field::new(DESC, &description)));
+        }
 
         binary_data.trim().to_string()
     }
@@ -192,15 +458,22 @@ impl Control {
         let email = &config.control.source_control.maintainer.email;
         Control::format_maintainer(name, email, &mut source_data);
 
-        Control::format_vec(
-            BUILD_DEPENDS,
-            &config.control.source_control.build_depends,
-            &mut source_data,
-        );
+        let uploaders: Vec<String> = config
+            .control
+            .source_control
+            .uploaders
+            .iter()
+            .map(|u| format!("{} <{}>", u.name, u.email))
+            .collect();
+        Control::format_str(UPLOADERS, &uploaders.join(", "), &mut source_data);
+
+        for (key, values) in config.control.source_control.relations() {
+            Control::format_vec(key, values, &mut source_data);
+        }
 
         Control::format_str(
             STD_VER,
-            &config.control.source_control.standards_version,
+            config.control.standards_version(),
             &mut source_data,
         );
 
@@ -216,21 +489,211 @@ impl Control {
             &mut source_data,
         );
 
+        Control::format_str(
+            VCS_GIT,
+            &config.control.source_control.vcs_git,
+            &mut source_data,
+        );
+
+        Control::format_str(
+            VCS_SVN,
+            &config.control.source_control.vcs_svn,
+            &mut source_data,
+        );
+
+        Control::format_str(
+            VCS_HG,
+            &config.control.source_control.vcs_hg,
+            &mut source_data,
+        );
+
+        Control::format_str(
+            TESTSUITE,
+            &config.control.source_control.testsuite,
+            &mut source_data,
+        );
+
+        Control::format_str(
+            ORIGIN,
+            &config.control.source_control.origin,
+            &mut source_data,
+        );
+
+        Control::format_str(BUGS, &config.control.source_control.bugs, &mut source_data);
+
         source_data.trim().to_string()
     }
 
-    /// Formats additional values to be used in _control_ file
+    /// Returns conventional capitalization of a field name: known fields are spelled
+    /// the way deby writes them, `X`, `XS`, `XB` and `XC` prefixes are upper-cased,
+    /// other ones get every dash separated word capitalized
     ///
     /// # Arguments
     ///
-    /// - `user_defined_fields` - dynamic fields defined by a user
-    fn format_additional_fields(user_defined_fields: Vec<&str>) -> String {
-        let mut additional = String::new();
-        for field in user_defined_fields {
-            additional.push_str(&format!("{}\n", field));
+    /// - `name` - field name, e.g. `vcs-browser`
+    fn normalize_field_name(name: &str) -> String {
+        let known = SOURCE_FIELD_ORDER
+            .iter()
+            .chain(BINARY_FIELD_ORDER)
+            .find(|f| f.eq_ignore_ascii_case(name));
+        if let Some(known) = known {
+            return known.to_string();
+        }
+
+        name.split('-')
+            .enumerate()
+            .map(|(i, word)| {
+                let is_prefix = i == 0
+                    && word.len() <= 2
+                    && word.starts_with(['x', 'X'])
+                    && word.chars().skip(1).all(|c| "sbcSBC".contains(c));
+                if is_prefix {
+                    return word.to_ascii_uppercase();
+                }
+
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(c) => c.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("-")
+    }
+
+    /// Appends user-defined fields placed into a paragraph to it, field names are normalized.
+    /// Fields already present in the paragraph or given twice are rejected
+    ///
+    /// # Arguments
+    ///
+    /// - `paragraph` - generated paragraph
+    /// - `custom_fields` - user-defined fields of all paragraphs
+    /// - `placement` - placement of the paragraph
+    fn with_custom_fields(
+        paragraph: String,
+        custom_fields: &[CustomField],
+        placement: &Placement,
+    ) -> Result<String, DebyError> {
+        let mut acc = deb822::parse(&paragraph)?.pop().unwrap_or_default();
+        for field in custom_fields.iter().filter(|f| &f.placement == placement) {
+            let name = Control::normalize_field_name(field.name.trim());
+            if acc.get(&name).is_some() {
+                return Err(DebyError::DuplicateField(name));
+            }
+
+            acc.fields.push(deb822::Field::new(&name, &field.value));
+        }
+
+        Ok(acc.to_string().trim().to_string())
+    }
+
+    /// Renders _control_ file contents exactly as they are written on update,
+    /// relationship fields are checked first
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `custom_fields` - user-defined fields appended to their paragraphs
+    /// - `paragraphs` - paragraphs to be generated
+    pub(crate) fn render(
+        config: &Config,
+        custom_fields: &[CustomField],
+        paragraphs: &Paragraphs,
+    ) -> Result<String, Box<dyn Error>> {
+        Control::check_relations(config)?;
+
+        if config.control.binary_control.len() > 1 {
+            let issues = Control::dependency_issues(config);
+            if !issues.is_empty() {
+                return Err(DebyError::DependencyGraph(issues).into());
+            }
+        }
+
+        let control_file = config.path(CONTROL_FILE);
+        let mut contents = config.control.wrap_and_sort(
+            &config.interpolate(&Control::create_contents(config, custom_fields)?),
+        )?;
+        if *paragraphs != Paragraphs::All {
+            let generated: Vec<Paragraph> = deb822::parse(&contents)?
+                .into_iter()
+                .filter(|p| paragraphs.generates(p))
+                .collect();
+            contents = deb822::to_string(&generated);
+        }
+
+        if config.control.merge {
+            let mut managed: Vec<String> = SOURCE_FIELD_ORDER
+                .iter()
+                .chain(BINARY_FIELD_ORDER)
+                .map(|f| f.to_string())
+                .collect();
+            managed.extend(
+                config
+                    .control
+                    .custom_fields()
+                    .iter()
+                    .chain(custom_fields)
+                    .map(|f| Control::normalize_field_name(f.name.trim())),
+            );
+            let managed: Vec<&str> = managed.iter().map(String::as_str).collect();
+            contents = merge::merge(
+                &encoding::read_to_string(&control_file)?,
+                &contents,
+                &managed,
+            )?;
+        } else if *paragraphs != Paragraphs::All && control_file.exists() {
+            let current = encoding::read_to_string(&control_file)?;
+            contents = Control::with_kept_paragraphs(&current, &contents, paragraphs)?;
+        }
+        Control::check_kept_paragraphs(&contents, paragraphs)?;
+
+        Ok(contents)
+    }
+
+    /// Checks that paragraphs which are not generated are kept from existing _control_ file,
+    /// otherwise a file with only a half of paragraphs would be written
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - _control_ file contents to be written
+    /// - `paragraphs` - paragraphs which are generated
+    fn check_kept_paragraphs(contents: &str, paragraphs: &Paragraphs) -> Result<(), DebyError> {
+        let kept = match paragraphs {
+            Paragraphs::All => return Ok(()),
+            Paragraphs::Source => "binary",
+            Paragraphs::Binary => "source",
+        };
+        let found = deb822::parse(contents)?
+            .iter()
+            .any(|p| !p.fields.is_empty() && !paragraphs.generates(p));
+
+        match found {
+            true => Ok(()),
+            false => Err(DebyError::KeptParagraphsMissing(kept.to_string())),
         }
+    }
 
-        additional.trim().to_string()
+    /// Adds paragraphs of existing _control_ file which are not generated to generated ones,
+    /// source paragraph goes first
+    ///
+    /// # Arguments
+    ///
+    /// - `current_file_contents` - current _control_ file contents
+    /// - `generated` - generated paragraphs
+    /// - `paragraphs` - paragraphs which are generated
+    fn with_kept_paragraphs(
+        current_file_contents: &str,
+        generated: &str,
+        paragraphs: &Paragraphs,
+    ) -> Result<String, DebyError> {
+        let mut contents: Vec<Paragraph> = deb822::parse(current_file_contents)?
+            .into_iter()
+            .filter(|p| !paragraphs.generates(p))
+            .chain(deb822::parse(generated)?)
+            .collect();
+        contents.sort_by_key(|p| p.get(SOURCE).is_none());
+
+        Ok(deb822::to_string(&contents))
     }
 
     /// Updates _control_ file and writes its contents to `debian/control` file
@@ -238,180 +701,1913 @@ impl Control {
     /// # Arguments
     ///
     /// - `config` - data from config file `.debyrc`
-    /// - `user_defined_fields` - dynamic field values provided by a user
-    pub(crate) fn update<'a>(
+    /// - `custom_fields` - user-defined fields appended to their paragraphs
+    /// - `paragraphs` - paragraphs to be generated, config file setting is used for `None`
+    pub(crate) fn update(
         config: &Config,
-        user_defined_fields: Vec<&str>,
-    ) -> Result<&'a str, Box<dyn Error>> {
+        custom_fields: &[CustomField],
+        paragraphs: Option<&Paragraphs>,
+    ) -> Result<String, Box<dyn Error>> {
         if !config.control.update {
-            return Ok("debian/control file not updated due to config file setting");
+            return Ok("debian/control file not updated due to config file setting".to_string());
         }
 
+        let paragraphs = paragraphs.unwrap_or(&config.control.paragraphs);
+        let contents = Control::render(config, custom_fields, paragraphs)?;
+        let control_file = config.path(CONTROL_FILE);
         let mut file = OpenOptions::new()
             .read(true)
             .truncate(true)
             .write(true)
             .create(true)
-            .open("debian/control")?;
-
-        let contents = Control::create_contents(config, user_defined_fields);
+            .open(&control_file)?;
 
         file.write_all(contents.as_bytes())?;
+        Sign::sign_file(config, &control_file)?;
 
-        Ok("Successfully created a new entry in debian/control file")
+        let binaries: Vec<&BinaryControl> = config.control.binary_control.iter().collect();
+        Ok(Control::with_warnings(
+            "Successfully created a new entry in debian/control file",
+            &binaries,
+        ))
     }
 
-    pub(crate) fn default() -> Self {
-        Self {
-            update: false,
-            source_control: SourceControl {
-                source: "".to_string(),
-                maintainer: Maintainer {
-                    name: "".to_string(),
-                    email: "".to_string(),
-                },
-                section: "".to_string(),
-                priority: Priority::Optional,
-                build_depends: vec![],
-                standards_version: "".to_string(),
-                homepage: "".to_string(),
-                vcs_browser: "".to_string(),
-            },
-            binary_control: BinaryControl {
-                package: "".to_string(),
-                description: "".to_string(),
-                section: "".to_string(),
-                priority: Priority::Optional,
-                pre_depends: "".to_string(),
-                architecture: Architecture::Any,
-            },
+    /// Orders fields of every paragraph canonically and sorts relationship fields
+    /// if it's enabled in config file, contents are returned as is otherwise
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - _control_ file contents or a single paragraph
+    fn wrap_and_sort(&self, contents: &str) -> Result<String, DebyError> {
+        if !self.wrap_and_sort {
+            return Ok(contents.to_string());
         }
-    }
 
-    fn default_string_value() -> String {
-        "".to_string()
-    }
+        let paragraphs: Vec<Paragraph> = deb822::parse(contents)?
+            .into_iter()
+            .map(|p| match p.get(SOURCE) {
+                Some(_) => wrap::wrap_and_sort(p, SOURCE_FIELD_ORDER, LIST_FIELDS),
+                None => wrap::wrap_and_sort(p, BINARY_FIELD_ORDER, LIST_FIELDS),
+            })
+            .collect();
 
-    fn default_vec_value() -> Vec<String> {
-        vec![]
+        Ok(deb822::to_string(&paragraphs))
     }
-}
 
-#[derive(Deserialize, Debug, PartialEq)]
-enum Architecture {
-    #[serde(rename(deserialize = "all"))]
-    All,
-    #[serde(rename(deserialize = "any"))]
-    Any,
-}
-
-impl Display for Architecture {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Architecture::All => write!(f, "all"),
-            Architecture::Any => write!(f, "any"),
+    /// Appends a binary paragraph to existing _control_ file contents
+    /// leaving all other paragraphs untouched
+    ///
+    /// # Arguments
+    ///
+    /// - `current_file_contents` - current _control_ file contents
+    /// - `package` - binary package name
+    /// - `paragraph` - formatted binary paragraph to be appended
+    fn append_paragraph(
+        current_file_contents: &str,
+        package: &str,
+        paragraph: &str,
+    ) -> Result<String, DebyError> {
+        if current_file_contents.trim().is_empty() {
+            return Err(DebyError::ControlMissing);
         }
-    }
-}
 
-#[derive(Deserialize, Debug, PartialEq)]
-enum Priority {
-    #[serde(rename(deserialize = "required"))]
-    Required,
-    #[serde(rename(deserialize = "important"))]
-    Important,
-    #[serde(rename(deserialize = "standard"))]
-    Standard,
-    #[serde(rename(deserialize = "optional"))]
-    Optional,
-    #[serde(rename(deserialize = "extra"))]
-    Extra,
-}
+        let exists = deb822::parse(current_file_contents)?
+            .iter()
+            .any(|p| p.get(PACKAGE) == Some(package));
+        if exists {
+            return Err(DebyError::BinaryPackageExists(package.to_string()));
+        }
 
-impl Display for Priority {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Priority::Required => write!(f, "required"),
-            Priority::Important => write!(f, "important"),
-            Priority::Standard => write!(f, "standard"),
-            Priority::Optional => write!(f, "optional"),
-            Priority::Extra => write!(f, "extra"),
+        let mut contents = current_file_contents.to_string();
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        if !contents.ends_with("\n\n") {
+            contents.push('\n');
         }
+        contents.push_str(paragraph.trim());
+        contents.push('\n');
+
+        Ok(contents)
     }
-}
 
-#[derive(Deserialize, Debug)]
-struct BinaryControl {
-    #[serde(default = "Control::default_string_value")]
-    package: String,
-    #[serde(default = "Control::default_string_value")]
-    description: String,
-    #[serde(default = "Control::default_string_value")]
-    section: String,
-    priority: Priority,
-    #[serde(
-        rename(deserialize = "preDepends"),
-        default = "Control::default_string_value"
-    )]
-    pre_depends: String,
-    architecture: Architecture,
-}
+    /// Appends a configured binary package paragraph to `debian/control` file
+    /// without regenerating existing paragraphs
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `package` - name of a binary package defined in config file
+    /// - `custom_fields` - user-defined fields, all of them must be placed into the binary package
+    pub(crate) fn append_binary(
+        config: &Config,
+        package: &str,
+        custom_fields: &[CustomField],
+    ) -> Result<String, Box<dyn Error>> {
+        let binary = config
+            .control
+            .binary_control
+            .iter()
+            .find(|b| b.package == package)
+            .ok_or_else(|| DebyError::UnknownBinaryPackage(package.to_string()))?;
+        binary.check_relations()?;
+
+        let placement = Placement::Binary(package.to_string());
+        if let Some(field) = custom_fields.iter().find(|f| f.placement != placement) {
+            return Err(DebyError::FieldNotAppended(field.name.clone()).into());
+        }
 
-#[derive(Deserialize, Debug)]
-struct SourceControl {
-    #[serde(default = "Control::default_string_value")]
-    source: String,
-    maintainer: Maintainer,
-    #[serde(default = "Control::default_string_value")]
-    section: String,
-    priority: Priority,
-    #[serde(
-        rename(deserialize = "buildDepends"),
-        default = "Control::default_vec_value"
-    )]
-    build_depends: Vec<String>,
-    #[serde(
-        rename(deserialize = "standardsVersion"),
-        default = "Control::default_string_value"
-    )]
-    standards_version: String,
-    #[serde(default = "Control::default_string_value")]
-    homepage: String,
-    #[serde(
-        rename(deserialize = "vcsBrowser"),
-        default = "Control::default_string_value"
-    )]
-    vcs_browser: String,
-}
+        let mut fields = binary.custom_fields();
+        fields.extend_from_slice(custom_fields);
+        let paragraph = Control::with_custom_fields(
+            Control::format_binary_contents(binary, Control::adds_misc_depends(config)),
+            &fields,
+            &placement,
+        )?;
+        let paragraph = config
+            .control
+            .wrap_and_sort(&config.interpolate(&paragraph))?;
+
+        let control_file = config.path(CONTROL_FILE);
+        let current_file = encoding::read_to_string(&control_file)?;
+        let contents = Control::append_paragraph(&current_file, package, &paragraph)?;
+
+        fs::write(&control_file, contents)?;
+        Sign::sign_file(config, &control_file)?;
+
+        Ok(Control::with_warnings(
+            "Successfully appended a binary package to debian/control file",
+            &[binary],
+        ))
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Appends a warning for every binary package marked `Essential` or `Protected`,
+    /// such packages can't be removed easily once installed
+    ///
+    /// # Arguments
+    ///
+    /// - `msg` - message of a successful update
+    /// - `binaries` - written binary packages
+    fn with_warnings(msg: &str, binaries: &[&BinaryControl]) -> String {
+        let mut msg = msg.to_string();
+        for binary in binaries {
+            if binary.essential {
+                msg.push_str(&format!(
+                    "\nWARNING: {} is marked Essential: yes, it must work unconfigured and can't be removed",
+                    binary.package
+                ));
+            }
+            if binary.protected {
+                msg.push_str(&format!(
+                    "\nWARNING: {} is marked Protected: yes, it can't be removed without --allow-remove-essential",
+                    binary.package
+                ));
+            }
+        }
 
-    #[test]
-    fn test_default() {
-        let default = Control::default();
-        let empty_str = String::new();
-        let empty_vec: Vec<String> = vec![];
+        msg
+    }
 
-        assert_eq!(default.update, false);
+    /// Returns `true` if `${misc:Depends}` is added to binary packages:
+    /// it's enabled in config file and source package is built with debhelper
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    fn adds_misc_depends(config: &Config) -> bool {
+        config.control.misc_depends
+            && config
+                .control
+                .source_control
+                .build_depends
+                .iter()
+                .filter_map(|d| Relation::parse(d).ok())
+                .any(|r| r.name == "debhelper" || r.name == "debhelper-compat")
+    }
 
-        assert_eq!(default.source_control.source, empty_str);
-        assert_eq!(default.source_control.maintainer.name, empty_str);
-        assert_eq!(default.source_control.maintainer.email, empty_str);
-        assert_eq!(default.source_control.section, empty_str);
+    /// Checks syntax of relationship fields of source and all binary packages
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    fn check_relations(config: &Config) -> Result<(), DebyError> {
+        let source = &config.control.source_control;
+        for (field, values) in source.relations() {
+            for value in values {
+                Control::check_relation(&source.source, field, value)?;
+            }
+        }
+        for binary in &config.control.binary_control {
+            binary.check_relations()?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks syntax of a single relationship field value
+    ///
+    /// # Arguments
+    ///
+    /// - `package` - package name the field belongs to
+    /// - `field` - field name, e.g. `Depends`
+    /// - `value` - field value
+    fn check_relation(package: &str, field: &str, value: &str) -> Result<(), DebyError> {
+        Relation::check(value).map_err(|e| {
+            DebyError::InvalidRelationSyntax(
+                format!("{} {}", package, field),
+                value.to_string(),
+                e.to_string(),
+            )
+        })
+    }
+
+    /// Validates relationship fields of all configured binary packages
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    pub(crate) fn dependency_issues(config: &Config) -> Vec<DependencyIssue> {
+        let nodes: Vec<Node> = config
+            .control
+            .binary_control
+            .iter()
+            .map(|b| Node {
+                package: &b.package,
+                fields: b.relation_fields(),
+            })
+            .collect();
+
+        graph::validate(&nodes)
+    }
+
+    /// Checks that a section is a Debian archive section, optionally prefixed with an area,
+    /// e.g. `utils` or `contrib/utils`
+    ///
+    /// # Arguments
+    ///
+    /// - `section` - section value
+    fn is_known_section(section: &str) -> bool {
+        let section = match section.split_once('/') {
+            Some((area, section)) if AREAS.contains(&area) => section,
+            Some(_) => return false,
+            None => section,
+        };
+
+        SECTIONS.contains(&section)
+    }
+
+    /// Reports sections of source and binary packages which are not Debian archive sections,
+    /// nothing is reported when custom sections are allowed
+    pub(crate) fn section_issues(&self) -> Vec<ValidationIssue> {
+        if self.allow_custom_sections {
+            return vec![];
+        }
+
+        let mut sections = vec![(
+            "control.sourceControl.section".to_string(),
+            &self.source_control.section,
+        )];
+        sections.extend(
+            self.binary_control
+                .iter()
+                .enumerate()
+                .map(|(i, b)| (format!("control.binaryControl[{}].section", i), &b.section)),
+        );
+
+        sections
+            .into_iter()
+            .filter(|(_, section)| !section.is_empty() && !Control::is_known_section(section))
+            .map(|(path, section)| {
+                ValidationIssue::new(
+                    &path,
+                    &format!(
+                        "unknown section {}, set allowCustomSections to use it",
+                        section
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// Checks syntax of a URL: `http` or `https` scheme followed by a host name
+    /// and an optional port, only `https` is accepted when it's required in config file
+    ///
+    /// # Arguments
+    ///
+    /// - `url` - URL value
+    fn url_issue(&self, url: &str) -> Option<String> {
+        let re = Regex::new(r"^([A-Za-z][A-Za-z0-9+.-]*)://([^/?#\s]*)([/?#]\S*)?$").unwrap();
+        let host_re = Regex::new(
+            r"^[A-Za-z0-9]([A-Za-z0-9-]*[A-Za-z0-9])?(\.[A-Za-z0-9]([A-Za-z0-9-]*[A-Za-z0-9])?)*(:\d+)?$",
+        )
+        .unwrap();
+
+        let Some(caps) = re.captures(url) else {
+            return Some(format!("malformed URL {}, expected scheme://host", url));
+        };
+        let scheme = caps[1].to_ascii_lowercase();
+        if scheme != "http" && scheme != "https" {
+            return Some(format!("unsupported URL scheme {} in {}", scheme, url));
+        }
+        if !host_re.is_match(&caps[2]) {
+            return Some(format!("malformed URL {}, invalid host {}", url, &caps[2]));
+        }
+        if self.require_https && scheme != "https" {
+            return Some(format!("insecure URL {}, https is required", url));
+        }
+
+        None
+    }
+
+    /// Reports malformed `homepage` and `vcsBrowser` URLs of source package
+    pub(crate) fn url_issues(&self) -> Vec<ValidationIssue> {
+        [
+            ("homepage", &self.source_control.homepage),
+            ("vcsBrowser", &self.source_control.vcs_browser),
+        ]
+        .into_iter()
+        .filter(|(_, url)| !url.is_empty())
+        .filter_map(|(key, url)| {
+            self.url_issue(url).map(|message| {
+                ValidationIssue::new(&format!("control.sourceControl.{}", key), &message)
+            })
+        })
+        .collect()
+    }
+
+    /// Returns standards version of source package, the latest known policy version
+    /// is used for an omitted value if it's enabled in config file
+    fn standards_version(&self) -> &str {
+        let standards_version = self.source_control.standards_version.as_str();
+        match standards_version.is_empty() && self.fill_standards_version {
+            true => LATEST_STANDARDS_VERSION,
+            false => standards_version,
+        }
+    }
+
+    /// Warns about standards version of an older major policy release
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    pub(crate) fn standards_version_lints(config: &Config) -> Vec<Lint> {
+        let standards_version = config.control.standards_version();
+        let major = standards_version
+            .split('.')
+            .next()
+            .and_then(|m| m.parse::<u32>().ok());
+
+        match major {
+            Some(major) if major < ANCIENT_STANDARDS_MAJOR => vec![Lint::warning(
+                "ancient-standards-version",
+                format!(
+                    "standards version {} is very old, the latest known one is {}",
+                    standards_version, LATEST_STANDARDS_VERSION
+                ),
+            )],
+            _ => vec![],
+        }
+    }
+
+    /// Replaces deprecated `extra` priority of source and binary packages
+    /// with `optional` if it's enabled in config file
+    pub(crate) fn rewrite_extra_priority(&mut self) {
+        if !self.rewrite_extra_priority {
+            return;
+        }
+
+        let priorities = std::iter::once(&mut self.source_control.priority)
+            .chain(self.binary_control.iter_mut().map(|b| &mut b.priority));
+        for priority in priorities {
+            if *priority == Priority::Extra {
+                *priority = Priority::Optional;
+            }
+        }
+    }
+
+    /// Warns about deprecated `extra` priority of source and binary packages
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    pub(crate) fn priority_lints(config: &Config) -> Vec<Lint> {
+        let control = &config.control;
+        let source = (control.source_name(), &control.source_control.priority);
+        let binaries = control
+            .binary_control
+            .iter()
+            .map(|b| (b.package.as_str(), &b.priority));
+
+        std::iter::once(source)
+            .chain(binaries)
+            .filter(|(_, priority)| **priority == Priority::Extra)
+            .map(|(package, _)| {
+                Lint::warning(
+                    "priority-extra-is-replaced-by-priority-optional",
+                    format!(
+                        "{} uses deprecated priority extra, use optional or set rewriteExtraPriority",
+                        package
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// Checks description synopses of all configured binary packages
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    pub(crate) fn description_lints(config: &Config) -> Vec<Lint> {
+        config
+            .control
+            .binary_control
+            .iter()
+            .flat_map(|b| b.description.lints(&b.package))
+            .collect()
+    }
+
+    /// Returns config key of a known field, e.g. `vcsBrowser` for `Vcs-Browser`
+    ///
+    /// # Arguments
+    ///
+    /// - `name` - canonical field name
+    fn config_key(name: &str) -> String {
+        name.split('-')
+            .enumerate()
+            .map(|(i, word)| match i {
+                0 => word.to_ascii_lowercase(),
+                _ => word.to_string(),
+            })
+            .collect()
+    }
+
+    /// Splits a relationship field into relations with whitespace of folded lines collapsed
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - unfolded field value
+    fn import_relations(value: &str) -> Vec<String> {
+        value
+            .split(',')
+            .map(|r| r.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|r| !r.is_empty())
+            .collect()
+    }
+
+    /// Parses a `Name <email>` value into a maintainer object of config file
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - maintainer or uploader value
+    fn import_person(value: &str) -> Result<Value, DebyError> {
+        let malformed = || DebyError::ControlImport(format!("malformed maintainer {}", value));
+        let (name, email) = value.trim().rsplit_once('<').ok_or_else(malformed)?;
+        let email = email.strip_suffix('>').ok_or_else(malformed)?;
+
+        Ok(json!({"name": name.trim(), "email": email.trim()}))
+    }
+
+    /// Converts a paragraph of _control_ file into a `sourceControl` or `binaryControl` entry,
+    /// fields deby doesn't know are kept as `customFields`
+    ///
+    /// # Arguments
+    ///
+    /// - `paragraph` - source or binary paragraph
+    /// - `order` - known fields of the paragraph
+    fn import_paragraph(paragraph: &Paragraph, order: &[&str]) -> Result<Value, DebyError> {
+        let mut section = Map::new();
+        let mut custom_fields = Map::new();
+        for field in &paragraph.fields {
+            let name = Control::normalize_field_name(&field.name);
+            if !order.contains(&name.as_str()) {
+                custom_fields.insert(name, json!(field.value));
+                continue;
+            }
+
+            let relations = Control::import_relations(&field.value);
+            let value = match name.as_str() {
+                MAINTAINER => Control::import_person(&field.value)?,
+                UPLOADERS => field
+                    .value
+                    .split_inclusive('>')
+                    .map(|u| u.trim_start_matches([',', ' ', '\n']))
+                    .filter(|u| !u.trim().is_empty())
+                    .map(Control::import_person)
+                    .collect::<Result<Value, DebyError>>()?,
+                ESSENTIAL | PROTECTED => json!(field.value == "yes"),
+                BUILD_DEPENDS | BUILD_DEPENDS_ARCH | BUILD_DEPENDS_INDEP | BUILD_CONFLICTS
+                | BUILT_USING => json!(relations),
+                _ if LIST_FIELDS.contains(&name.as_str()) => json!(relations.join(", ")),
+                DESC => {
+                    let (synopsis, extended) =
+                        field.value.split_once('\n').unwrap_or((&field.value, ""));
+                    json!({"synopsis": synopsis, "extended": extended})
+                }
+                _ => json!(field.value),
+            };
+            section.insert(Control::config_key(&name), value);
+        }
+        if !custom_fields.is_empty() {
+            section.insert("customFields".to_string(), Value::Object(custom_fields));
+        }
+
+        Ok(Value::Object(section))
+    }
+
+    /// Converts _control_ file contents into `control` section of config file.
+    /// Binary packages without `Priority` inherit it from source paragraph
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - _control_ file contents
+    fn import(contents: &str) -> Result<Value, DebyError> {
+        let paragraphs: Vec<Paragraph> = deb822::parse(contents)?
+            .into_iter()
+            .filter(|p| !p.fields.is_empty())
+            .collect();
+        let (source, binaries) = paragraphs
+            .split_first()
+            .filter(|(s, _)| s.get(SOURCE).is_some())
+            .ok_or_else(|| DebyError::ControlImport("missing source paragraph".to_string()))?;
+
+        let mut source_control = Control::import_paragraph(source, SOURCE_FIELD_ORDER)?;
+        let priority = source.get(PRIORITY).unwrap_or("optional").to_string();
+        source_control["priority"] = json!(priority);
+
+        let mut binary_control = vec![];
+        for binary in binaries {
+            if binary.get(PACKAGE).is_none() {
+                return Err(DebyError::ControlImport(
+                    "binary paragraph without Package field".to_string(),
+                ));
+            }
+            let mut value = Control::import_paragraph(binary, BINARY_FIELD_ORDER)?;
+            if binary.get(PRIORITY).is_none() {
+                value["priority"] = json!(priority);
+            }
+            binary_control.push(value);
+        }
+
+        let control = json!({
+            "update": true,
+            "sourceControl": source_control,
+            "binaryControl": binary_control,
+        });
+        serde_json::from_value::<Control>(control.clone())
+            .map_err(|e| DebyError::ControlImport(e.to_string()))?;
+
+        Ok(control)
+    }
+
+    /// Reads existing _control_ file into `control` section of config file,
+    /// which lets packages with a hand-written _control_ file adopt deby
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - path to _control_ file
+    pub(crate) fn import_file(path: &Path) -> Result<Value, Box<dyn Error>> {
+        if !path.exists() {
+            return Err(DebyError::ControlMissing.into());
+        }
+
+        Ok(Control::import(&encoding::read_to_string(path)?)?)
+    }
+
+    /// Reads existing _control_ file into source and binary package definitions
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - path to _control_ file
+    pub(crate) fn from_file(path: &Path) -> Result<Control, Box<dyn Error>> {
+        Ok(serde_json::from_value(Control::import_file(path)?)?)
+    }
+
+    /// Returns custom fields of source and all binary packages declared in config file
+    fn custom_fields(&self) -> Vec<CustomField> {
+        let source =
+            self.source_control.custom_fields.iter().map(|(k, v)| {
+                CustomField::new(k, v.as_str().unwrap_or_default(), Placement::Source)
+            });
+
+        source
+            .chain(self.binary_control.iter().flat_map(|b| b.custom_fields()))
+            .collect()
+    }
+
+    /// Returns source package name
+    pub(crate) fn source_name(&self) -> &str {
+        &self.source_control.source
+    }
+
+    /// Returns names of all binary packages
+    pub(crate) fn binary_names(&self) -> Vec<&str> {
+        self.binary_control
+            .iter()
+            .map(|b| b.package.as_str())
+            .collect()
+    }
+
+    /// Returns paragraphs generated according to config file setting
+    pub(crate) fn paragraphs(&self) -> &Paragraphs {
+        &self.paragraphs
+    }
+
+    /// Returns `true` if _control_ file is updated according to config file setting
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.update
+    }
+
+    pub(crate) fn default() -> Self {
+        Self {
+            update: false,
+            source_control: SourceControl {
+                source: "".to_string(),
+                maintainer: Maintainer {
+                    name: "".to_string(),
+                    email: "".to_string(),
+                },
+                section: "".to_string(),
+                priority: Priority::Optional,
+                uploaders: vec![],
+                build_depends: vec![],
+                build_depends_arch: vec![],
+                build_depends_indep: vec![],
+                build_conflicts: vec![],
+                standards_version: "".to_string(),
+                homepage: "".to_string(),
+                vcs_browser: "".to_string(),
+                vcs_git: "".to_string(),
+                vcs_svn: "".to_string(),
+                vcs_hg: "".to_string(),
+                testsuite: "".to_string(),
+                origin: "".to_string(),
+                bugs: "".to_string(),
+                custom_fields: Map::new(),
+            },
+            binary_control: vec![BinaryControl {
+                package: "".to_string(),
+                description: Description::default(),
+                section: "".to_string(),
+                priority: Priority::Optional,
+                package_type: PackageType::Deb,
+                essential: false,
+                protected: false,
+                pre_depends: "".to_string(),
+                depends: "".to_string(),
+                recommends: "".to_string(),
+                suggests: "".to_string(),
+                enhances: "".to_string(),
+                breaks: "".to_string(),
+                conflicts: "".to_string(),
+                replaces: "".to_string(),
+                provides: "".to_string(),
+                built_using: vec![],
+                architecture: Architecture::Any,
+                custom_fields: Map::new(),
+            }],
+            misc_depends: false,
+            allow_custom_sections: false,
+            fill_standards_version: false,
+            rewrite_extra_priority: false,
+            require_https: false,
+            paragraphs: Paragraphs::All,
+            wrap_and_sort: false,
+            merge: false,
+        }
+    }
+
+    /// Deserializes `binaryControl` value which can be a single package or an array of packages
+    fn deserialize_binaries<'de, D>(deserializer: D) -> Result<Vec<BinaryControl>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(BinariesVisitor)
+    }
+
+    fn default_package_type() -> PackageType {
+        PackageType::Deb
+    }
+
+    fn default_paragraphs() -> Paragraphs {
+        Paragraphs::All
+    }
+
+    fn default_bool_value() -> bool {
+        false
+    }
+
+    fn default_string_value() -> String {
+        "".to_string()
+    }
+
+    fn default_vec_value() -> Vec<String> {
+        vec![]
+    }
+
+    fn default_uploaders() -> Vec<Maintainer> {
+        vec![]
+    }
+
+    fn default_custom_fields() -> Map<String, Value> {
+        Map::new()
+    }
+
+    /// Deserializes `customFields` keeping fields in the order of config file,
+    /// every value must be a string
+    fn deserialize_custom_fields<'de, D>(deserializer: D) -> Result<Map<String, Value>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = Map::deserialize(deserializer)?;
+        match fields.iter().find(|(_, v)| !v.is_string()) {
+            Some((name, value)) => Err(serde::de::Error::custom(format!(
+                "invalid type: {}, expected a string value of custom field {}",
+                value, name
+            ))),
+            None => Ok(fields),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(try_from = "String")]
+enum Architecture {
+    All,
+    Any,
+    /// Space separated architectures and wildcards, e.g. `amd64 arm64` or `linux-any`
+    List(Vec<String>),
+}
+
+impl TryFrom<String> for Architecture {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let archs: Vec<&str> = value.split_whitespace().collect();
+        match archs.as_slice() {
+            [] => return Err("architecture is empty".to_string()),
+            ["all"] => return Ok(Architecture::All),
+            ["any"] => return Ok(Architecture::Any),
+            _ => {}
+        }
+
+        for arch in &archs {
+            if *arch == "all" || *arch == "any" {
+                return Err(format!(
+                    "{} can't be combined with other architectures",
+                    arch
+                ));
+            }
+            if !arch::is_known(arch) {
+                return Err(format!("unknown architecture {}", arch));
+            }
+        }
+
+        Ok(Architecture::List(
+            archs.iter().map(|a| a.to_string()).collect(),
+        ))
+    }
+}
+
+impl Display for Architecture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Architecture::All => write!(f, "all"),
+            Architecture::Any => write!(f, "any"),
+            Architecture::List(archs) => write!(f, "{}", archs.join(" ")),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+enum PackageType {
+    #[serde(rename(deserialize = "deb"))]
+    Deb,
+    /// Installer component, _micro-deb_
+    #[serde(rename(deserialize = "udeb"))]
+    Udeb,
+}
+
+impl Display for PackageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageType::Deb => write!(f, "deb"),
+            PackageType::Udeb => write!(f, "udeb"),
+        }
+    }
+}
+
+/// Description of a binary package: a synopsis line and an optional extended description
+#[derive(Deserialize, Debug, PartialEq, Default)]
+#[serde(from = "DescriptionValue")]
+struct Description {
+    synopsis: String,
+    extended: String,
+}
+
+/// Description value of config file, either text with synopsis on the first line
+/// or an object with `synopsis` and `extended` keys
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DescriptionValue {
+    Text(String),
+    Structured {
+        synopsis: String,
+        #[serde(default = "Control::default_string_value")]
+        extended: String,
+    },
+}
+
+impl From<DescriptionValue> for Description {
+    fn from(value: DescriptionValue) -> Self {
+        match value {
+            DescriptionValue::Text(text) => {
+                let (synopsis, extended) = text.split_once('\n').unwrap_or((&text, ""));
+                Self {
+                    synopsis: synopsis.trim().to_string(),
+                    extended: extended.to_string(),
+                }
+            }
+            DescriptionValue::Structured { synopsis, extended } => Self {
+                synopsis: synopsis.trim().to_string(),
+                extended,
+            },
+        }
+    }
+}
+
+impl Description {
+    /// Formats `Description` field value: extended description is wrapped so that folded
+    /// lines fit 79 columns, paragraphs are separated by blank lines.
+    /// Lines starting with whitespace are kept verbatim
+    fn format(&self) -> String {
+        let mut lines = vec![self.synopsis.clone()];
+        let mut text = String::new();
+        let mut blank = false;
+        for line in self.extended.trim_matches('\n').lines() {
+            if line.trim().is_empty() {
+                lines.extend(wrap::fill(&std::mem::take(&mut text), DESC_WIDTH - 1));
+                blank = true;
+                continue;
+            }
+            if blank && lines.len() > 1 {
+                lines.push(String::new());
+            }
+            blank = false;
+            if line.starts_with(char::is_whitespace) {
+                lines.extend(wrap::fill(&std::mem::take(&mut text), DESC_WIDTH - 1));
+                lines.push(line.trim_end().to_string());
+            } else {
+                text.push_str(line);
+                text.push(' ');
+            }
+        }
+        lines.extend(wrap::fill(&text, DESC_WIDTH - 1));
+
+        lines.join("\n").trim().to_string()
+    }
+
+    /// Checks synopsis the way lintian does: length, trailing period,
+    /// package name or an article at the beginning
+    ///
+    /// # Arguments
+    ///
+    /// - `package` - binary package name
+    fn lints(&self, package: &str) -> Vec<Lint> {
+        let synopsis = self.synopsis.as_str();
+        if synopsis.is_empty() {
+            return vec![];
+        }
+
+        let mut lints = vec![];
+        let length = synopsis.chars().count();
+        if length > SYNOPSIS_MAX_LENGTH {
+            lints.push(Lint::warning(
+                "description-too-long",
+                format!(
+                    "{} synopsis is {} characters long, at most {} are allowed",
+                    package, length, SYNOPSIS_MAX_LENGTH
+                ),
+            ));
+        }
+        if synopsis.ends_with('.') && !synopsis.ends_with("..") {
+            lints.push(Lint::warning(
+                "synopsis-is-a-sentence",
+                format!("{} synopsis ends with a period", package),
+            ));
+        }
+        let first_word = synopsis
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if !package.is_empty() && first_word == package {
+            lints.push(Lint::warning(
+                "description-starts-with-package-name",
+                format!("{} synopsis starts with package name", package),
+            ));
+        }
+        if ARTICLES.contains(&first_word.as_str()) {
+            lints.push(Lint::warning(
+                "description-synopsis-starts-with-article",
+                format!("{} synopsis starts with an article", package),
+            ));
+        }
+
+        lints
+    }
+}
+
+/// Options of a single _control_ update overriding config file settings
+#[derive(Debug, Clone, Default)]
+pub struct ControlOptions {
+    /// User-defined fields placed into the source paragraph or a paragraph
+    /// of a certain binary package, written together with raw `Key: value` fields
+    pub fields: Vec<CustomField>,
+    /// Paragraphs to be written, `paragraphs` setting of config file is used for `None`
+    pub paragraphs: Option<Paragraphs>,
+    /// Path to config file read instead of `.debyrc`, e.g. `ci/deby.json`
+    pub config_path: Option<PathBuf>,
+    /// Profile from `profiles` map of config file to be applied, e.g. `bookworm`
+    pub profile: Option<String>,
+    /// Values for `{context.<key>}` placeholders, they override `context` from config file
+    pub context: BTreeMap<String, String>,
+}
+
+/// Paragraph of _control_ file a user-defined field is written to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Placement {
+    /// Source paragraph
+    Source,
+    /// Paragraph of a binary package with the given name
+    Binary(String),
+}
+
+/// A user-defined field of _control_ file, its name is capitalized the conventional way
+/// when it's written, e.g. `vcs-browser` is written as `Vcs-Browser`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomField {
+    /// Field name, e.g. `Multi-Arch`
+    pub name: String,
+    /// Field value, lines of a multi-line value are separated by `\n`
+    pub value: String,
+    pub placement: Placement,
+}
+
+impl CustomField {
+    pub fn new(name: &str, value: &str, placement: Placement) -> Self {
+        CustomField {
+            name: name.to_string(),
+            value: value.to_string(),
+            placement,
+        }
+    }
+
+    /// Parses raw `Key: value` user-defined fields, a line starting with whitespace
+    /// continues the previous field. `XS-` fields go to source paragraph,
+    /// other ones to the paragraph of `package` or source paragraph without it
+    ///
+    /// # Arguments
+    ///
+    /// - `user_defined_fields` - dynamic fields defined by a user
+    /// - `package` - binary package name
+    pub(crate) fn parse(
+        user_defined_fields: Vec<&str>,
+        package: Option<&str>,
+    ) -> Result<Vec<CustomField>, DebyError> {
+        let mut fields: Vec<CustomField> = vec![];
+        for line in user_defined_fields {
+            let split = line.split_once(':');
+            match (fields.last_mut(), split) {
+                (Some(field), _) if line.starts_with([' ', '\t']) => {
+                    field.value.push('\n');
+                    field.value.push_str(&line[1..]);
+                }
+                (_, Some((name, value))) => {
+                    let is_source = name
+                        .get(..SOURCE_FIELD_PREFIX.len())
+                        .is_some_and(|p| p.eq_ignore_ascii_case(SOURCE_FIELD_PREFIX));
+                    let placement = match (is_source, package) {
+                        (false, Some(package)) => Placement::Binary(package.to_string()),
+                        _ => Placement::Source,
+                    };
+                    fields.push(CustomField::new(name.trim(), value.trim(), placement));
+                }
+                _ => return Err(DebyError::UnknownField(line.to_string())),
+            }
+        }
+
+        Ok(fields)
+    }
+}
+
+/// Paragraphs of _control_ file written by deby, e.g. when another tool owns the other half
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Paragraphs {
+    /// The whole file
+    #[serde(rename(deserialize = "all"))]
+    All,
+    /// Source paragraph only
+    #[serde(rename(deserialize = "source"))]
+    Source,
+    /// Binary paragraphs only
+    #[serde(rename(deserialize = "binary"))]
+    Binary,
+}
+
+impl Paragraphs {
+    /// Returns `true` if a paragraph of _control_ file is generated
+    ///
+    /// # Arguments
+    ///
+    /// - `paragraph` - a single paragraph of _control_ file
+    fn generates(&self, paragraph: &Paragraph) -> bool {
+        let is_source = paragraph.get(SOURCE).is_some();
+        match self {
+            _ if paragraph.fields.is_empty() => false,
+            Paragraphs::All => true,
+            Paragraphs::Source => is_source,
+            Paragraphs::Binary => !is_source,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+enum Priority {
+    #[serde(rename(deserialize = "required"))]
+    Required,
+    #[serde(rename(deserialize = "important"))]
+    Important,
+    #[serde(rename(deserialize = "standard"))]
+    Standard,
+    #[serde(rename(deserialize = "optional"))]
+    Optional,
+    #[serde(rename(deserialize = "extra"))]
+    Extra,
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Required => write!(f, "required"),
+            Priority::Important => write!(f, "important"),
+            Priority::Standard => write!(f, "standard"),
+            Priority::Optional => write!(f, "optional"),
+            Priority::Extra => write!(f, "extra"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct BinaryControl {
+    #[serde(default = "Control::default_string_value")]
+    package: String,
+    #[serde(default = "Description::default")]
+    description: Description,
+    #[serde(default = "Control::default_string_value")]
+    section: String,
+    priority: Priority,
+    #[serde(
+        rename(deserialize = "packageType"),
+        alias = "package_type",
+        default = "Control::default_package_type"
+    )]
+    package_type: PackageType,
+    #[serde(default = "Control::default_bool_value")]
+    essential: bool,
+    #[serde(default = "Control::default_bool_value")]
+    protected: bool,
+    #[serde(
+        rename(deserialize = "preDepends"),
+        alias = "pre_depends",
+        default = "Control::default_string_value"
+    )]
+    pre_depends: String,
+    #[serde(default = "Control::default_string_value")]
+    depends: String,
+    #[serde(default = "Control::default_string_value")]
+    recommends: String,
+    #[serde(default = "Control::default_string_value")]
+    suggests: String,
+    #[serde(default = "Control::default_string_value")]
+    enhances: String,
+    #[serde(default = "Control::default_string_value")]
+    breaks: String,
+    #[serde(default = "Control::default_string_value")]
+    conflicts: String,
+    #[serde(default = "Control::default_string_value")]
+    replaces: String,
+    #[serde(default = "Control::default_string_value")]
+    provides: String,
+    #[serde(
+        rename(deserialize = "builtUsing"),
+        alias = "built_using",
+        default = "Control::default_vec_value"
+    )]
+    built_using: Vec<String>,
+    architecture: Architecture,
+    /// Extra fields written to the paragraph on every update, also accepted as `extraFields`
+    #[serde(
+        rename(deserialize = "customFields"),
+        alias = "custom_fields",
+        alias = "extraFields",
+        alias = "extra_fields",
+        default = "Control::default_custom_fields",
+        deserialize_with = "Control::deserialize_custom_fields"
+    )]
+    custom_fields: Map<String, Value>,
+}
+
+impl BinaryControl {
+    /// Returns relationship fields in the order they are written to _control_ file
+    fn relations(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            (PRE_DEPENDS, &self.pre_depends),
+            (DEPENDS, &self.depends),
+            (RECOMMENDS, &self.recommends),
+            (SUGGESTS, &self.suggests),
+            (ENHANCES, &self.enhances),
+            (BREAKS, &self.breaks),
+            (CONFLICTS, &self.conflicts),
+            (REPLACES, &self.replaces),
+            (PROVIDES, &self.provides),
+        ]
+    }
+
+    /// Returns custom fields declared in config file
+    fn custom_fields(&self) -> Vec<CustomField> {
+        self.custom_fields
+            .iter()
+            .map(|(k, v)| {
+                CustomField::new(
+                    k,
+                    v.as_str().unwrap_or_default(),
+                    Placement::Binary(self.package.clone()),
+                )
+            })
+            .collect()
+    }
+
+    /// Checks syntax of relationship fields
+    fn check_relations(&self) -> Result<(), DebyError> {
+        for (field, value) in self.relations() {
+            Control::check_relation(&self.package, field, value)?;
+        }
+        for value in &self.built_using {
+            Control::check_relation(&self.package, BUILT_USING, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns relationship fields with one relation per item
+    fn relation_fields(&self) -> Vec<(&'static str, Vec<String>)> {
+        let split = |s: &str| -> Vec<String> {
+            s.split(',')
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty())
+                .collect()
+        };
+
+        self.relations()
+            .into_iter()
+            .map(|(field, val)| (field, split(val)))
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceControl {
+    #[serde(default = "Control::default_string_value")]
+    source: String,
+    maintainer: Maintainer,
+    #[serde(default = "Control::default_uploaders")]
+    uploaders: Vec<Maintainer>,
+    #[serde(default = "Control::default_string_value")]
+    section: String,
+    priority: Priority,
+    #[serde(
+        rename(deserialize = "buildDepends"),
+        alias = "build_depends",
+        default = "Control::default_vec_value"
+    )]
+    build_depends: Vec<String>,
+    #[serde(
+        rename(deserialize = "buildDependsArch"),
+        alias = "build_depends_arch",
+        default = "Control::default_vec_value"
+    )]
+    build_depends_arch: Vec<String>,
+    #[serde(
+        rename(deserialize = "buildDependsIndep"),
+        alias = "build_depends_indep",
+        default = "Control::default_vec_value"
+    )]
+    build_depends_indep: Vec<String>,
+    #[serde(
+        rename(deserialize = "buildConflicts"),
+        alias = "build_conflicts",
+        default = "Control::default_vec_value"
+    )]
+    build_conflicts: Vec<String>,
+    #[serde(
+        rename(deserialize = "standardsVersion"),
+        alias = "standards_version",
+        default = "Control::default_string_value"
+    )]
+    standards_version: String,
+    #[serde(default = "Control::default_string_value")]
+    homepage: String,
+    #[serde(
+        rename(deserialize = "vcsBrowser"),
+        alias = "vcs_browser",
+        default = "Control::default_string_value"
+    )]
+    vcs_browser: String,
+    #[serde(
+        rename(deserialize = "vcsGit"),
+        alias = "vcs_git",
+        default = "Control::default_string_value"
+    )]
+    vcs_git: String,
+    #[serde(
+        rename(deserialize = "vcsSvn"),
+        alias = "vcs_svn",
+        default = "Control::default_string_value"
+    )]
+    vcs_svn: String,
+    #[serde(
+        rename(deserialize = "vcsHg"),
+        alias = "vcs_hg",
+        default = "Control::default_string_value"
+    )]
+    vcs_hg: String,
+    #[serde(default = "Control::default_string_value")]
+    testsuite: String,
+    /// Distribution the package comes from, e.g. a Debian derivative
+    #[serde(default = "Control::default_string_value")]
+    origin: String,
+    /// Bug tracking system of the package, e.g. `debbugs://bugs.example.com`
+    #[serde(default = "Control::default_string_value")]
+    bugs: String,
+    /// Extra fields written to the paragraph on every update, also accepted as `extraFields`
+    #[serde(
+        rename(deserialize = "customFields"),
+        alias = "custom_fields",
+        alias = "extraFields",
+        alias = "extra_fields",
+        default = "Control::default_custom_fields",
+        deserialize_with = "Control::deserialize_custom_fields"
+    )]
+    custom_fields: Map<String, Value>,
+}
+
+impl SourceControl {
+    /// Returns relationship fields in the order they are written to _control_ file
+    fn relations(&self) -> Vec<(&'static str, &[String])> {
+        vec![
+            (BUILD_DEPENDS, &self.build_depends),
+            (BUILD_DEPENDS_ARCH, &self.build_depends_arch),
+            (BUILD_DEPENDS_INDEP, &self.build_depends_indep),
+            (BUILD_CONFLICTS, &self.build_conflicts),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let default = Control::default();
+        let empty_str = String::new();
+        let empty_vec: Vec<String> = vec![];
+
+        assert!(!default.update);
+        assert!(!default.misc_depends);
+        assert!(!default.allow_custom_sections);
+        assert!(!default.fill_standards_version);
+        assert!(!default.rewrite_extra_priority);
+        assert!(!default.require_https);
+        assert_eq!(default.paragraphs, Paragraphs::All);
+        assert!(!default.wrap_and_sort);
+        assert!(!default.merge);
+
+        assert_eq!(default.source_control.source, empty_str);
+        assert_eq!(default.source_control.maintainer.name, empty_str);
+        assert_eq!(default.source_control.maintainer.email, empty_str);
+        assert!(default.source_control.uploaders.is_empty());
+        assert_eq!(default.source_control.section, empty_str);
         assert_eq!(default.source_control.priority, Priority::Optional);
         assert_eq!(default.source_control.build_depends, empty_vec);
+        assert_eq!(default.source_control.build_depends_arch, empty_vec);
+        assert_eq!(default.source_control.build_depends_indep, empty_vec);
+        assert_eq!(default.source_control.build_conflicts, empty_vec);
         assert_eq!(default.source_control.standards_version, empty_str);
         assert_eq!(default.source_control.homepage, empty_str);
         assert_eq!(default.source_control.vcs_browser, empty_str);
+        assert_eq!(default.source_control.vcs_git, empty_str);
+        assert_eq!(default.source_control.vcs_svn, empty_str);
+        assert_eq!(default.source_control.vcs_hg, empty_str);
+        assert_eq!(default.source_control.testsuite, empty_str);
+        assert_eq!(default.source_control.origin, empty_str);
+        assert_eq!(default.source_control.bugs, empty_str);
+        assert!(default.source_control.custom_fields.is_empty());
+
+        assert_eq!(default.binary_control[0].package, empty_str);
+        assert_eq!(
+            default.binary_control[0].description,
+            Description::default()
+        );
+        assert_eq!(default.binary_control[0].section, empty_str);
+        assert_eq!(default.binary_control[0].priority, Priority::Optional);
+        assert_eq!(default.binary_control[0].package_type, PackageType::Deb);
+        assert!(!default.binary_control[0].essential);
+        assert!(!default.binary_control[0].protected);
+        assert_eq!(default.binary_control[0].pre_depends, empty_str);
+        assert_eq!(default.binary_control[0].depends, empty_str);
+        assert_eq!(default.binary_control[0].recommends, empty_str);
+        assert_eq!(default.binary_control[0].suggests, empty_str);
+        assert_eq!(default.binary_control[0].enhances, empty_str);
+        assert_eq!(default.binary_control[0].breaks, empty_str);
+        assert_eq!(default.binary_control[0].conflicts, empty_str);
+        assert_eq!(default.binary_control[0].replaces, empty_str);
+        assert_eq!(default.binary_control[0].provides, empty_str);
+        assert_eq!(default.binary_control[0].built_using, empty_vec);
+        assert!(default.binary_control[0].custom_fields.is_empty());
+        assert_eq!(default.binary_control[0].architecture, Architecture::Any);
+    }
+
+    #[test]
+    fn test_deserialize_binaries() {
+        let fake_one = r#"{"update": true, "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional"},
+            "binaryControl": {"package": "a", "priority": "optional", "architecture": "any"}}"#;
+        let fake_many = r#"{"update": true, "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional"},
+            "binaryControl": [{"package": "a", "priority": "optional", "architecture": "any"},
+                              {"package": "b", "priority": "optional", "architecture": "all"}]}"#;
+
+        let one: Control = serde_json::from_str(fake_one).unwrap();
+        let many: Control = serde_json::from_str(fake_many).unwrap();
+
+        assert_eq!(one.binary_control.len(), 1);
+        assert_eq!(many.binary_control.len(), 2);
+        assert_eq!(many.binary_control[1].package, "b");
+        assert_eq!(many.binary_control[1].architecture, Architecture::All);
+    }
+
+    #[test]
+    fn test_format_source_contents_build_relations() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "buildDepends": ["debhelper (>= 12)"], "buildDependsArch": ["cargo"],
+                              "buildDependsIndep": ["pandoc", "graphviz"], "buildConflicts": ["rustc-mozilla"]},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any"}}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::format_source_contents(&fake_config);
+
+        assert_eq!(
+            actual,
+            "Source: foo
+Priority: optional
+Maintainer: n <e@example.com>
+Build-Depends: debhelper (>= 12)
+Build-Depends-Arch: cargo
+Build-Depends-Indep:
+ pandoc,
+ graphviz
+Build-Conflicts: rustc-mozilla"
+        );
+    }
+
+    #[test]
+    fn test_create_contents_misc_depends() {
+        let fake_config: Config = r#"{"control": {"update": true, "miscDepends": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "buildDepends": ["debhelper-compat (= 13)"]},
+            "binaryControl": [{"package": "foo", "priority": "optional", "architecture": "any", "depends": "${shlibs:Depends}"},
+                              {"package": "foo-doc", "priority": "optional", "architecture": "all"},
+                              {"package": "foo-dbg", "priority": "optional", "architecture": "any", "depends": "${misc:Depends}, foo"}]}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::create_contents(&fake_config, &[]).unwrap();
+
+        assert!(actual.contains(
+            "Package: foo\nPriority: optional\nDepends: ${misc:Depends}, ${shlibs:Depends}\n"
+        ));
+        assert!(actual.contains("Package: foo-doc\nPriority: optional\nDepends: ${misc:Depends}\n"));
+        assert!(actual
+            .contains("Package: foo-dbg\nPriority: optional\nDepends: ${misc:Depends}, foo\n"));
+    }
+
+    #[test]
+    fn test_adds_misc_depends_without_debhelper() {
+        let fake_config: Config = r#"{"control": {"update": true, "miscDepends": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "buildDepends": ["cdbs"]},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any"}}}"#
+            .parse()
+            .unwrap();
+
+        assert!(!Control::adds_misc_depends(&fake_config));
+    }
+
+    #[test]
+    fn test_format_source_contents_uploaders() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "Team", "email": "team@example.com"}, "priority": "optional",
+                              "uploaders": [{"name": "John Doe", "email": "john@example.com"}, {"name": "Jane Doe", "email": "jane@example.com"}]},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any"}}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::format_source_contents(&fake_config);
+
+        assert_eq!(
+            actual,
+            "Source: foo
+Priority: optional
+Maintainer: Team <team@example.com>
+Uploaders: John Doe <john@example.com>, Jane Doe <jane@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_format_source_contents_vcs() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "vcsBrowser": "https://salsa.debian.org/foo", "vcsGit": "https://salsa.debian.org/foo.git -b debian/latest",
+                              "testsuite": "autopkgtest"},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any"}}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::format_source_contents(&fake_config);
+
+        assert!(actual.ends_with(
+            "Vcs-Browser: https://salsa.debian.org/foo\nVcs-Git: https://salsa.debian.org/foo.git -b debian/latest\nTestsuite: autopkgtest"
+        ));
+    }
+
+    #[test]
+    fn test_format_source_contents_origin_bugs() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "testsuite": "autopkgtest", "origin": "Example", "bugs": "https://bugs.example.com/foo"},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any"}}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::format_source_contents(&fake_config);
+
+        assert!(actual.ends_with(
+            "Testsuite: autopkgtest\nOrigin: Example\nBugs: https://bugs.example.com/foo"
+        ));
+    }
+
+    #[test]
+    fn test_is_known_section() {
+        assert!(Control::is_known_section("utils"));
+        assert!(Control::is_known_section("non-free/libs"));
+        assert!(!Control::is_known_section("main/utils"));
+        assert!(!Control::is_known_section("internal"));
+        assert!(!Control::is_known_section("contrib/internal"));
+    }
+
+    #[test]
+    fn test_section_issues() {
+        let fake_control = r#"{"update": true, "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "section": "contrib/utils", "priority": "optional"},
+            "binaryControl": [{"package": "a", "section": "utils", "priority": "optional", "architecture": "any"},
+                              {"package": "b", "section": "internal", "priority": "optional", "architecture": "any"}]}"#;
+
+        let actual: Control = serde_json::from_str(fake_control).unwrap();
+        let allowed: Control = serde_json::from_str(&fake_control.replacen(
+            "{",
+            r#"{"allowCustomSections": true, "#,
+            1,
+        ))
+        .unwrap();
+
+        let paths: Vec<String> = actual
+            .section_issues()
+            .into_iter()
+            .map(|i| i.path)
+            .collect();
+        assert_eq!(paths, vec!["control.binaryControl[1].section"]);
+        assert!(allowed.section_issues().is_empty());
+    }
+
+    #[test]
+    fn test_url_issues() {
+        let fake_control = |vcs_browser: &str| -> Control {
+            serde_json::from_str(&format!(
+                r#"{{"update": true,
+                "sourceControl": {{"maintainer": {{"name": "n", "email": "e"}}, "priority": "optional",
+                                   "homepage": "http://example.com:8080/foo?a=b", "vcsBrowser": "{}"}},
+                "binaryControl": []}}"#,
+                vcs_browser
+            ))
+            .unwrap()
+        };
+        let fake_valid = fake_control("https://salsa.debian.org/foo");
+
+        assert!(fake_valid.url_issues().is_empty());
+        assert!(fake_valid.url_issue("salsa.debian.org/foo").is_some());
+        assert!(fake_valid.url_issue("https//salsa.debian.org").is_some());
+        assert!(fake_valid.url_issue("ftp://example.com").is_some());
+        assert!(fake_valid.url_issue("https://exa_mple.com/").is_some());
+        assert!(fake_valid.url_issue("https://").is_some());
+
+        let fake_invalid = fake_control("salsa.debian.org/foo");
+        let paths: Vec<String> = fake_invalid
+            .url_issues()
+            .into_iter()
+            .map(|i| i.path)
+            .collect();
+        assert_eq!(paths, vec!["control.sourceControl.vcsBrowser"]);
+    }
+
+    #[test]
+    fn test_url_issues_require_https() {
+        let fake_control: Control = serde_json::from_str(
+            r#"{"update": true, "requireHttps": true,
+                "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional",
+                                  "homepage": "http://example.com", "vcsBrowser": "https://salsa.debian.org/foo"},
+                "binaryControl": []}"#,
+        )
+        .unwrap();
+
+        let actual = fake_control.url_issues();
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].path, "control.sourceControl.homepage");
+        assert_eq!(
+            actual[0].message,
+            "insecure URL http://example.com, https is required"
+        );
+    }
+
+    #[test]
+    fn test_standards_version() {
+        let fake_config = |control: &str| -> Config {
+            format!(
+                r#"{{"control": {{"update": true, {}
+                "sourceControl": {{"source": "foo", "maintainer": {{"name": "n", "email": "e@example.com"}}, "priority": "optional"}},
+                "binaryControl": {{"package": "foo", "priority": "optional", "architecture": "any"}}}}}}"#,
+                control
+            )
+            .parse()
+            .unwrap()
+        };
+        let fake_omitted = fake_config("");
+        let fake_filled = fake_config(r#""fillStandardsVersion": true,"#);
+
+        assert_eq!(fake_omitted.control.standards_version(), "");
+        assert_eq!(
+            fake_filled.control.standards_version(),
+            LATEST_STANDARDS_VERSION
+        );
+        assert!(Control::format_source_contents(&fake_filled)
+            .contains(&format!("Standards-Version: {}", LATEST_STANDARDS_VERSION)));
+        assert!(Control::standards_version_lints(&fake_filled).is_empty());
+    }
+
+    #[test]
+    fn test_standards_version_lints() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional", "standardsVersion": "3.9.8"},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any"}}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::standards_version_lints(&fake_config);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].code, "ancient-standards-version");
+    }
+
+    #[test]
+    fn test_priority_extra() {
+        let fake_config = |control: &str| -> Config {
+            format!(
+                r#"{{"control": {{"update": true, {}
+                "sourceControl": {{"source": "foo", "maintainer": {{"name": "n", "email": "e@example.com"}}, "priority": "extra"}},
+                "binaryControl": [{{"package": "foo", "priority": "optional", "architecture": "any"}},
+                                  {{"package": "foo-dbg", "priority": "extra", "architecture": "any"}}]}}}}"#,
+                control
+            )
+            .parse()
+            .unwrap()
+        };
+        let fake_kept = fake_config("");
+        let fake_rewritten = fake_config(r#""rewriteExtraPriority": true,"#);
+
+        let actual = Control::priority_lints(&fake_kept);
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(
+            actual[1].code,
+            "priority-extra-is-replaced-by-priority-optional"
+        );
+        assert!(actual[1].message.starts_with("foo-dbg "));
+        assert!(Control::create_contents(&fake_kept, &[])
+            .unwrap()
+            .contains("Priority: extra\n"));
+
+        assert!(Control::priority_lints(&fake_rewritten).is_empty());
+        assert!(!Control::create_contents(&fake_rewritten, &[])
+            .unwrap()
+            .contains("Priority: extra"));
+    }
+
+    #[test]
+    fn test_wrap_and_sort() {
+        let fake_config: Config = r#"{"control": {"update": true, "wrapAndSort": true, "miscDepends": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "buildDepends": ["debhelper-compat (= 13)", "cargo"], "standardsVersion": "4.7.2"},
+            "binaryControl": [{"package": "foo", "priority": "optional", "architecture": "any", "depends": "zlib1g, libc6"},
+                              {"package": "foo-doc", "priority": "optional", "architecture": "all"}]}}"#
+            .parse()
+            .unwrap();
+        let fake_fields = CustomField::parse(vec!["X-Custom: yes"], Some("foo-doc")).unwrap();
+
+        let actual = fake_config
+            .control
+            .wrap_and_sort(&Control::create_contents(&fake_config, &fake_fields).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            actual,
+            "Source: foo
+Priority: optional
+Maintainer: n <e@example.com>
+Build-Depends:
+ cargo,
+ debhelper-compat (= 13),
+Standards-Version: 4.7.2
+
+Package: foo
+Architecture: any
+Priority: optional
+Depends:
+ libc6,
+ zlib1g,
+ ${misc:Depends},
+
+Package: foo-doc
+Architecture: all
+Priority: optional
+Depends:
+ ${misc:Depends},
+X-Custom: yes
+"
+        );
+    }
+
+    #[test]
+    fn test_check_relations() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "buildDepends": ["debhelper (>= 12)", "cargo [amd64] <!nocheck>"]},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any",
+                              "depends": "libc6 (>= 2.31), libfoo (>= 1.2"}}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::check_relations(&fake_config).unwrap_err();
+
+        assert_eq!(
+            actual.to_string(),
+            "Invalid foo Depends value `libc6 (>= 2.31), libfoo (>= 1.2`: expected `)`, found end of value at column 32"
+        );
+    }
+
+    #[test]
+    fn test_create_contents_multiple_binaries() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional"},
+            "binaryControl": [{"package": "foo", "priority": "optional", "architecture": "any"},
+                              {"package": "foo-dev", "priority": "optional", "architecture": "any"},
+                              {"package": "foo-doc", "priority": "optional", "architecture": "all"}]}}"#
+            .parse()
+            .unwrap();
+
+        let actual = Control::create_contents(&fake_config, &[]).unwrap();
+        let paragraphs: Vec<&str> = actual.trim_end().split("\n\n").collect();
+
+        assert_eq!(paragraphs.len(), 4);
+        assert!(paragraphs[1].starts_with("Package: foo\n"));
+        assert!(paragraphs[2].starts_with("Package: foo-dev\n"));
+        assert!(paragraphs[3].starts_with("Package: foo-doc\n"));
+        assert!(paragraphs[3].contains("Architecture: all"));
+    }
+
+    #[test]
+    fn test_format_binary_contents_relations() {
+        let fake_binary: BinaryControl = serde_json::from_str(
+            r#"{"package": "foo", "priority": "optional", "architecture": "any",
+                "depends": "libc6 (>= 2.31), libfoo1", "recommends": "foo-doc", "suggests": "bar",
+                "breaks": "foo-old (<< 2)", "conflicts": "baz", "replaces": "foo-old (<< 2)",
+                "provides": "foo-tool", "enhances": "qux"}"#,
+        )
+        .unwrap();
+
+        let actual = Control::format_binary_contents(&fake_binary, false);
+
+        assert_eq!(
+            actual,
+            "Package: foo
+Priority: optional
+Depends: libc6 (>= 2.31), libfoo1
+Recommends: foo-doc
+Suggests: bar
+Enhances: qux
+Breaks: foo-old (<< 2)
+Conflicts: baz
+Replaces: foo-old (<< 2)
+Provides: foo-tool
+Architecture: any"
+        );
+    }
+
+    #[test]
+    fn test_format_binary_contents_udeb() {
+        let fake_udeb: BinaryControl = serde_json::from_str(
+            r#"{"package": "foo-udeb", "packageType": "udeb", "priority": "optional", "architecture": "any"}"#,
+        )
+        .unwrap();
+        let fake_section: BinaryControl = serde_json::from_str(
+            r#"{"package": "foo-udeb", "package_type": "udeb", "section": "utils", "priority": "optional", "architecture": "any"}"#,
+        )
+        .unwrap();
+
+        let actual = Control::format_binary_contents(&fake_udeb, false);
+
+        assert_eq!(
+            actual,
+            "Package: foo-udeb
+Package-Type: udeb
+Section: debian-installer
+Priority: optional
+Architecture: any"
+        );
+        assert!(Control::format_binary_contents(&fake_section, false).contains("Section: utils\n"));
+    }
+
+    #[test]
+    fn test_format_binary_contents_built_using() {
+        let fake_binary: BinaryControl = serde_json::from_str(
+            r#"{"package": "foo", "priority": "optional", "architecture": "any",
+                "builtUsing": ["golang-1.21 (= 1.21.6-1)", "${misc:Built-Using}"]}"#,
+        )
+        .unwrap();
+
+        let actual = Control::format_binary_contents(&fake_binary, false);
+
+        assert_eq!(
+            actual,
+            "Package: foo
+Priority: optional
+Built-Using:
+ golang-1.21 (= 1.21.6-1),
+ ${misc:Built-Using}
+Architecture: any"
+        );
+        assert!(fake_binary.check_relations().is_ok());
+    }
+
+    #[test]
+    fn test_essential() {
+        let fake_binary: BinaryControl = serde_json::from_str(
+            r#"{"package": "foo", "priority": "required", "essential": true, "protected": true, "architecture": "any"}"#,
+        )
+        .unwrap();
+
+        let actual = Control::format_binary_contents(&fake_binary, false);
+        let msg = Control::with_warnings("Done", &[&fake_binary]);
+
+        assert_eq!(
+            actual,
+            "Package: foo
+Priority: required
+Essential: yes
+Protected: yes
+Architecture: any"
+        );
+        assert_eq!(msg.lines().count(), 3);
+        assert!(msg.contains("WARNING: foo is marked Essential: yes"));
+        assert!(msg.contains("WARNING: foo is marked Protected: yes"));
+    }
+
+    #[test]
+    fn test_description_format() {
+        let fake_description = Description {
+            synopsis: "command line tool".to_string(),
+            extended: "Foo is a rather long description of a tool which doesn't fit into a single line of control file.\n\nFeatures:\n  - fast\n  - small\n".to_string(),
+        };
+
+        let actual = deb822::Field::new(DESC, &fake_description.format()).to_string();
+
+        assert_eq!(
+            actual,
+            "Description: command line tool
+ Foo is a rather long description of a tool which doesn't fit into a single
+ line of control file.
+ .
+ Features:
+   - fast
+   - small"
+        );
+        assert!(actual
+            .lines()
+            .skip(1)
+            .all(|l| l.chars().count() <= DESC_WIDTH));
+
+        let fake_description = Description {
+            synopsis: "outil".to_string(),
+            extended: "Ça gère les fichiers ".repeat(6),
+        };
+
+        let actual = deb822::Field::new(DESC, &fake_description.format()).to_string();
+
+        assert_eq!(
+            actual,
+            "Description: outil
+ Ça gère les fichiers Ça gère les fichiers Ça gère les fichiers Ça gère les
+ fichiers Ça gère les fichiers Ça gère les fichiers"
+        );
+        assert!(actual
+            .lines()
+            .skip(1)
+            .all(|l| l.chars().count() <= DESC_WIDTH));
+    }
+
+    #[test]
+    fn test_description_lints() {
+        let fake_description = |synopsis: &str| Description {
+            synopsis: synopsis.to_string(),
+            extended: "".to_string(),
+        };
+        let codes = |synopsis: &str| -> Vec<String> {
+            fake_description(synopsis)
+                .lints("foo")
+                .into_iter()
+                .map(|l| l.code)
+                .collect()
+        };
+
+        assert!(codes("command line tool for foo files").is_empty());
+        assert!(codes("").is_empty());
+        assert!(codes("tool for foo files...").is_empty());
+        assert_eq!(codes(&"x".repeat(81)), vec!["description-too-long"]);
+        assert_eq!(
+            codes("A tool for foo files."),
+            vec![
+                "synopsis-is-a-sentence",
+                "description-synopsis-starts-with-article"
+            ]
+        );
+        assert_eq!(
+            codes("Foo command line tool"),
+            vec!["description-starts-with-package-name"]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_description() {
+        let fake_text: Description =
+            serde_json::from_str(r#""tool\nFirst paragraph.\n\nSecond paragraph.""#).unwrap();
+        let fake_structured: Description =
+            serde_json::from_str(r#"{"synopsis": "tool", "extended": "First paragraph."}"#)
+                .unwrap();
+
+        assert_eq!(fake_text.synopsis, "tool");
+        assert_eq!(
+            fake_text.format(),
+            "tool\nFirst paragraph.\n\nSecond paragraph."
+        );
+        assert_eq!(fake_structured.format(), "tool\nFirst paragraph.");
+        assert_eq!(Description::default().format(), "");
+    }
 
-        assert_eq!(default.binary_control.package, empty_str);
-        assert_eq!(default.binary_control.description, empty_str);
-        assert_eq!(default.binary_control.section, empty_str);
-        assert_eq!(default.binary_control.priority, Priority::Optional);
-        assert_eq!(default.binary_control.pre_depends, empty_str);
-        assert_eq!(default.binary_control.architecture, Architecture::Any);
+    #[test]
+    fn test_deserialize_snake_case() {
+        let fake_control = r#"{"update": true,
+            "source_control": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional",
+                               "build_depends": ["debhelper"], "build_depends_indep": ["pandoc"], "standards_version": "4.5.1", "vcs_browser": "v", "vcs_git": "g"},
+            "binary_control": {"package": "a", "priority": "optional", "architecture": "any", "pre_depends": "b"}}"#;
+
+        let actual: Control = serde_json::from_str(fake_control).unwrap();
+
+        assert_eq!(actual.source_control.build_depends, vec!["debhelper"]);
+        assert_eq!(actual.source_control.build_depends_indep, vec!["pandoc"]);
+        assert_eq!(actual.source_control.standards_version, "4.5.1");
+        assert_eq!(actual.source_control.vcs_browser, "v");
+        assert_eq!(actual.source_control.vcs_git, "g");
+        assert_eq!(actual.binary_control[0].pre_depends, "b");
     }
 
     #[test]
@@ -462,6 +2658,42 @@ mod tests {
         assert_eq!(acc, expected);
     }
 
+    #[test]
+    fn test_deserialize_architecture_list() {
+        let fake_binary =
+            r#"{"package": "a", "priority": "optional", "architecture": "amd64  arm64 hurd-any"}"#;
+
+        let actual: BinaryControl = serde_json::from_str(fake_binary).unwrap();
+
+        assert_eq!(
+            actual.architecture,
+            Architecture::List(vec![
+                "amd64".to_string(),
+                "arm64".to_string(),
+                "hurd-any".to_string()
+            ])
+        );
+        assert_eq!(actual.architecture.to_string(), "amd64 arm64 hurd-any");
+    }
+
+    #[test]
+    fn test_deserialize_architecture_invalid() {
+        let fake_unknown =
+            r#"{"package": "a", "priority": "optional", "architecture": "amd64 x86_64"}"#;
+        let fake_combined =
+            r#"{"package": "a", "priority": "optional", "architecture": "any amd64"}"#;
+
+        let unknown = serde_json::from_str::<BinaryControl>(fake_unknown).unwrap_err();
+        let combined = serde_json::from_str::<BinaryControl>(fake_combined).unwrap_err();
+
+        assert!(unknown
+            .to_string()
+            .starts_with("unknown architecture x86_64"));
+        assert!(combined
+            .to_string()
+            .starts_with("any can't be combined with other architectures"));
+    }
+
     #[test]
     fn test_format_custom_data_arch() {
         let fake_key = "fake key";
@@ -475,29 +2707,446 @@ mod tests {
     }
 
     #[test]
-    fn test_format_additional_fields() {
-        let fake_row_1 = "key1: value1";
-        let fake_row_2 = "key2: value2";
-        let fake_row_3 = "key3: value3";
-        let fake_fields: Vec<&str> = vec![fake_row_1, fake_row_2, fake_row_3];
-        let expected = format!(
-            "
-{row_1}
-{row_2}
-{row_3}
-",
-            row_1 = fake_row_1,
-            row_2 = fake_row_2,
-            row_3 = fake_row_3,
+    fn test_with_custom_fields() {
+        let fake_fields = CustomField::parse(
+            vec!["Key1: value1", "Key2: value2", "Key3: value3"],
+            Some("foo"),
+        )
+        .unwrap();
+        let fake_placement = Placement::Binary("foo".to_string());
+
+        let actual = Control::with_custom_fields(
+            "Package: foo\n".to_string(),
+            &fake_fields,
+            &fake_placement,
+        )
+        .unwrap();
+
+        assert_eq!(
+            actual,
+            "Package: foo\nKey1: value1\nKey2: value2\nKey3: value3"
+        );
+    }
+
+    #[test]
+    fn test_with_custom_fields_normalized() {
+        let fake_fields = vec![
+            CustomField::new("vcs-browser", "https://example.com", Placement::Source),
+            CustomField::new("x-python3-version", ">= 3.9", Placement::Source),
+            CustomField::new("xs-go-import-path", "example.com/foo", Placement::Source),
+            CustomField::new(
+                "description",
+                "ignored",
+                Placement::Binary("foo".to_string()),
+            ),
+        ];
+
+        let actual = Control::with_custom_fields(
+            "Source: foo".to_string(),
+            &fake_fields,
+            &Placement::Source,
+        )
+        .unwrap();
+
+        assert_eq!(
+            actual,
+            "Source: foo\nVcs-Browser: https://example.com\nX-Python3-Version: >= 3.9\nXS-Go-Import-Path: example.com/foo"
+        );
+    }
+
+    #[test]
+    fn test_with_custom_fields_duplicate() {
+        let fake_paragraph = "Package: foo\nArchitecture: any".to_string();
+        let fake_placement = Placement::Binary("foo".to_string());
+
+        let fake_fields = CustomField::parse(vec!["architecture: all"], Some("foo")).unwrap();
+        let actual =
+            Control::with_custom_fields(fake_paragraph.clone(), &fake_fields, &fake_placement);
+        assert!(matches!(actual, Err(DebyError::DuplicateField(f)) if f == "Architecture"));
+
+        let fake_fields = CustomField::parse(vec!["X-Foo: a", "x-foo: b"], Some("foo")).unwrap();
+        let actual = Control::with_custom_fields(fake_paragraph, &fake_fields, &fake_placement);
+        assert!(matches!(actual, Err(DebyError::DuplicateField(f)) if f == "X-Foo"));
+    }
+
+    #[test]
+    fn test_parse_custom_fields() {
+        let actual = CustomField::parse(
+            vec!["XB-Foo: a", "xs-bar: b", " more", "Baz: c"],
+            Some("foo"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            actual,
+            vec![
+                CustomField::new("XB-Foo", "a", Placement::Binary("foo".to_string())),
+                CustomField::new("xs-bar", "b\nmore", Placement::Source),
+                CustomField::new("Baz", "c", Placement::Binary("foo".to_string())),
+            ]
+        );
+        assert_eq!(
+            CustomField::parse(vec!["Baz: c"], None).unwrap()[0].placement,
+            Placement::Source
+        );
+        assert!(matches!(
+            CustomField::parse(vec!["no colon"], Some("foo")),
+            Err(DebyError::UnknownField(f)) if f == "no colon"
+        ));
+    }
+
+    #[test]
+    fn test_create_contents_custom_fields() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional"},
+            "binaryControl": [{"package": "foo", "priority": "optional", "architecture": "any"},
+                              {"package": "foo-doc", "priority": "optional", "architecture": "all"}]}}"#
+            .parse()
+            .unwrap();
+        let fake_fields = vec![
+            CustomField::new("multi-arch", "same", Placement::Binary("foo".to_string())),
+            CustomField::new("X-Notes", "first\n\nsecond", Placement::Source),
+        ];
+
+        let actual = Control::create_contents(&fake_config, &fake_fields).unwrap();
+
+        assert_eq!(
+            actual,
+            "Source: foo
+Priority: optional
+Maintainer: n <e@example.com>
+X-Notes: first
+ .
+ second
+
+Package: foo
+Priority: optional
+Architecture: any
+Multi-Arch: same
+
+Package: foo-doc
+Priority: optional
+Architecture: all
+"
+        );
+
+        let fake_unknown = vec![CustomField::new(
+            "Multi-Arch",
+            "same",
+            Placement::Binary("bar".to_string()),
+        )];
+        let actual = Control::create_contents(&fake_config, &fake_unknown);
+        assert!(matches!(actual, Err(DebyError::UnknownBinaryPackage(p)) if p == "bar"));
+    }
+
+    #[test]
+    fn test_create_contents_config_custom_fields() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional",
+                              "customFields": {"XS-Go-Import-Path": "example.com/foo"}},
+            "binaryControl": {"package": "foo", "priority": "optional", "architecture": "any",
+                              "custom_fields": {"XB-Build-Id": "${BUILD_ID}", "multi-arch": "foreign"}}}}"#
+            .parse()
+            .unwrap();
+        let fake_fields = vec![CustomField::new(
+            "X-Notes",
+            "a",
+            Placement::Binary("foo".to_string()),
+        )];
+
+        let actual = Control::create_contents(&fake_config, &fake_fields).unwrap();
+
+        assert_eq!(
+            actual,
+            "Source: foo
+Priority: optional
+Maintainer: n <e@example.com>
+XS-Go-Import-Path: example.com/foo
+
+Package: foo
+Priority: optional
+Architecture: any
+XB-Build-Id: ${BUILD_ID}
+Multi-Arch: foreign
+X-Notes: a
+"
+        );
+
+        let fake_duplicate = vec![CustomField::new(
+            "Multi-Arch",
+            "same",
+            Placement::Binary("foo".to_string()),
+        )];
+        let actual = Control::create_contents(&fake_config, &fake_duplicate);
+        assert!(matches!(actual, Err(DebyError::DuplicateField(f)) if f == "Multi-Arch"));
+    }
+
+    #[test]
+    fn test_deserialize_custom_fields_order() {
+        let fake_control: Control = serde_json::from_str(
+            r#"{"update": true,
+                "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional",
+                                  "customFields": {"XS-Zulu": "z", "XS-Alpha": "a", "Homepage-Mirror": "m"}},
+                "binaryControl": {"package": "foo", "priority": "optional", "architecture": "all"}}"#,
+        )
+        .unwrap();
+
+        let actual: Vec<String> = fake_control
+            .custom_fields()
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+
+        assert_eq!(actual, vec!["XS-Zulu", "XS-Alpha", "Homepage-Mirror"]);
+    }
+
+    #[test]
+    fn test_deserialize_custom_fields_not_string() {
+        let actual = serde_json::from_str::<Control>(
+            r#"{"update": true,
+                "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional",
+                                  "customFields": {"X-Count": 1}},
+                "binaryControl": {"package": "foo", "priority": "optional", "architecture": "all"}}"#,
+        );
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_extra_fields() {
+        let fake_control: Control = serde_json::from_str(
+            r#"{"update": true,
+                "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional",
+                                  "extraFields": {"X-Python-Version": ">= 3.9"}},
+                "binaryControl": {"package": "foo", "priority": "optional", "architecture": "all",
+                                  "extra_fields": {"Multi-Arch": "foreign"}}}"#,
+        )
+        .unwrap();
+
+        let actual: Vec<(String, Placement)> = fake_control
+            .custom_fields()
+            .into_iter()
+            .map(|f| (f.name, f.placement))
+            .collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                ("X-Python-Version".to_string(), Placement::Source),
+                (
+                    "Multi-Arch".to_string(),
+                    Placement::Binary("foo".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_contents_source_fields() {
+        let fake_config: Config = r#"{"control": {"update": true,
+            "sourceControl": {"source": "foo", "maintainer": {"name": "n", "email": "e@example.com"}, "priority": "optional"},
+            "binaryControl": [{"package": "foo", "priority": "optional", "architecture": "any"},
+                              {"package": "foo-doc", "priority": "optional", "architecture": "all"}]}}"#
+            .parse()
+            .unwrap();
+        let fake_fields = CustomField::parse(
+            vec![
+                "xb-foo: binary",
+                "xs-go-import-path: example.com/foo",
+                "XC-Bar: changes",
+                "XS-Multi-Line: first",
+                " second",
+            ],
+            Some("foo-doc"),
+        )
+        .unwrap();
+
+        let actual = Control::create_contents(&fake_config, &fake_fields).unwrap();
+
+        assert_eq!(
+            actual,
+            "Source: foo
+Priority: optional
+Maintainer: n <e@example.com>
+XS-Go-Import-Path: example.com/foo
+XS-Multi-Line: first
+ second
+
+Package: foo
+Priority: optional
+Architecture: any
+
+Package: foo-doc
+Priority: optional
+Architecture: all
+XB-Foo: binary
+XC-Bar: changes
+"
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let fake_contents = "# header
+
+Source: foo
+Section: utils
+Priority: optional
+Maintainer: Foo Bar <foo@example.com>
+Uploaders: A <a@example.com>, B <b@example.com>
+Build-Depends:
+ cargo,
+ debhelper-compat (= 13)
+Standards-Version: 4.7.2
+Vcs-Git: https://example.com/foo.git
+Rules-Requires-Root: no
+
+Package: foo
+Priority: optional
+Essential: yes
+Depends: libc6, ${misc:Depends}
+Built-Using: rustc (= 1.70)
+Architecture: any
+Description: foo tool
+ Long description.
+ .
+ More.
+Multi-Arch: foreign
+
+Package: foo-doc
+Section: doc
+Architecture: all
+Description: foo documentation
+";
+
+        let actual = Control::import(fake_contents).unwrap();
+
+        assert_eq!(
+            actual["sourceControl"]["maintainer"]["email"],
+            "foo@example.com"
+        );
+        assert_eq!(actual["sourceControl"]["uploaders"][1]["name"], "B");
+        assert_eq!(
+            actual["sourceControl"]["buildDepends"],
+            json!(["cargo", "debhelper-compat (= 13)"])
+        );
+        assert_eq!(
+            actual["sourceControl"]["customFields"],
+            json!({"Rules-Requires-Root": "no"})
+        );
+        assert_eq!(actual["binaryControl"][0]["essential"], true);
+        assert_eq!(actual["binaryControl"][1]["priority"], "optional");
+
+        let fake_config: Config = json!({ "control": actual }).to_string().parse().unwrap();
+        let actual = Control::create_contents(&fake_config, &[]).unwrap();
+
+        assert_eq!(
+            actual,
+            fake_contents
+                .replace("# header\n\n", "")
+                .replace("Section: doc\n", "Section: doc\nPriority: optional\n")
+        );
+    }
+
+    #[test]
+    fn test_import_malformed() {
+        let actual = Control::import("Package: foo\nArchitecture: any\n");
+        assert!(matches!(actual, Err(DebyError::ControlImport(_))));
+
+        let actual = Control::import("Source: foo\nMaintainer: nobody\n");
+        assert!(matches!(actual, Err(DebyError::ControlImport(m)) if m.contains("nobody")));
+
+        let actual =
+            Control::import("Source: foo\nMaintainer: n <e@example.com>\n\nPackage: foo\n");
+        assert!(matches!(actual, Err(DebyError::ControlImport(_))));
+    }
+
+    #[test]
+    fn test_with_kept_paragraphs() {
+        let fake_current = "Source: foo\nSection: old\n\nPackage: foo\nArchitecture: any\n";
+
+        let actual = Control::with_kept_paragraphs(
+            fake_current,
+            "Package: foo\nArchitecture: all\n\nPackage: foo-doc\nArchitecture: all\n",
+            &Paragraphs::Binary,
+        )
+        .unwrap();
+        assert_eq!(
+            actual,
+            "Source: foo\nSection: old\n\nPackage: foo\nArchitecture: all\n\nPackage: foo-doc\nArchitecture: all\n"
+        );
+
+        let actual = Control::with_kept_paragraphs(
+            fake_current,
+            "Source: foo\nSection: utils\n",
+            &Paragraphs::Source,
+        )
+        .unwrap();
+        assert_eq!(
+            actual,
+            "Source: foo\nSection: utils\n\nPackage: foo\nArchitecture: any\n"
+        );
+    }
+
+    #[test]
+    fn test_check_kept_paragraphs() {
+        let fake_contents = "Source: foo\n\nPackage: foo\n";
+
+        assert!(Control::check_kept_paragraphs(fake_contents, &Paragraphs::Binary).is_ok());
+        assert!(Control::check_kept_paragraphs("Package: foo\n", &Paragraphs::All).is_ok());
+        assert!(matches!(
+            Control::check_kept_paragraphs("Package: foo\n", &Paragraphs::Binary),
+            Err(DebyError::KeptParagraphsMissing(kept)) if kept == "source"
+        ));
+        assert!(matches!(
+            Control::check_kept_paragraphs("Source: foo\n", &Paragraphs::Source),
+            Err(DebyError::KeptParagraphsMissing(kept)) if kept == "binary"
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_paragraphs() {
+        let fake_control: Control = serde_json::from_str(
+            r#"{"update": true, "paragraphs": "source",
+                "sourceControl": {"maintainer": {"name": "n", "email": "e"}, "priority": "optional"},
+                "binaryControl": []}"#,
         )
-        .trim()
-        .to_string();
+        .unwrap();
 
-        let actual = Control::format_additional_fields(fake_fields);
+        assert_eq!(fake_control.paragraphs, Paragraphs::Source);
+        let fake_paragraphs = deb822::parse("# header\n\nSource: foo\n\nPackage: foo\n").unwrap();
+
+        assert!(!Paragraphs::Source.generates(&fake_paragraphs[0]));
+        assert!(!Paragraphs::Binary.generates(&fake_paragraphs[0]));
+        assert!(Paragraphs::Source.generates(&fake_paragraphs[1]));
+        assert!(!Paragraphs::Source.generates(&fake_paragraphs[2]));
+        assert!(Paragraphs::Binary.generates(&fake_paragraphs[2]));
+    }
+
+    #[test]
+    fn test_append_paragraph() {
+        let fake_current = "Source: a\n# comment\n\nPackage: b  \n";
+        let actual = Control::append_paragraph(fake_current, "c", "Package: c\n").unwrap();
+        let expected = "Source: a\n# comment\n\nPackage: b  \n\nPackage: c\n";
 
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_append_paragraph_existing_package() {
+        let fake_current = "Source: a\n\nPackage: b\n";
+        let actual = Control::append_paragraph(fake_current, "b", "Package: b\n");
+
+        assert!(matches!(actual, Err(DebyError::BinaryPackageExists(_))));
+    }
+
+    #[test]
+    fn test_append_paragraph_missing_control() {
+        let actual = Control::append_paragraph("", "b", "Package: b\n");
+
+        assert!(matches!(actual, Err(DebyError::ControlMissing)));
+    }
+
     #[test]
     fn test_format_vec_empty() {
         let fake_key = "KEY";
@@ -0,0 +1,148 @@
+use super::Config;
+use crate::lint::Lint;
+use crate::version;
+
+/// Source package formats
+#[derive(Debug, PartialEq)]
+pub(crate) enum SourceFormat {
+    Native,
+    Quilt,
+    Other,
+}
+
+impl SourceFormat {
+    /// Parses contents of `debian/source/format` file
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - file contents, e.g. `3.0 (quilt)`
+    pub(crate) fn parse(contents: &str) -> Self {
+        match contents.trim() {
+            "3.0 (native)" | "1.0" => SourceFormat::Native,
+            "3.0 (quilt)" => SourceFormat::Quilt,
+            _ => SourceFormat::Other,
+        }
+    }
+}
+
+/// Checks that changelog package name is the source package name of control
+///
+/// # Arguments
+///
+/// - `package` - changelog package name
+/// - `source` - source package name from control config
+/// - `binaries` - binary package names from control config
+pub(crate) fn check_package(package: &str, source: &str, binaries: &[&str]) -> Vec<Lint> {
+    if package.is_empty() || source.is_empty() || package == source {
+        return vec![];
+    }
+
+    let message = if binaries.contains(&package) {
+        format!(
+            "changelog package {} is a binary package, changelog should use source package name {}",
+            package, source
+        )
+    } else {
+        format!(
+            "changelog package {} doesn't match source package {}",
+            package, source
+        )
+    };
+
+    vec![Lint::error("package-name-mismatch", message)]
+}
+
+/// Checks that a version agrees with the source package format:
+/// native packages have no debian revision, non-native packages require one
+///
+/// # Arguments
+///
+/// - `version` - version to be written to changelog
+/// - `format` - source package format
+pub(crate) fn check_version_format(version: &str, format: &SourceFormat) -> Vec<Lint> {
+    let has_revision = version.contains('-');
+
+    match format {
+        SourceFormat::Native if has_revision => vec![Lint::error(
+            "native-package-with-revision",
+            format!(
+                "version {} of a native package has a debian revision",
+                version
+            ),
+        )],
+        SourceFormat::Quilt if !has_revision => vec![Lint::error(
+            "non-native-package-without-revision",
+            format!(
+                "version {} of a non-native package has no debian revision",
+                version
+            ),
+        )],
+        _ => vec![],
+    }
+}
+
+/// Runs cross checks of changelog and control settings
+///
+/// # Arguments
+///
+/// - `config` - data from config file `.debyrc`
+/// - `version` - version to be written to changelog
+/// - `format` - source package format, if known
+pub(crate) fn check(
+    config: &Config,
+    version: Option<&str>,
+    format: Option<&SourceFormat>,
+) -> Vec<Lint> {
+    let mut lints = check_package(
+        config.changelog.package(),
+        config.control.source_name(),
+        &config.control.binary_names(),
+    );
+
+    if let Some(Err(e)) = version.map(version::validate) {
+        lints.push(Lint::error("invalid-version", e.to_string()));
+    }
+    if let (Some(version), Some(format)) = (version, format) {
+        lints.extend(check_version_format(version, format));
+    }
+
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_format_parse() {
+        assert_eq!(SourceFormat::parse("3.0 (native)\n"), SourceFormat::Native);
+        assert_eq!(SourceFormat::parse("3.0 (quilt)"), SourceFormat::Quilt);
+        assert_eq!(SourceFormat::parse("3.0 (git)"), SourceFormat::Other);
+    }
+
+    #[test]
+    fn test_check_package() {
+        assert!(check_package("foo", "foo", &["foo-bin"]).is_empty());
+        assert!(check_package("", "foo", &[]).is_empty());
+
+        let binary = check_package("foo-bin", "foo", &["foo-bin"]);
+        let other = check_package("bar", "foo", &["foo-bin"]);
+
+        assert_eq!(binary.len(), 1);
+        assert!(binary[0].message.contains("is a binary package"));
+        assert_eq!(other[0].code, "package-name-mismatch");
+    }
+
+    #[test]
+    fn test_check_version_format() {
+        assert!(check_version_format("1.0", &SourceFormat::Native).is_empty());
+        assert!(check_version_format("1.0-1", &SourceFormat::Quilt).is_empty());
+        assert!(check_version_format("1.0", &SourceFormat::Other).is_empty());
+
+        let native = check_version_format("1.0-1", &SourceFormat::Native);
+        let quilt = check_version_format("1.0", &SourceFormat::Quilt);
+
+        assert_eq!(native[0].code, "native-package-with-revision");
+        assert_eq!(quilt[0].code, "non-native-package-without-revision");
+    }
+}
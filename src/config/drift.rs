@@ -0,0 +1,209 @@
+use std::fmt::Display;
+
+use super::control::Placement;
+use crate::deb822::{self, Paragraph};
+use crate::DebyError;
+
+const SOURCE: &str = "Source";
+const PACKAGE: &str = "Package";
+
+/// A field of `debian/control` file which differs from generated contents
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlDrift {
+    /// Paragraph the field belongs to
+    pub placement: Placement,
+    /// Field name, e.g. `Depends`
+    pub field: String,
+    pub kind: DriftKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DriftKind {
+    /// Field is generated but missing from the file, generated value is kept
+    Added(String),
+    /// Field is in the file but isn't generated, current value is kept
+    Removed(String),
+    /// Field values differ
+    Changed { current: String, generated: String },
+}
+
+impl Display for ControlDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let paragraph = match &self.placement {
+            Placement::Source => "source paragraph".to_string(),
+            Placement::Binary(package) => format!("binary package {}", package),
+        };
+        let prefix = format!("{} {}", paragraph, self.field);
+        match &self.kind {
+            DriftKind::Added(v) => write!(f, "{}: `{}` is missing", prefix, v),
+            DriftKind::Removed(v) => write!(f, "{}: `{}` is not generated", prefix, v),
+            DriftKind::Changed { current, generated } => {
+                write!(f, "{}: `{}` instead of `{}`", prefix, current, generated)
+            }
+        }
+    }
+}
+
+/// Returns placement of a paragraph, paragraphs without `Source` and `Package` have none
+///
+/// # Arguments
+///
+/// - `paragraph` - a paragraph of _control_ file
+fn placement(paragraph: &Paragraph) -> Option<Placement> {
+    match (paragraph.get(SOURCE), paragraph.get(PACKAGE)) {
+        (Some(_), _) => Some(Placement::Source),
+        (None, Some(package)) => Some(Placement::Binary(package.to_string())),
+        _ => None,
+    }
+}
+
+/// Compares fields of two paragraphs with the same placement, field names are case-insensitive
+///
+/// # Arguments
+///
+/// - `placement` - placement of both paragraphs
+/// - `current` - paragraph of existing _control_ file
+/// - `generated` - generated paragraph
+fn diff_paragraph(
+    placement: &Placement,
+    current: &Paragraph,
+    generated: &Paragraph,
+) -> Vec<ControlDrift> {
+    let drift = |field: &str, kind: DriftKind| ControlDrift {
+        placement: placement.clone(),
+        field: field.to_string(),
+        kind,
+    };
+
+    let mut drifts = vec![];
+    for field in &generated.fields {
+        match current.get(&field.name) {
+            None => drifts.push(drift(&field.name, DriftKind::Added(field.value.clone()))),
+            Some(value) if value.trim() != field.value.trim() => drifts.push(drift(
+                &field.name,
+                DriftKind::Changed {
+                    current: value.to_string(),
+                    generated: field.value.clone(),
+                },
+            )),
+            _ => {}
+        }
+    }
+    for field in &current.fields {
+        if generated.get(&field.name).is_none() {
+            drifts.push(drift(&field.name, DriftKind::Removed(field.value.clone())));
+        }
+    }
+
+    drifts
+}
+
+/// Compares existing _control_ file contents with generated ones, paragraphs are matched
+/// by source or binary package, so their order doesn't matter
+///
+/// # Arguments
+///
+/// - `current` - existing _control_ file contents
+/// - `generated` - generated _control_ file contents
+pub(crate) fn diff(current: &str, generated: &str) -> Result<Vec<ControlDrift>, DebyError> {
+    let current: Vec<(Placement, Paragraph)> = deb822::parse(current)?
+        .into_iter()
+        .filter_map(|p| placement(&p).map(|pl| (pl, p)))
+        .collect();
+    let generated: Vec<(Placement, Paragraph)> = deb822::parse(generated)?
+        .into_iter()
+        .filter_map(|p| placement(&p).map(|pl| (pl, p)))
+        .collect();
+    let empty = Paragraph::new();
+
+    let mut drifts = vec![];
+    for (placement, paragraph) in &generated {
+        let existing = current.iter().find(|(pl, _)| pl == placement);
+        let existing = existing.map_or(&empty, |(_, p)| p);
+        drifts.extend(diff_paragraph(placement, existing, paragraph));
+    }
+    for (placement, paragraph) in &current {
+        if !generated.iter().any(|(pl, _)| pl == placement) {
+            drifts.extend(diff_paragraph(placement, paragraph, &empty));
+        }
+    }
+
+    Ok(drifts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let fake_current = "# edited by hand
+Source: foo
+Maintainer: n <e@example.com>
+X-Manual: yes
+
+Package: foo
+Architecture: any
+Depends: libc6
+
+Package: foo-old
+Architecture: all
+";
+        let fake_generated = "Source: foo
+Maintainer: n <e@example.com>
+Standards-Version: 4.7.2
+
+Package: foo
+Architecture: any
+Depends: libc6, zlib1g
+";
+
+        let actual = diff(fake_current, fake_generated).unwrap();
+
+        assert_eq!(
+            actual,
+            vec![
+                ControlDrift {
+                    placement: Placement::Source,
+                    field: "Standards-Version".to_string(),
+                    kind: DriftKind::Added("4.7.2".to_string()),
+                },
+                ControlDrift {
+                    placement: Placement::Source,
+                    field: "X-Manual".to_string(),
+                    kind: DriftKind::Removed("yes".to_string()),
+                },
+                ControlDrift {
+                    placement: Placement::Binary("foo".to_string()),
+                    field: "Depends".to_string(),
+                    kind: DriftKind::Changed {
+                        current: "libc6".to_string(),
+                        generated: "libc6, zlib1g".to_string(),
+                    },
+                },
+                ControlDrift {
+                    placement: Placement::Binary("foo-old".to_string()),
+                    field: "Package".to_string(),
+                    kind: DriftKind::Removed("foo-old".to_string()),
+                },
+                ControlDrift {
+                    placement: Placement::Binary("foo-old".to_string()),
+                    field: "Architecture".to_string(),
+                    kind: DriftKind::Removed("all".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            actual[2].to_string(),
+            "binary package foo Depends: `libc6` instead of `libc6, zlib1g`"
+        );
+    }
+
+    #[test]
+    fn test_diff_in_sync() {
+        let fake_contents = "Source: foo\n\nPackage: foo\nArchitecture: any\n";
+
+        assert!(diff(fake_contents, fake_contents).unwrap().is_empty());
+        assert_eq!(diff("", fake_contents).unwrap().len(), 3);
+    }
+}
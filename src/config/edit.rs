@@ -0,0 +1,398 @@
+use serde_json::{json, Map, Value};
+
+use super::relation::Relation;
+use crate::DebyError;
+
+const CONTROL: &str = "control";
+const SOURCE_CONTROL: &str = "sourceControl";
+const BINARY_CONTROL: &str = "binaryControl";
+const PACKAGE: &str = "package";
+
+/// Relationship fields of source section
+const SOURCE_RELATIONSHIPS: &[&str] = &[
+    "buildDepends",
+    "buildDependsArch",
+    "buildDependsIndep",
+    "buildConflicts",
+];
+/// Relationship fields of binary section
+const BINARY_RELATIONSHIPS: &[&str] = &[
+    "preDepends",
+    "depends",
+    "recommends",
+    "suggests",
+    "enhances",
+    "breaks",
+    "conflicts",
+    "replaces",
+    "provides",
+];
+/// Relationship fields stored as a single comma separated string
+const STRING_RELATIONSHIPS: &[&str] = BINARY_RELATIONSHIPS;
+
+/// Converts a camelCase config key into its snake_case alias
+///
+/// # Arguments
+///
+/// - `key` - camelCase key, e.g. `buildDepends`
+pub(crate) fn snake_case(key: &str) -> String {
+    let mut alias = String::new();
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            alias.push('_');
+        }
+        alias.push(c.to_ascii_lowercase());
+    }
+
+    alias
+}
+
+/// Returns a key present in a map, either `key` itself or its snake_case alias,
+/// `key` is returned when neither is present
+///
+/// # Arguments
+///
+/// - `map` - JSON map
+/// - `key` - camelCase key
+fn present_key(map: &Map<String, Value>, key: &str) -> String {
+    let alias = snake_case(key);
+
+    match !map.contains_key(key) && map.contains_key(&alias) {
+        true => alias,
+        false => key.to_string(),
+    }
+}
+
+/// Returns an object stored under `key`, creates an empty object if it's missing
+///
+/// # Arguments
+///
+/// - `value` - parent JSON object
+/// - `key` - key of a child object
+fn object_entry<'a>(
+    value: &'a mut Value,
+    key: &str,
+) -> Result<&'a mut Map<String, Value>, DebyError> {
+    let parent = value.as_object_mut().ok_or(DebyError::ConfigWrite)?;
+    map_entry(parent, key)
+}
+
+/// Returns an object stored under `key` of a map, creates an empty object if it's missing
+///
+/// # Arguments
+///
+/// - `map` - parent JSON map
+/// - `key` - key of a child object
+fn map_entry<'a>(
+    map: &'a mut Map<String, Value>,
+    key: &str,
+) -> Result<&'a mut Map<String, Value>, DebyError> {
+    map.entry(present_key(map, key))
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or(DebyError::ConfigWrite)
+}
+
+/// Adds a binary package definition to `binaryControl` section of config.
+/// A single package object is converted into an array of packages
+///
+/// # Arguments
+///
+/// - `config` - contents of config file `.debyrc`
+/// - `binary` - binary package definition
+pub(crate) fn add_binary(config: &mut Value, binary: Value) -> Result<(), DebyError> {
+    let name = binary[PACKAGE].as_str().unwrap_or_default().to_string();
+
+    let control = object_entry(config, CONTROL)?;
+    let key = present_key(control, BINARY_CONTROL);
+    let binaries = match control.remove(&key) {
+        None => vec![],
+        Some(Value::Array(v)) => v,
+        Some(v) => vec![v],
+    };
+
+    if binaries.iter().any(|b| b[PACKAGE] == name.as_str()) {
+        return Err(DebyError::BinaryPackageExists(name));
+    }
+
+    let mut binaries = binaries;
+    binaries.push(binary);
+    control.insert(key, Value::Array(binaries));
+
+    Ok(())
+}
+
+/// Merges an imported `control` section into config: source and binary packages are replaced,
+/// other settings of an existing section, e.g. `merge` or `wrapAndSort`, are kept
+///
+/// # Arguments
+///
+/// - `config` - contents of config file `.debyrc`
+/// - `imported` - `control` section read from _control_ file
+pub(crate) fn import_control(config: &mut Value, imported: Value) -> Result<(), DebyError> {
+    let control = object_entry(config, CONTROL)?;
+    let imported = match imported {
+        Value::Object(m) => m,
+        _ => return Err(DebyError::ConfigWrite),
+    };
+
+    for (key, value) in imported {
+        let present = present_key(control, &key);
+        match key.as_str() {
+            SOURCE_CONTROL | BINARY_CONTROL => {
+                control.remove(&present);
+                control.insert(key, value);
+            }
+            _ => {
+                control.entry(present).or_insert(value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a binary package object by its name.
+/// Package name can be omitted when config contains a single binary package
+///
+/// # Arguments
+///
+/// - `control` - `control` section of config
+/// - `package` - binary package name
+fn binary_entry<'a>(
+    control: &'a mut Map<String, Value>,
+    package: Option<&str>,
+) -> Result<&'a mut Map<String, Value>, DebyError> {
+    let unknown = || DebyError::UnknownBinaryPackage(package.unwrap_or_default().to_string());
+
+    let binaries: Vec<&mut Value> = match control.get_mut(&present_key(control, BINARY_CONTROL)) {
+        Some(Value::Array(v)) => v.iter_mut().collect(),
+        Some(v) => vec![v],
+        None => vec![],
+    };
+
+    let binary = match package {
+        Some(name) => binaries.into_iter().find(|b| b[PACKAGE] == name),
+        None if binaries.len() == 1 => binaries.into_iter().next(),
+        None => None,
+    };
+
+    binary.and_then(|b| b.as_object_mut()).ok_or_else(unknown)
+}
+
+/// Adds a dependency to a relationship field of config.
+/// Dependencies with the same package name are rejected
+///
+/// # Arguments
+///
+/// - `config` - contents of config file `.debyrc`
+/// - `field` - relationship field name, e.g. `buildDepends`
+/// - `dependency` - dependency string value, e.g. `libfoo (>= 1.2)`
+/// - `package` - binary package name for binary relationship fields
+pub(crate) fn add_dependency(
+    config: &mut Value,
+    field: &str,
+    dependency: &str,
+    package: Option<&str>,
+) -> Result<(), DebyError> {
+    let alternatives = dependency
+        .split('|')
+        .map(Relation::parse)
+        .collect::<Result<Vec<Relation>, DebyError>>()?;
+
+    let control = object_entry(config, CONTROL)?;
+    let section = if SOURCE_RELATIONSHIPS.contains(&field) {
+        map_entry(control, SOURCE_CONTROL)?
+    } else if BINARY_RELATIONSHIPS.contains(&field) {
+        binary_entry(control, package)?
+    } else {
+        return Err(DebyError::UnknownField(field.to_string()));
+    };
+
+    let key = present_key(section, field);
+    let mut values: Vec<String> = match section.get(&key) {
+        Some(Value::Array(v)) => v
+            .iter()
+            .filter_map(|d| d.as_str().map(|d| d.to_string()))
+            .collect(),
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .collect(),
+        _ => vec![],
+    };
+
+    // a package is listed once across all alternatives, `a | b` already covers `a`
+    let existing: Vec<String> = values
+        .iter()
+        .flat_map(|d| d.split('|'))
+        .filter_map(|a| Relation::parse(a).ok())
+        .map(|r| r.name)
+        .collect();
+    if let Some(relation) = alternatives.iter().find(|r| existing.contains(&r.name)) {
+        return Err(DebyError::DependencyExists(relation.name.to_string()));
+    }
+    let relation: Vec<String> = alternatives.iter().map(Relation::to_string).collect();
+    values.push(relation.join(" | "));
+
+    let value = if STRING_RELATIONSHIPS.contains(&field) {
+        Value::String(values.join(", "))
+    } else {
+        json!(values)
+    };
+    section.insert(key, value);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_binary_to_single_package() {
+        let mut fake_config =
+            json!({"control": {"update": true, "binaryControl": {"package": "a"}}});
+
+        add_binary(&mut fake_config, json!({"package": "b"})).unwrap();
+
+        let expected = json!({"control": {"update": true, "binaryControl": [{"package": "a"}, {"package": "b"}]}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_add_binary_snake_case() {
+        let mut fake_config = json!({"control": {"binary_control": {"package": "a"}}});
+
+        add_binary(&mut fake_config, json!({"package": "b"})).unwrap();
+
+        let expected = json!({"control": {"binary_control": [{"package": "a"}, {"package": "b"}]}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_import_control_keeps_settings() {
+        let mut fake_config = json!({"changelog": {"update": true}, "control": {"update": false, "merge": true, "source_control": {"source": "old"}}});
+        let fake_imported = json!({"update": true, "sourceControl": {"source": "foo"}, "binaryControl": [{"package": "foo"}]});
+
+        import_control(&mut fake_config, fake_imported).unwrap();
+
+        let expected = json!({"changelog": {"update": true}, "control": {"update": false, "merge": true, "sourceControl": {"source": "foo"}, "binaryControl": [{"package": "foo"}]}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_add_binary_missing_control() {
+        let mut fake_config = json!({});
+
+        add_binary(&mut fake_config, json!({"package": "a"})).unwrap();
+
+        let expected = json!({"control": {"binaryControl": [{"package": "a"}]}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_add_binary_duplicate() {
+        let mut fake_config = json!({"control": {"binaryControl": [{"package": "a"}]}});
+
+        let actual = add_binary(&mut fake_config, json!({"package": "a"}));
+
+        assert!(matches!(actual, Err(DebyError::BinaryPackageExists(_))));
+    }
+
+    #[test]
+    fn test_add_dependency_source() {
+        let mut fake_config =
+            json!({"control": {"sourceControl": {"buildDepends": ["debhelper"]}}});
+
+        add_dependency(&mut fake_config, "buildDepends", "cargo>=0.50", None).unwrap();
+
+        let expected = json!({"control": {"sourceControl": {"buildDepends": ["debhelper", "cargo (>= 0.50)"]}}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_add_dependency_snake_case() {
+        let mut fake_config =
+            json!({"control": {"source_control": {"build_depends": ["debhelper"]}}});
+
+        add_dependency(&mut fake_config, "buildDepends", "cargo", None).unwrap();
+
+        let expected =
+            json!({"control": {"source_control": {"build_depends": ["debhelper", "cargo"]}}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_add_dependency_binary() {
+        let mut fake_config = json!({"control": {"binaryControl": [
+            {"package": "a", "preDepends": "libc6"},
+            {"package": "b"}
+        ]}});
+
+        add_dependency(&mut fake_config, "preDepends", "dpkg (>= 1.15)", Some("a")).unwrap();
+
+        let expected = json!({"control": {"binaryControl": [
+            {"package": "a", "preDepends": "libc6, dpkg (>= 1.15)"},
+            {"package": "b"}
+        ]}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_add_dependency_ambiguous_binary() {
+        let mut fake_config =
+            json!({"control": {"binaryControl": [{"package": "a"}, {"package": "b"}]}});
+
+        let actual = add_dependency(&mut fake_config, "preDepends", "dpkg", None);
+
+        assert!(matches!(actual, Err(DebyError::UnknownBinaryPackage(_))));
+    }
+
+    #[test]
+    fn test_add_dependency_duplicate() {
+        let mut fake_config =
+            json!({"control": {"sourceControl": {"buildDepends": ["cargo (>= 0.40)"]}}});
+
+        let actual = add_dependency(&mut fake_config, "buildDepends", "cargo (>= 0.50)", None);
+
+        assert!(matches!(actual, Err(DebyError::DependencyExists(_))));
+    }
+
+    #[test]
+    fn test_add_dependency_duplicate_alternative() {
+        let mut fake_config =
+            json!({"control": {"sourceControl": {"buildDepends": ["cargo | rustc", "debhelper"]}}});
+
+        let actual = add_dependency(&mut fake_config, "buildDepends", "rustc (>= 1.70)", None);
+        assert!(matches!(actual, Err(DebyError::DependencyExists(d)) if d == "rustc"));
+
+        let actual = add_dependency(
+            &mut fake_config,
+            "buildDepends",
+            "dh-cargo | debhelper",
+            None,
+        );
+        assert!(matches!(actual, Err(DebyError::DependencyExists(d)) if d == "debhelper"));
+    }
+
+    #[test]
+    fn test_add_dependency_alternatives() {
+        let mut fake_config = json!({"control": {"sourceControl": {"buildDepends": ["cargo"]}}});
+
+        add_dependency(&mut fake_config, "buildDepends", "gcc|clang (>= 14)", None).unwrap();
+
+        let expected = json!({"control": {"sourceControl": {"buildDepends": ["cargo", "gcc | clang (>= 14)"]}}});
+        assert_eq!(fake_config, expected);
+    }
+
+    #[test]
+    fn test_add_dependency_unknown_field() {
+        let mut fake_config = json!({});
+
+        let actual = add_dependency(&mut fake_config, "depend", "cargo", None);
+
+        assert!(matches!(actual, Err(DebyError::UnknownField(_))));
+    }
+}
@@ -0,0 +1,128 @@
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::DebyError;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Decodes raw file contents into a string.
+/// A leading UTF-8 BOM is stripped, legacy non-UTF-8 content is rejected
+/// rather than transcoded, so a file is never rewritten in another encoding silently
+///
+/// # Arguments
+///
+/// - `path` - file path used in error messages
+/// - `bytes` - raw file contents
+pub(crate) fn decode(path: &Path, bytes: &[u8]) -> Result<String, DebyError> {
+    if bytes.starts_with(UTF16_LE_BOM) || bytes.starts_with(UTF16_BE_BOM) {
+        return Err(DebyError::UnsupportedEncoding(
+            path.display().to_string(),
+            "UTF-16".to_string(),
+        ));
+    }
+
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Ok(s.to_string()),
+        Err(e) => Err(DebyError::InvalidUtf8(
+            path.display().to_string(),
+            e.valid_up_to(),
+        )),
+    }
+}
+
+/// Decodes ISO-8859-1 bytes, every byte maps to the Unicode code point of the same value
+///
+/// # Arguments
+///
+/// - `bytes` - raw file contents
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Rewrites a Latin-1 encoded file as UTF-8, returns `false` when the file
+/// is valid UTF-8 already and is left untouched
+///
+/// # Arguments
+///
+/// - `path` - path to a file to be converted
+pub(crate) fn convert_latin1(path: &Path) -> Result<bool, Box<dyn Error>> {
+    let bytes = fs::read(path)?;
+    match decode(path, &bytes) {
+        Ok(_) => Ok(false),
+        Err(DebyError::InvalidUtf8(_, _)) => {
+            fs::write(path, decode_latin1(&bytes))?;
+            Ok(true)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Reads file contents as a string, a missing file is treated as an empty one
+///
+/// # Arguments
+///
+/// - `path` - path to a file to be read
+pub(crate) fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String, Box<dyn Error>> {
+    let path = path.as_ref();
+    let bytes = match fs::read(path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(decode(path, &bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8() {
+        let fake_path = Path::new("fake");
+        let actual = decode(fake_path, "entry – ü".as_bytes()).unwrap();
+
+        assert_eq!(actual, "entry – ü");
+    }
+
+    #[test]
+    fn test_decode_strips_bom() {
+        let fake_path = Path::new("fake");
+        let fake_bytes = [UTF8_BOM, b"entry"].concat();
+        let actual = decode(fake_path, &fake_bytes).unwrap();
+
+        assert_eq!(actual, "entry");
+    }
+
+    #[test]
+    fn test_decode_latin1_rejected() {
+        let fake_path = Path::new("fake");
+        let fake_bytes = b"J\xfcrgen M\xfcller";
+        let actual = decode(fake_path, fake_bytes);
+
+        assert!(matches!(actual, Err(DebyError::InvalidUtf8(p, 1)) if p == "fake"));
+    }
+
+    #[test]
+    fn test_decode_latin1() {
+        let fake_bytes = b"J\xfcrgen M\xfcller";
+        let actual = decode_latin1(fake_bytes);
+
+        assert_eq!(actual, "Jürgen Müller");
+    }
+
+    #[test]
+    fn test_decode_utf16_rejected() {
+        let fake_path = Path::new("fake");
+        let fake_bytes = [UTF16_LE_BOM, b"e\0"].concat();
+        let actual = decode(fake_path, &fake_bytes);
+
+        assert!(matches!(actual, Err(DebyError::UnsupportedEncoding(_, _))));
+    }
+}
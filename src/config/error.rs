@@ -0,0 +1,178 @@
+use serde_json::Value;
+
+use std::fmt::Display;
+
+use super::{jsonc, Config};
+
+/// Describes why config contents couldn't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigParseError {
+    /// Path to the offending key, e.g. `control.sourceControl.priority`, empty for syntax errors
+    pub path: String,
+    /// Line and column of the offending value, only known when config is read from a single JSON file
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl ConfigParseError {
+    pub(crate) fn new(path: &str, message: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            position: None,
+            message: message.to_string(),
+        }
+    }
+
+    /// Describes a JSON syntax error
+    ///
+    /// # Arguments
+    ///
+    /// - `err` - error returned by JSON parser
+    pub(crate) fn syntax(err: &serde_json::Error) -> Self {
+        Self {
+            path: "".to_string(),
+            position: Some((err.line(), err.column())),
+            message: Self::strip_position(err),
+        }
+    }
+
+    /// Describes an error of config contents which are valid JSON but not a valid config
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - config contents
+    pub(crate) fn invalid(value: Value) -> Self {
+        match serde_path_to_error::deserialize::<_, Config>(value) {
+            Ok(_) => Self {
+                path: "".to_string(),
+                position: None,
+                message: "invalid config".to_string(),
+            },
+            Err(e) => Self {
+                path: Self::format_path(&e.path().to_string()),
+                position: None,
+                message: e.into_inner().to_string(),
+            },
+        }
+    }
+
+    /// Prepends a key path to the path of the error, e.g. when the error belongs to a profile
+    ///
+    /// # Arguments
+    ///
+    /// - `prefix` - key path, e.g. `profiles.bookworm`
+    pub(crate) fn prefixed(mut self, prefix: &str) -> Self {
+        self.path = match self.path.is_empty() {
+            true => prefix.to_string(),
+            false => format!("{}.{}", prefix, self.path),
+        };
+
+        self
+    }
+
+    /// Finds line and column of the error by parsing config contents once more
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - contents of a single JSON config file
+    pub(crate) fn locate(mut self, contents: &str) -> Self {
+        let stripped = jsonc::strip(contents);
+        let de = &mut serde_json::Deserializer::from_str(&stripped);
+        if let Err(e) = serde_path_to_error::deserialize::<_, Config>(de) {
+            let inner = e.inner();
+            self.position = Some((inner.line(), inner.column()));
+            self.message = Self::strip_position(inner);
+        }
+
+        self
+    }
+
+    /// Removes `.` path of the root value
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - key path
+    fn format_path(path: &str) -> String {
+        match path {
+            "." => "".to_string(),
+            p => p.to_string(),
+        }
+    }
+
+    /// Returns error message without ` at line X column Y` suffix added by JSON parser
+    ///
+    /// # Arguments
+    ///
+    /// - `err` - error returned by JSON parser
+    fn strip_position(err: &serde_json::Error) -> String {
+        let message = err.to_string();
+        let suffix = format!(" at line {} column {}", err.line(), err.column());
+
+        message
+            .strip_suffix(&suffix)
+            .unwrap_or(&message)
+            .to_string()
+    }
+}
+
+impl Display for ConfigParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some((line, column)) = self.position {
+            write!(f, " (line {}, column {})", line, column)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAKE_CONFIG: &str = r#"{
+  "control": {
+    "update": true,
+    "sourceControl": {
+      "maintainer": {"name": "n", "email": "e"},
+      "priority": "normal"
+    }
+  }
+}"#;
+
+    #[test]
+    fn test_invalid() {
+        let fake_value: Value = serde_json::from_str(FAKE_CONFIG).unwrap();
+
+        let actual = ConfigParseError::invalid(fake_value);
+
+        assert_eq!(actual.path, "control.sourceControl.priority");
+        assert_eq!(actual.position, None);
+        assert!(actual.message.starts_with("unknown variant `normal`"));
+    }
+
+    #[test]
+    fn test_locate() {
+        let fake_value: Value = serde_json::from_str(FAKE_CONFIG).unwrap();
+
+        let actual = ConfigParseError::invalid(fake_value).locate(FAKE_CONFIG);
+
+        assert_eq!(actual.path, "control.sourceControl.priority");
+        assert_eq!(actual.position, Some((6, 26)));
+        assert!(actual.to_string().ends_with("(line 6, column 26)"));
+    }
+
+    #[test]
+    fn test_syntax() {
+        let fake_err = serde_json::from_str::<Value>("{\n  \"a\": }").unwrap_err();
+
+        let actual = ConfigParseError::syntax(&fake_err);
+
+        assert_eq!(actual.path, "");
+        assert_eq!(actual.position, Some((2, 8)));
+        assert_eq!(actual.to_string(), "expected value (line 2, column 8)");
+    }
+}
@@ -0,0 +1,87 @@
+use serde_json::Value;
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{encoding, jsonc};
+
+/// Merges `overlay` into `base`: objects are merged key by key, any other value is replaced
+///
+/// # Arguments
+///
+/// - `base` - value to be updated
+/// - `overlay` - value with higher priority
+pub(crate) fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (k, v) in overlay {
+                match base.get_mut(&k) {
+                    Some(b) => merge(b, v),
+                    None => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Returns paths of `*.json` fragments in a directory sorted by file name
+///
+/// # Arguments
+///
+/// - `dir` - fragments directory
+fn fragment_paths(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Merges all fragments of a directory into `base` in file name order
+///
+/// # Arguments
+///
+/// - `base` - config value to be updated
+/// - `dir` - fragments directory
+pub(crate) fn merge_dir(base: &mut Value, dir: &Path) -> Result<(), Box<dyn Error>> {
+    for path in fragment_paths(dir)? {
+        let bytes = fs::read(&path)?;
+        let fragment: Value =
+            serde_json::from_str(&jsonc::strip(&encoding::decode(&path, &bytes)?))?;
+        merge(base, fragment);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge() {
+        let mut fake_base = json!({
+            "changelog": {"update": true, "package": "foo"},
+            "control": {"sourceControl": {"buildDepends": ["a"]}}
+        });
+        let fake_overlay = json!({
+            "changelog": {"package": "bar"},
+            "control": {"sourceControl": {"buildDepends": ["b"]}, "update": true}
+        });
+
+        merge(&mut fake_base, fake_overlay);
+
+        let expected = json!({
+            "changelog": {"update": true, "package": "bar"},
+            "control": {"sourceControl": {"buildDepends": ["b"]}, "update": true}
+        });
+        assert_eq!(fake_base, expected);
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::process::Command;
+
+use super::CHANGELOG_FILE;
+use crate::DebyError;
+
+/// Returns a value of git config key, `None` if git is missing or the key is not set
+///
+/// # Arguments
+///
+/// - `key` - git config key, e.g. `user.name`
+pub(crate) fn config_value(key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "--get", key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(value).filter(|v| !v.is_empty())
+}
+
+/// Runs git with arguments in a directory and returns its trimmed output
+///
+/// # Arguments
+///
+/// - `root` - directory git runs in, current directory for an empty path
+/// - `args` - git arguments
+pub(crate) fn run(root: &Path, args: &[&str]) -> Result<String, DebyError> {
+    let mut command = Command::new("git");
+    if !root.as_os_str().is_empty() {
+        command.current_dir(root);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .map_err(|e| DebyError::Git(e.to_string()))?;
+    if !output.status.success() {
+        let reason = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(DebyError::Git(reason));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns revision of the previous release: the latest tag reachable from `HEAD`
+/// or the latest commit which changed _changelog_ file when there are no tags
+///
+/// # Arguments
+///
+/// - `root` - directory containing `debian` directory
+fn previous_release(root: &Path) -> Option<String> {
+    run(root, &["describe", "--tags", "--abbrev=0"])
+        .or_else(|_| run(root, &["log", "-1", "--format=%H", "--", CHANGELOG_FILE]))
+        .ok()
+        .filter(|r| !r.is_empty())
+}
+
+/// Returns revision range of `git log`, the whole history is used when there's no previous release
+///
+/// # Arguments
+///
+/// - `since` - revision of the previous release
+fn log_range(since: Option<&str>) -> String {
+    match since {
+        Some(rev) => format!("{}..HEAD", rev),
+        None => "HEAD".to_string(),
+    }
+}
+
+/// Returns subjects of non-merge commits since the previous release, oldest first
+///
+/// # Arguments
+///
+/// - `root` - directory containing `debian` directory
+pub(crate) fn subjects_since_release(root: &Path) -> Result<Vec<String>, DebyError> {
+    let range = log_range(previous_release(root).as_deref());
+    let stdout = run(
+        root,
+        &["log", "--no-merges", "--reverse", "--format=%s", &range],
+    )?;
+
+    Ok(stdout
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+
+    /// Commits a file change with a subject in a temporary repo, commits made
+    /// within the same second are ordered by `time` offset
+    fn fake_commit(root: &Path, file: &str, subject: &str, time: u32) {
+        let date = format!("{} +0000", 1641798000 + time);
+        fs::write(root.join(file), subject).unwrap();
+        run(root, &["add", "-A"]).unwrap();
+        let status = Command::new("git")
+            .current_dir(root)
+            .args(["commit", "-q", "-m", subject])
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_log_range() {
+        assert_eq!(log_range(Some("v1.0.0")), "v1.0.0..HEAD");
+        assert_eq!(log_range(None), "HEAD");
+    }
+
+    #[test]
+    fn test_subjects_since_release() {
+        let fake_root = env::temp_dir().join(format!("deby-git-{}", std::process::id()));
+        fs::create_dir_all(fake_root.join("debian")).unwrap();
+        run(&fake_root, &["init", "-q", "-b", "main"]).unwrap();
+        run(&fake_root, &["config", "user.name", "John Doe"]).unwrap();
+        run(&fake_root, &["config", "user.email", "john@example.com"]).unwrap();
+        run(&fake_root, &["config", "commit.gpgsign", "false"]).unwrap();
+        run(&fake_root, &["config", "tag.gpgsign", "false"]).unwrap();
+
+        fake_commit(&fake_root, "a", "initial commit", 0);
+        fake_commit(&fake_root, CHANGELOG_FILE, "release 0.9.0", 1);
+        fake_commit(&fake_root, "a", "add feature", 2);
+        let without_tags = subjects_since_release(&fake_root);
+
+        run(&fake_root, &["tag", "v1.0.0"]).unwrap();
+        fake_commit(&fake_root, "a", "fix crash", 3);
+        run(&fake_root, &["checkout", "-q", "-b", "topic"]).unwrap();
+        fake_commit(&fake_root, "b", "add option", 4);
+        run(&fake_root, &["checkout", "-q", "main"]).unwrap();
+        run(
+            &fake_root,
+            &["merge", "-q", "--no-ff", "-m", "Merge topic", "topic"],
+        )
+        .unwrap();
+        let with_tags = subjects_since_release(&fake_root);
+
+        fs::remove_dir_all(&fake_root).unwrap();
+        assert_eq!(without_tags.unwrap(), vec!["add feature"]);
+        assert_eq!(with_tags.unwrap(), vec!["fix crash", "add option"]);
+    }
+}
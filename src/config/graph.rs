@@ -0,0 +1,330 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+use super::relation::Relation;
+use crate::version;
+
+const PRE_DEPENDS: &str = "Pre-Depends";
+
+/// Relationship fields of a single binary package
+pub(crate) struct Node<'a> {
+    pub(crate) package: &'a str,
+    /// Field names and their relations, one relation (or a group of alternatives) per item
+    pub(crate) fields: Vec<(&'static str, Vec<String>)>,
+}
+
+/// A problem found in relationship fields of binary packages
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyIssue {
+    /// Binary package the problem was found in
+    pub package: String,
+    /// Relationship field name, e.g. `Pre-Depends`
+    pub field: String,
+    pub kind: DependencyIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyIssueKind {
+    /// Relation string can't be parsed
+    Invalid(String),
+    /// Package depends on itself
+    SelfReference,
+    /// Package is listed more than once in the same field
+    Duplicate(String),
+    /// Version constraints on a package can't be satisfied together
+    Impossible(String),
+    /// Packages pre-depend on each other, the cycle is listed in order
+    CircularPreDepends(Vec<String>),
+}
+
+impl Display for DependencyIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prefix = format!("{} {}", self.package, self.field);
+        match &self.kind {
+            DependencyIssueKind::Invalid(r) => write!(f, "{}: invalid relation {}", prefix, r),
+            DependencyIssueKind::SelfReference => write!(f, "{}: depends on itself", prefix),
+            DependencyIssueKind::Duplicate(p) => write!(f, "{}: {} is listed twice", prefix, p),
+            DependencyIssueKind::Impossible(p) => {
+                write!(f, "{}: constraints on {} can't be satisfied", prefix, p)
+            }
+            DependencyIssueKind::CircularPreDepends(cycle) => {
+                write!(f, "{}: circular dependency {}", prefix, cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+/// A version bound, a version and whether the bound is inclusive
+type Bound<'a> = Option<(&'a str, bool)>;
+
+/// Checks that all version constraints on a single package can be satisfied together
+///
+/// # Arguments
+///
+/// - `constraints` - operators and versions, e.g. `(">=", "2")`
+fn is_satisfiable(constraints: &[&(String, String)]) -> bool {
+    let mut lower: Bound = None;
+    let mut upper: Bound = None;
+
+    for (op, v) in constraints {
+        let (l, u) = match op.as_str() {
+            ">=" => (Some((v.as_str(), true)), None),
+            ">>" => (Some((v.as_str(), false)), None),
+            "<=" => (None, Some((v.as_str(), true))),
+            "<<" => (None, Some((v.as_str(), false))),
+            _ => (Some((v.as_str(), true)), Some((v.as_str(), true))),
+        };
+        if let Some(l) = l {
+            lower = match lower {
+                Some(cur) => match version::compare(l.0, cur.0) {
+                    Ordering::Greater => Some(l),
+                    Ordering::Equal => Some((l.0, l.1 && cur.1)),
+                    Ordering::Less => Some(cur),
+                },
+                None => Some(l),
+            };
+        }
+        if let Some(u) = u {
+            upper = match upper {
+                Some(cur) => match version::compare(u.0, cur.0) {
+                    Ordering::Less => Some(u),
+                    Ordering::Equal => Some((u.0, u.1 && cur.1)),
+                    Ordering::Greater => Some(cur),
+                },
+                None => Some(u),
+            };
+        }
+    }
+
+    match (lower, upper) {
+        (Some(l), Some(u)) => match version::compare(l.0, u.0) {
+            Ordering::Greater => false,
+            Ordering::Equal => l.1 && u.1,
+            Ordering::Less => true,
+        },
+        _ => true,
+    }
+}
+
+/// Validates relations of a single field
+///
+/// # Arguments
+///
+/// - `package` - binary package name
+/// - `field` - relationship field name
+/// - `relations` - relations of the field
+fn validate_field(package: &str, field: &str, relations: &[String]) -> Vec<DependencyIssue> {
+    let issue = |kind| DependencyIssue {
+        package: package.to_string(),
+        field: field.to_string(),
+        kind,
+    };
+
+    let mut issues = vec![];
+    let mut parsed: Vec<Relation> = vec![];
+    for r in relations {
+        // alternatives and restricted relations are only checked for syntax
+        let alternatives: Vec<&str> = r.split('|').collect();
+        let mut group = vec![];
+        for a in &alternatives {
+            match Relation::parse(a) {
+                Ok(rel) => group.push(rel),
+                Err(_) => issues.push(issue(DependencyIssueKind::Invalid(a.trim().to_string()))),
+            }
+        }
+        if alternatives.len() == 1 {
+            parsed.extend(group.into_iter().filter(|r| !r.is_restricted()));
+        }
+    }
+
+    if parsed.iter().any(|r| r.name == package) {
+        issues.push(issue(DependencyIssueKind::SelfReference));
+    }
+
+    let mut names: Vec<&str> = parsed.iter().map(|r| r.name.as_str()).collect();
+    names.sort_unstable();
+    names.dedup();
+    for name in names {
+        let same: Vec<&Relation> = parsed.iter().filter(|r| r.name == name).collect();
+        if same.len() < 2 {
+            continue;
+        }
+        let constraints: Vec<&(String, String)> =
+            same.iter().filter_map(|r| r.constraint.as_ref()).collect();
+        if is_satisfiable(&constraints) {
+            issues.push(issue(DependencyIssueKind::Duplicate(name.to_string())));
+        } else {
+            issues.push(issue(DependencyIssueKind::Impossible(name.to_string())));
+        }
+    }
+
+    issues
+}
+
+/// Finds cycles of `Pre-Depends` relations between binary packages of the same source
+///
+/// # Arguments
+///
+/// - `nodes` - relationship fields of binary packages
+fn find_cycles(nodes: &[Node]) -> Vec<Vec<String>> {
+    let edges: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|node| {
+            node.fields
+                .iter()
+                .filter(|(field, _)| *field == PRE_DEPENDS)
+                .flat_map(|(_, relations)| relations)
+                .filter_map(|r| Relation::parse(r).ok())
+                .filter_map(|r| nodes.iter().position(|n| n.package == r.name))
+                .collect()
+        })
+        .collect();
+
+    let mut cycles: Vec<Vec<String>> = vec![];
+    for start in 0..nodes.len() {
+        let mut stack = vec![(start, vec![start])];
+        while let Some((current, path)) = stack.pop() {
+            for &next in &edges[current] {
+                if next == start {
+                    // every cycle is reported once, starting at its smallest node
+                    if path.iter().all(|&i| i >= start) && path.len() > 1 {
+                        let mut cycle: Vec<String> =
+                            path.iter().map(|&i| nodes[i].package.to_string()).collect();
+                        cycle.push(nodes[start].package.to_string());
+                        cycles.push(cycle);
+                    }
+                } else if next > start && !path.contains(&next) {
+                    let mut next_path = path.clone();
+                    next_path.push(next);
+                    stack.push((next, next_path));
+                }
+            }
+        }
+    }
+
+    cycles
+}
+
+/// Validates relationship fields of all binary packages of the same source
+///
+/// # Arguments
+///
+/// - `nodes` - relationship fields of binary packages
+pub(crate) fn validate(nodes: &[Node]) -> Vec<DependencyIssue> {
+    let mut issues: Vec<DependencyIssue> = nodes
+        .iter()
+        .flat_map(|node| {
+            node.fields
+                .iter()
+                .flat_map(move |(field, relations)| validate_field(node.package, field, relations))
+        })
+        .collect();
+
+    for cycle in find_cycles(nodes) {
+        issues.push(DependencyIssue {
+            package: cycle[0].clone(),
+            field: PRE_DEPENDS.to_string(),
+            kind: DependencyIssueKind::CircularPreDepends(cycle),
+        });
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_node<'a>(package: &'a str, pre_depends: &[&str]) -> Node<'a> {
+        Node {
+            package,
+            fields: vec![(
+                PRE_DEPENDS,
+                pre_depends.iter().map(|s| s.to_string()).collect(),
+            )],
+        }
+    }
+
+    #[test]
+    fn test_is_satisfiable() {
+        let c = |op: &str, v: &str| (op.to_string(), v.to_string());
+
+        assert!(is_satisfiable(&[&c(">=", "1"), &c("<<", "2")]));
+        assert!(is_satisfiable(&[&c(">=", "1"), &c("<=", "1")]));
+        assert!(!is_satisfiable(&[&c(">=", "2"), &c("<<", "1")]));
+        assert!(!is_satisfiable(&[&c(">>", "1"), &c("<=", "1")]));
+        assert!(!is_satisfiable(&[&c("=", "1"), &c("=", "2")]));
+    }
+
+    #[test]
+    fn test_validate_self_reference() {
+        let actual = validate(&[fake_node("foo", &["foo"])]);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].kind, DependencyIssueKind::SelfReference);
+    }
+
+    #[test]
+    fn test_validate_duplicate_and_impossible() {
+        let actual = validate(&[
+            fake_node("foo", &["libc6", "libc6 (>= 2.30)"]),
+            fake_node("bar", &["libc6 (>= 2)", "libc6 (<< 1)"]),
+        ]);
+
+        assert_eq!(
+            actual[0].kind,
+            DependencyIssueKind::Duplicate("libc6".to_string())
+        );
+        assert_eq!(actual[1].package, "bar");
+        assert_eq!(
+            actual[1].kind,
+            DependencyIssueKind::Impossible("libc6".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_restricted() {
+        let actual = validate(&[fake_node(
+            "foo",
+            &[
+                "libfoo-dev [amd64]",
+                "libfoo-dev (>= 2) [i386]",
+                "foo <stage1>",
+            ],
+        )]);
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_validate_invalid() {
+        let actual = validate(&[fake_node("foo", &["Bad Name", "a1 | b1"])]);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(
+            actual[0].kind,
+            DependencyIssueKind::Invalid("Bad Name".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_circular_pre_depends() {
+        let actual = validate(&[
+            fake_node("foo", &["foo-data"]),
+            fake_node("foo-data", &["foo-common"]),
+            fake_node("foo-common", &["foo"]),
+            fake_node("foo-doc", &["foo"]),
+        ]);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(
+            actual[0].kind,
+            DependencyIssueKind::CircularPreDepends(vec![
+                "foo".to_string(),
+                "foo-data".to_string(),
+                "foo-common".to_string(),
+                "foo".to_string()
+            ])
+        );
+    }
+}
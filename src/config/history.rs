@@ -0,0 +1,155 @@
+use chrono::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use super::Config;
+
+/// Environment variables identifying a CI job, the first one found is used
+const CI_JOB_VARS: [&str; 4] = ["GITHUB_RUN_ID", "CI_JOB_ID", "BUILD_TAG", "BUILD_ID"];
+/// Environment variables identifying a user, the first one found is used
+const USER_VARS: [&str; 2] = ["USER", "USERNAME"];
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct History {
+    enabled: bool,
+    #[serde(default = "History::default_path")]
+    path: String,
+}
+
+impl History {
+    /// Returns a value of the first environment variable set
+    ///
+    /// # Arguments
+    ///
+    /// - `vars` - environment variable names
+    fn first_env(vars: &[&str]) -> Option<String> {
+        vars.iter().find_map(|v| env::var(v).ok())
+    }
+
+    /// Formats SHA-256 hash of file contents
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - file contents
+    fn hash(contents: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(contents))
+    }
+
+    /// Formats a single history record as a JSON line
+    ///
+    /// # Arguments
+    ///
+    /// - `timestamp` - time of a run
+    /// - `version` - version written to _changelog_ file
+    /// - `targets` - written files and their contents
+    fn format_record(
+        timestamp: &DateTime<Utc>,
+        version: Option<&str>,
+        targets: &[(&str, Vec<u8>)],
+    ) -> String {
+        let targets: Vec<_> = targets
+            .iter()
+            .map(|(path, contents)| json!({"path": path, "sha256": History::hash(contents)}))
+            .collect();
+
+        let record = json!({
+            "timestamp": timestamp.to_rfc3339(),
+            "version": version,
+            "targets": targets,
+            "user": History::first_env(&USER_VARS),
+            "ciJob": History::first_env(&CI_JOB_VARS),
+        });
+
+        format!("{}\n", record)
+    }
+
+    /// Appends a record of a generation run to history file if history is enabled
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `version` - version written to _changelog_ file
+    /// - `targets` - paths to written files
+    pub(crate) fn record(
+        config: &Config,
+        version: Option<&str>,
+        targets: &[&str],
+    ) -> Result<(), Box<dyn Error>> {
+        let history = &config.history;
+        if !history.enabled || targets.is_empty() {
+            return Ok(());
+        }
+
+        let mut written = vec![];
+        for &path in targets {
+            written.push((path, fs::read(config.path(path))?));
+        }
+        let record = History::format_record(&Utc::now(), version, &written);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(config.path(&history.path))?;
+        file.write_all(record.as_bytes())?;
+
+        Ok(())
+    }
+
+    pub(crate) fn default() -> Self {
+        Self {
+            enabled: false,
+            path: History::default_path(),
+        }
+    }
+
+    fn default_path() -> String {
+        "debian/.deby-history".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let default = History::default();
+
+        assert!(!default.enabled);
+        assert_eq!(default.path, "debian/.deby-history");
+    }
+
+    #[test]
+    fn test_hash() {
+        let actual = History::hash(b"abc");
+
+        assert_eq!(
+            actual,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_format_record() {
+        let fake_time = Utc.with_ymd_and_hms(2022, 1, 10, 10, 0, 0).unwrap();
+        let fake_targets = vec![("debian/changelog", b"abc".to_vec())];
+        let actual = History::format_record(&fake_time, Some("1.0.0"), &fake_targets);
+        let record: serde_json::Value = serde_json::from_str(&actual).unwrap();
+
+        assert!(actual.ends_with('\n'));
+        assert_eq!(record["timestamp"], "2022-01-10T10:00:00+00:00");
+        assert_eq!(record["version"], "1.0.0");
+        assert_eq!(record["targets"][0]["path"], "debian/changelog");
+        assert_eq!(
+            record["targets"][0]["sha256"],
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
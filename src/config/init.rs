@@ -0,0 +1,190 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use super::control::LATEST_STANDARDS_VERSION;
+use super::git::config_value as git_config;
+use super::CONFIG_FILE;
+use crate::DebyError;
+
+/// Values detected from current directory and git metadata
+#[derive(Debug, PartialEq)]
+pub(crate) struct Detected {
+    pub(crate) package: String,
+    pub(crate) name: String,
+    pub(crate) email: String,
+    pub(crate) vcs_browser: String,
+}
+
+impl Detected {
+    /// Detects package name from current directory, maintainer and repository URL from git config
+    fn detect() -> Self {
+        let package = env::current_dir()
+            .ok()
+            .and_then(|d| d.file_name().map(|n| n.to_string_lossy().to_string()))
+            .map(|n| package_name(&n))
+            .unwrap_or_default();
+
+        Self {
+            package,
+            name: git_config("user.name").unwrap_or_default(),
+            email: git_config("user.email").unwrap_or_default(),
+            vcs_browser: git_config("remote.origin.url")
+                .map(|url| browser_url(&url))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Converts a directory name into a valid package name
+///
+/// # Arguments
+///
+/// - `name` - directory name
+fn package_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '+' | '-' | '.' => c,
+            _ => '-',
+        })
+        .collect::<String>()
+        .trim_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+/// Converts a git remote URL into a URL which can be opened in a browser
+///
+/// # Arguments
+///
+/// - `url` - git remote URL, e.g. `git@github.com:user/repo.git`
+fn browser_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches(".git");
+    match url.strip_prefix("git@") {
+        Some(rest) => format!("https://{}", rest.replacen(':', "/", 1)),
+        None => url.to_string(),
+    }
+}
+
+/// Formats contents of a starter config file with comments
+///
+/// # Arguments
+///
+/// - `detected` - values detected from current directory and git metadata
+pub(crate) fn template(detected: &Detected) -> String {
+    format!(
+        r#"{{
+  // generated by deby::init, see https://github.com/ink8bit/deby for all settings
+  "changelog": {{
+    "update": true,
+    "package": "{package}",
+    // any suite name, e.g. unstable, experimental or bookworm-backports
+    "distribution": "unstable",
+    // low, medium, high, emergency or critical
+    "urgency": "low",
+    "maintainer": {{
+      "name": "{name}",
+      "email": "{email}"
+    }}
+  }},
+  "control": {{
+    "update": true,
+    "sourceControl": {{
+      "source": "{package}",
+      "section": "utils",
+      "priority": "optional",
+      "maintainer": {{
+        "name": "{name}",
+        "email": "{email}"
+      }},
+      // packages needed to build the source package
+      "buildDepends": ["debhelper (>= 12)"],
+      "standardsVersion": "{standards_version}",
+      "vcsBrowser": "{vcs_browser}"
+    }},
+    "binaryControl": {{
+      "package": "{package}",
+      "description": "",
+      "section": "utils",
+      "priority": "optional",
+      // all, any or a space separated list of architectures, e.g. "amd64 arm64"
+      "architecture": "any"
+    }}
+  }}
+}}
+"#,
+        package = detected.package,
+        standards_version = LATEST_STANDARDS_VERSION,
+        name = json_escape(&detected.name),
+        email = json_escape(&detected.email),
+        vcs_browser = json_escape(&detected.vcs_browser),
+    )
+}
+
+/// Escapes a value to be put inside a JSON string
+///
+/// # Arguments
+///
+/// - `value` - raw value
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted.trim_matches('"').to_string()
+}
+
+/// Writes a starter config file `.debyrc` into current directory, existing config file is kept
+///
+/// # Arguments
+///
+/// - `create_debian_dir` - create an empty `debian` directory as well
+pub(crate) fn init(create_debian_dir: bool) -> Result<(), Box<dyn Error>> {
+    if Path::new(CONFIG_FILE).exists() {
+        return Err(DebyError::ConfigExists.into());
+    }
+
+    fs::write(CONFIG_FILE, template(&Detected::detect()))?;
+    if create_debian_dir {
+        fs::create_dir_all("debian")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("My_Project"), "my-project");
+        assert_eq!(package_name("_foo2_"), "foo2");
+    }
+
+    #[test]
+    fn test_browser_url() {
+        assert_eq!(
+            browser_url("git@github.com:ink8bit/deby.git"),
+            "https://github.com/ink8bit/deby"
+        );
+        assert_eq!(
+            browser_url("https://github.com/ink8bit/deby.git\n"),
+            "https://github.com/ink8bit/deby"
+        );
+    }
+
+    #[test]
+    fn test_template_is_valid_config() {
+        let fake_detected = Detected {
+            package: "foo".to_string(),
+            name: "John \"JD\" Doe".to_string(),
+            email: "john@example.com".to_string(),
+            vcs_browser: "https://example.com/foo".to_string(),
+        };
+
+        let actual: Config = template(&fake_detected).parse().unwrap();
+
+        assert_eq!(actual.changelog.package(), "foo");
+        assert_eq!(actual.control.source_name(), "foo");
+    }
+}
@@ -0,0 +1,150 @@
+/// Removes `//` line comments, `/* */` block comments and trailing commas
+/// so that commented config contents can be parsed as plain JSON.
+/// String values are kept as they are
+///
+/// # Arguments
+///
+/// - `contents` - config contents with comments
+pub(crate) fn strip(contents: &str) -> String {
+    let mut without_comments = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            without_comments.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        without_comments.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                without_comments.push(c);
+            }
+            ('/', Some('/')) => {
+                while chars.peek().is_some_and(|&next| next != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    // keep line breaks so error positions still point at the right line
+                    if next == '\n' {
+                        without_comments.push('\n');
+                    }
+                    prev = next;
+                }
+                without_comments.push(' ');
+            }
+            _ => without_comments.push(c),
+        }
+    }
+
+    strip_trailing_commas(&without_comments)
+}
+
+/// Returns `true` if config contents have `//` or `/* */` comments
+///
+/// # Arguments
+///
+/// - `contents` - config contents
+pub(crate) fn has_comments(contents: &str) -> bool {
+    strip(contents) != strip_trailing_commas(contents)
+}
+
+/// Removes commas followed only by whitespace and a closing bracket
+///
+/// # Arguments
+///
+/// - `contents` - config contents without comments
+fn strip_trailing_commas(contents: &str) -> String {
+    let chars: Vec<char> = contents.chars().collect();
+    let mut s = String::with_capacity(contents.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            s.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                s.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+            s.push(c);
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if !matches!(next, Some('}') | Some(']')) {
+                s.push(c);
+            }
+        } else {
+            s.push(c);
+        }
+        i += 1;
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_comments() {
+        let fake_contents = r#"{
+  // build tools
+  "buildDepends": ["debhelper", /* for tests */ "python3"],
+  "homepage": "https://example.com/a//b" /* trailing */
+}"#;
+
+        let actual: serde_json::Value = serde_json::from_str(&strip(fake_contents)).unwrap();
+
+        assert_eq!(
+            actual,
+            serde_json::json!({
+                "buildDepends": ["debhelper", "python3"],
+                "homepage": "https://example.com/a//b"
+            })
+        );
+    }
+
+    #[test]
+    fn test_strip_trailing_commas() {
+        let fake_contents = r#"{"a": [1, 2,], "b": "x,}",}"#;
+
+        assert_eq!(strip(fake_contents), r#"{"a": [1, 2], "b": "x,}"}"#);
+    }
+
+    #[test]
+    fn test_has_comments() {
+        assert!(has_comments("{\n  // comment\n  \"a\": 1\n}"));
+        assert!(has_comments(r#"{"a": /* why */ 1}"#));
+        assert!(!has_comments(r#"{"a": "https://example.com", "b": [1,],}"#));
+    }
+
+    #[test]
+    fn test_strip_escaped_quote() {
+        let fake_contents = r#"{"a": "say \"//hi\""} // comment"#;
+
+        assert_eq!(strip(fake_contents), r#"{"a": "say \"//hi\""} "#);
+    }
+}
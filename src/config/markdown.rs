@@ -0,0 +1,442 @@
+use chrono::NaiveDate;
+use regex::Regex;
+
+/// A single release of a keep-a-changelog formatted `CHANGELOG.md`
+#[derive(Debug, PartialEq)]
+pub(crate) struct Release {
+    pub(crate) version: String,
+    pub(crate) date: Option<NaiveDate>,
+    /// Sections like `Added` with their items, items before any section have no heading
+    pub(crate) sections: Vec<(Option<String>, Vec<String>)>,
+}
+
+impl Release {
+    /// Formats items of a release as changes, items of a section follow its heading, e.g. `Added:`
+    pub(crate) fn changes(&self) -> String {
+        let mut lines = vec![];
+        for (heading, items) in &self.sections {
+            match heading {
+                Some(h) => {
+                    lines.push(format!("{}:", h));
+                    lines.extend(items.iter().map(|i| format!("  - {}", i)));
+                }
+                None => lines.extend(items.iter().cloned()),
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Replaces markdown links `[text](url)` with `text (url)`, a link which text is its URL becomes the URL
+///
+/// # Arguments
+///
+/// - `line` - a line of markdown
+pub(crate) fn collapse_links(line: &str) -> String {
+    let link = Regex::new(r"\[([^\[\]]+)\]\(([^()\s]+)\)").unwrap();
+
+    link.replace_all(line, |caps: &regex::Captures| match caps[1] == caps[2] {
+        true => caps[2].to_string(),
+        false => format!("{} ({})", &caps[1], &caps[2]),
+    })
+    .to_string()
+}
+
+/// Parses keep-a-changelog formatted contents into releases, newer releases go first.
+/// `Unreleased` section and link reference definitions are skipped, nested list items
+/// become separate items
+///
+/// # Arguments
+///
+/// - `contents` - contents of `CHANGELOG.md`
+pub(crate) fn parse(contents: &str) -> Vec<Release> {
+    let release_re = Regex::new(r"^##\s+\[?([^\]\s]+)\]?(?:\s+-\s+(\d{4}-\d{2}-\d{2}))?").unwrap();
+    let section_re = Regex::new(r"^###\s+(.+?)\s*$").unwrap();
+    let item_re = Regex::new(r"^\s*[-*+]\s+(.+?)\s*$").unwrap();
+
+    let mut releases: Vec<Release> = vec![];
+    let mut in_release = false;
+    for line in contents.lines() {
+        if let Some(caps) = release_re.captures(line) {
+            in_release = !caps[1].eq_ignore_ascii_case("unreleased");
+            if in_release {
+                releases.push(Release {
+                    version: caps[1].trim_start_matches('v').to_string(),
+                    date: caps
+                        .get(2)
+                        .and_then(|d| NaiveDate::parse_from_str(d.as_str(), "%Y-%m-%d").ok()),
+                    sections: vec![],
+                });
+            }
+            continue;
+        }
+        if line.starts_with("# ") {
+            in_release = false;
+        }
+        let release = match releases.last_mut().filter(|_| in_release) {
+            Some(r) => r,
+            None => continue,
+        };
+
+        if let Some(caps) = section_re.captures(line) {
+            release.sections.push((Some(caps[1].to_string()), vec![]));
+        } else if let Some(caps) = item_re.captures(line) {
+            if release.sections.is_empty() {
+                release.sections.push((None, vec![]));
+            }
+            let (_, items) = release.sections.last_mut().unwrap();
+            items.push(collapse_links(&caps[1]));
+        } else if line.starts_with(char::is_whitespace) && !line.trim().is_empty() {
+            let item = release
+                .sections
+                .last_mut()
+                .and_then(|(_, items)| items.last_mut());
+            if let Some(item) = item {
+                item.push(' ');
+                item.push_str(&collapse_links(line.trim()));
+            }
+        }
+    }
+
+    releases
+}
+
+/// Groups changes of a release: a change followed by `  - ` items is a heading
+/// of a subsection, e.g. `Bug fixes:`, other changes are ungrouped items
+///
+/// # Arguments
+///
+/// - `changes` - string value of changes
+fn group(changes: &str) -> (Vec<String>, Vec<(String, Vec<String>)>) {
+    let lines: Vec<&str> = changes.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    let mut items = vec![];
+    let mut subsections: Vec<(String, Vec<String>)> = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(item) = line.strip_prefix("  - ") {
+            match subsections.last_mut() {
+                Some((_, subsection)) => subsection.push(item.trim().to_string()),
+                None => items.push(item.trim().to_string()),
+            }
+            continue;
+        }
+
+        let is_heading = lines.get(i + 1).is_some_and(|l| l.starts_with("  - "));
+        match is_heading {
+            true => {
+                let heading = line.trim().trim_end_matches(':');
+                subsections.push((heading.to_string(), vec![]));
+            }
+            false => items.push(line.trim().to_string()),
+        }
+    }
+
+    (items, subsections)
+}
+
+/// Formats a release section from its header line, ungrouped items and subsections,
+/// ungrouped items go first
+///
+/// # Arguments
+///
+/// - `header` - header line of a release
+/// - `items` - list lines of ungrouped items
+/// - `subsections` - headings of subsections with their list lines
+fn format_section(header: &str, items: &[String], subsections: &[(String, Vec<String>)]) -> String {
+    let mut section = format!("{}\n", header);
+    if !items.is_empty() {
+        section.push('\n');
+        items
+            .iter()
+            .for_each(|i| section.push_str(&format!("{}\n", i)));
+    }
+    for (heading, lines) in subsections {
+        section.push_str(&format!("\n### {}\n\n", heading));
+        lines
+            .iter()
+            .for_each(|l| section.push_str(&format!("{}\n", l)));
+    }
+
+    section
+}
+
+/// Formats a keep-a-changelog section of a release. A change followed by `  - ` items
+/// is a heading of a subsection, e.g. `Bug fixes:`, other changes become list items
+/// written before subsections
+///
+/// # Arguments
+///
+/// - `version` - version of a release
+/// - `date` - date of a release
+/// - `changes` - string value of changes
+pub(crate) fn render(version: &str, date: NaiveDate, changes: &str) -> String {
+    let (items, subsections) = group(changes);
+    let bullet = |i: &String| format!("- {}", i);
+
+    format_section(
+        &format!("## [{}] - {}", version, date.format("%Y-%m-%d")),
+        &items.iter().map(bullet).collect::<Vec<String>>(),
+        &subsections
+            .iter()
+            .map(|(h, s)| (h.clone(), s.iter().map(bullet).collect()))
+            .collect::<Vec<(String, Vec<String>)>>(),
+    )
+}
+
+/// Adds changes to the section of an existing release: ungrouped items go after its ungrouped
+/// items, items of a subsection go to the subsection with the same heading, items already
+/// listed are skipped. A release missing from contents gets a new section on top of released versions
+///
+/// # Arguments
+///
+/// - `contents` - contents of `CHANGELOG.md`
+/// - `version` - version of a release
+/// - `date` - date of a release, used for a new section
+/// - `changes` - string value of changes
+pub(crate) fn extend(contents: &str, version: &str, date: NaiveDate, changes: &str) -> String {
+    let release_re = Regex::new(r"^##\s+\[?([^\]\s]+)\]?").unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    let start = lines.iter().position(|l| {
+        release_re
+            .captures(l)
+            .is_some_and(|caps| caps[1].trim_start_matches('v') == version)
+    });
+    let start = match start {
+        Some(start) => start,
+        None => return prepend(contents, &render(version, date, changes)),
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| {
+            l.starts_with("## ") || l.starts_with("# ") || l.starts_with('[') && l.contains("]: ")
+        })
+        .map_or(lines.len(), |p| start + 1 + p);
+
+    let mut items: Vec<String> = vec![];
+    let mut subsections: Vec<(String, Vec<String>)> = vec![];
+    for line in lines[start + 1..end]
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+    {
+        match (line.strip_prefix("### "), subsections.last_mut()) {
+            (Some(heading), _) => subsections.push((heading.trim().to_string(), vec![])),
+            (None, Some((_, subsection))) => subsection.push(line.to_string()),
+            (None, None) => items.push(line.to_string()),
+        }
+    }
+
+    let (new_items, new_subsections) = group(changes);
+    let add = |lines: &mut Vec<String>, new: Vec<String>| {
+        for item in new.iter().map(|i| format!("- {}", i)) {
+            if !lines.contains(&item) {
+                lines.push(item);
+            }
+        }
+    };
+    add(&mut items, new_items);
+    for (heading, new) in new_subsections {
+        match subsections
+            .iter_mut()
+            .find(|(h, _)| h.eq_ignore_ascii_case(&heading))
+        {
+            Some((_, subsection)) => add(subsection, new),
+            None => {
+                let mut subsection = vec![];
+                add(&mut subsection, new);
+                subsections.push((heading, subsection));
+            }
+        }
+    }
+
+    let mut extended: Vec<String> = lines[..start].iter().map(|l| l.to_string()).collect();
+    extended.push(format_section(lines[start], &items, &subsections));
+    if end < lines.len() {
+        extended.extend(lines[end..].iter().map(|l| l.to_string()));
+    }
+
+    format!("{}\n", extended.join("\n").trim_end())
+}
+
+/// Puts a release section on top of released versions, after `Unreleased` section and the title.
+/// A missing file gets `# Changelog` title
+///
+/// # Arguments
+///
+/// - `contents` - contents of `CHANGELOG.md`
+/// - `section` - release section
+pub(crate) fn prepend(contents: &str, section: &str) -> String {
+    if contents.trim().is_empty() {
+        return format!("# Changelog\n\n{}", section);
+    }
+
+    let release_re = Regex::new(r"^##\s+\[?([^\]\s]+)\]?").unwrap();
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let is_release = release_re
+            .captures(line)
+            .is_some_and(|caps| !caps[1].eq_ignore_ascii_case("unreleased"));
+        if is_release || line.starts_with("[") && line.contains("]: ") {
+            return format!(
+                "{}{}\n{}",
+                &contents[..offset],
+                section,
+                &contents[offset..]
+            );
+        }
+        offset += line.len();
+    }
+
+    format!("{}\n\n{}", contents.trim_end(), section)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAKE_CHANGELOG_MD: &str = "# Changelog
+
+## [Unreleased]
+
+- work in progress
+
+## [1.1.0] - 2022-01-10
+
+### Added
+
+- new [option](https://example.com/docs)
+  which is long
+  - nested item
+
+### Fixed
+
+- crash on start
+
+## [1.0.0] - 2022-01-09
+
+- initial release
+
+[1.1.0]: https://example.com/compare/v1.0.0...v1.1.0
+";
+
+    #[test]
+    fn test_parse() {
+        let actual = parse(FAKE_CHANGELOG_MD);
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0].version, "1.1.0");
+        assert_eq!(actual[0].date, NaiveDate::from_ymd_opt(2022, 1, 10));
+        assert_eq!(
+            actual[0].changes(),
+            "Added:
+  - new option (https://example.com/docs) which is long
+  - nested item
+Fixed:
+  - crash on start"
+        );
+        assert_eq!(actual[1].changes(), "initial release");
+    }
+
+    #[test]
+    fn test_collapse_links() {
+        assert_eq!(
+            collapse_links("see [docs](https://example.com) and [https://a.b](https://a.b)"),
+            "see docs (https://example.com) and https://a.b"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let fake_changes = "Bug fixes:\n  - crash on start\n  - typo\nupdate dependencies";
+
+        let actual = render(
+            "1.1.0",
+            NaiveDate::from_ymd_opt(2022, 1, 10).unwrap(),
+            fake_changes,
+        );
+
+        assert_eq!(
+            actual,
+            "## [1.1.0] - 2022-01-10
+
+- update dependencies
+
+### Bug fixes
+
+- crash on start
+- typo
+"
+        );
+    }
+
+    #[test]
+    fn test_extend() {
+        let fake_contents = "# Changelog
+
+## [1.1.0] - 2022-01-10
+
+- update dependencies
+
+### Fixed
+
+- crash on start
+
+## [1.0.0] - 2022-01-09
+
+- initial release
+";
+        let fake_date = NaiveDate::from_ymd_opt(2022, 1, 11).unwrap();
+
+        let actual = extend(
+            fake_contents,
+            "1.1.0",
+            fake_date,
+            "update docs\nupdate dependencies\nFixed:\n  - typo\nAdded:\n  - option",
+        );
+
+        assert_eq!(
+            actual,
+            "# Changelog
+
+## [1.1.0] - 2022-01-10
+
+- update dependencies
+- update docs
+
+### Fixed
+
+- crash on start
+- typo
+
+### Added
+
+- option
+
+## [1.0.0] - 2022-01-09
+
+- initial release
+"
+        );
+        assert_eq!(
+            extend(fake_contents, "1.2.0", fake_date, "change"),
+            prepend(fake_contents, &render("1.2.0", fake_date, "change"))
+        );
+    }
+
+    #[test]
+    fn test_prepend() {
+        let fake_section = "## [1.1.0] - 2022-01-10\n\n- change\n";
+
+        assert_eq!(
+            prepend("", fake_section),
+            "# Changelog\n\n## [1.1.0] - 2022-01-10\n\n- change\n"
+        );
+        assert_eq!(
+            prepend(
+                "# Changelog\n\n## [Unreleased]\n\n## [1.0.0] - 2022-01-09\n",
+                fake_section
+            ),
+            "# Changelog\n\n## [Unreleased]\n\n## [1.1.0] - 2022-01-10\n\n- change\n\n## [1.0.0] - 2022-01-09\n"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use crate::deb822::{self, Paragraph};
+use crate::DebyError;
+
+/// Returns a key identifying a paragraph: `Source` for source paragraph
+/// and `Package` value for binary ones
+///
+/// # Arguments
+///
+/// - `paragraph` - a single paragraph of _control_ file
+fn key(paragraph: &Paragraph) -> Option<String> {
+    match paragraph.get("Source") {
+        Some(_) => Some("Source".to_string()),
+        None => paragraph.get("Package").map(|p| format!("Package {}", p)),
+    }
+}
+
+/// Updates fields of an existing paragraph with generated ones in place,
+/// a new field is inserted after the preceding generated field.
+/// Managed fields which are not generated anymore are removed, their comments are kept
+///
+/// # Arguments
+///
+/// - `current` - an existing paragraph
+/// - `generated` - generated fields of the same paragraph
+/// - `managed` - names of fields deby writes
+fn merge_paragraph(current: &mut Paragraph, generated: Paragraph, managed: &[&str]) {
+    let removed: Vec<String> = current
+        .fields
+        .iter()
+        .map(|f| f.name.clone())
+        .filter(|name| {
+            generated.get(name).is_none() && managed.iter().any(|m| m.eq_ignore_ascii_case(name))
+        })
+        .collect();
+    for name in removed {
+        current.remove(&name);
+    }
+
+    let mut previous: Option<usize> = None;
+    for field in generated.fields {
+        let position = current
+            .fields
+            .iter()
+            .position(|f| f.name.eq_ignore_ascii_case(&field.name));
+        let index = match position {
+            Some(i) => {
+                current.fields[i].name = field.name;
+                current.fields[i].value = field.value;
+                i
+            }
+            None => {
+                let i = previous.map_or(0, |p| p + 1);
+                current.fields.insert(i, field);
+                i
+            }
+        };
+        previous = Some(index);
+    }
+}
+
+/// Merges generated _control_ file contents into existing ones: fields deby writes are
+/// updated or removed when they are not generated anymore, other fields, paragraphs
+/// which are not generated and comments are kept, generated paragraphs missing
+/// from existing contents are appended
+///
+/// # Arguments
+///
+/// - `current` - existing _control_ file contents
+/// - `generated` - generated _control_ file contents
+/// - `managed` - names of fields deby writes
+pub(crate) fn merge(current: &str, generated: &str, managed: &[&str]) -> Result<String, DebyError> {
+    let mut merged = deb822::parse(current)?;
+
+    for paragraph in deb822::parse(generated)? {
+        let generated_key = key(&paragraph);
+        let existing = merged
+            .iter_mut()
+            .find(|p| generated_key.is_some() && key(p) == generated_key);
+        match existing {
+            Some(existing) => merge_paragraph(existing, paragraph, managed),
+            None => merged.push(paragraph),
+        }
+    }
+
+    Ok(deb822::to_string(&merged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAKE_MANAGED: &[&str] = &[
+        "Source",
+        "Maintainer",
+        "Build-Depends",
+        "Standards-Version",
+        "Package",
+        "Architecture",
+        "Essential",
+        "Depends",
+        "Recommends",
+        "Description",
+    ];
+
+    #[test]
+    fn test_merge() {
+        let fake_current = "# Hand-maintained, deby updates generated fields only
+Source: foo
+Section: utils
+Maintainer: Old <old@example.com>
+X-Manual: kept
+Build-Depends: debhelper-compat (= 12),
+ cargo
+
+Package: foo
+Architecture: any
+# keep this package arch-specific
+Depends: libc6
+Description: foo tool
+ Long description.
+
+Package: foo-extra
+Architecture: all
+Description: hand written package
+";
+        let fake_generated = "Source: foo
+Section: utils
+Maintainer: New <new@example.com>
+Build-Depends: debhelper-compat (= 13)
+Standards-Version: 4.7.2
+
+Package: foo
+Architecture: any
+Depends: libc6, zlib1g
+Description: foo tool
+
+Package: foo-doc
+Architecture: all
+Description: foo documentation
+";
+
+        let actual = merge(fake_current, fake_generated, FAKE_MANAGED).unwrap();
+
+        assert_eq!(
+            actual,
+            "# Hand-maintained, deby updates generated fields only
+Source: foo
+Section: utils
+Maintainer: New <new@example.com>
+X-Manual: kept
+Build-Depends: debhelper-compat (= 13)
+Standards-Version: 4.7.2
+
+Package: foo
+Architecture: any
+# keep this package arch-specific
+Depends: libc6, zlib1g
+Description: foo tool
+
+Package: foo-extra
+Architecture: all
+Description: hand written package
+
+Package: foo-doc
+Architecture: all
+Description: foo documentation
+"
+        );
+    }
+
+    #[test]
+    fn test_merge_empty() {
+        let fake_generated = "Source: foo\n\nPackage: foo\nArchitecture: any\n";
+
+        assert_eq!(
+            merge("", fake_generated, FAKE_MANAGED).unwrap(),
+            fake_generated
+        );
+    }
+
+    #[test]
+    fn test_merge_removes_managed_fields() {
+        let fake_current = "Source: foo
+
+Package: foo
+Architecture: any
+Essential: yes
+Recommends: curl
+X-Manual: kept
+Description: foo tool
+";
+        let fake_generated = "Source: foo
+
+Package: foo
+Architecture: any
+Description: foo tool
+";
+
+        let actual = merge(fake_current, fake_generated, FAKE_MANAGED).unwrap();
+
+        assert_eq!(
+            actual,
+            "Source: foo
+
+Package: foo
+Architecture: any
+X-Manual: kept
+Description: foo tool
+"
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_comments_of_removed_fields() {
+        let fake_current = "Package: foo
+Depends: a,
+# keep b
+ b
+# recommended by upstream
+Recommends: curl
+Description: foo tool
+";
+        let fake_generated = "Package: foo
+Depends: a, c
+Description: foo tool
+";
+
+        let actual = merge(fake_current, fake_generated, FAKE_MANAGED).unwrap();
+
+        assert_eq!(
+            actual,
+            "Package: foo
+# keep b
+Depends: a, c
+# recommended by upstream
+Description: foo tool
+"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use serde_json::Value;
+
+use crate::DebyError;
+
+/// Current layout version of config file
+const CONFIG_VERSION: u64 = 2;
+
+const CONFIG_VERSION_KEY: &str = "configVersion";
+/// Top-level keys of legacy flat layout
+const LEGACY_KEYS: [&str; 2] = ["package", "maintainer"];
+
+/// Moves a legacy top-level value into a section unless the section already defines it.
+/// Missing sections are left missing, their required settings are not known.
+/// Returns `false` when the section is missing
+///
+/// # Arguments
+///
+/// - `config` - config contents
+/// - `path` - path to the section
+/// - `key` - key in the section
+/// - `value` - legacy value
+fn move_into(config: &mut Value, path: &str, key: &str, value: &Value) -> bool {
+    match config.pointer_mut(path).and_then(Value::as_object_mut) {
+        Some(section) => {
+            section.entry(key).or_insert_with(|| value.clone());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Upgrades version 1 layout: top-level `package` and `maintainer` keys
+/// are moved into `changelog` and `control.sourceControl` sections. A key is refused
+/// rather than dropped when neither section exists
+///
+/// # Arguments
+///
+/// - `config` - config contents
+fn migrate_v1(config: &mut Value) -> Result<Vec<String>, DebyError> {
+    let mut deprecations = vec![];
+
+    for key in LEGACY_KEYS {
+        let value = match config.as_object_mut().and_then(|c| c.remove(key)) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let source_key = if key == "package" { "source" } else { key };
+        let moved = move_into(config, "/changelog", key, &value);
+        let moved = move_into(config, "/control/sourceControl", source_key, &value) || moved;
+        if !moved {
+            return Err(DebyError::LegacyConfigKey(key.to_string()));
+        }
+
+        deprecations.push(format!(
+            "top-level `{}` is deprecated, move it into `changelog` and `control.sourceControl` sections",
+            key
+        ));
+    }
+
+    Ok(deprecations)
+}
+
+/// Upgrades config contents of older layouts to the current one, `configVersion` key is consumed.
+/// Returns deprecation messages for every upgraded setting
+///
+/// # Arguments
+///
+/// - `value` - config contents
+pub(crate) fn migrate(value: &mut Value) -> Result<Vec<String>, DebyError> {
+    let config = match value.as_object_mut() {
+        Some(c) => c,
+        None => return Ok(vec![]),
+    };
+
+    let has_legacy_keys = LEGACY_KEYS.iter().any(|k| config.contains_key(*k));
+    let version = match config.remove(CONFIG_VERSION_KEY) {
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| DebyError::UnsupportedConfigVersion(v.to_string()))?,
+        None if has_legacy_keys => 1,
+        None => CONFIG_VERSION,
+    };
+    if version == 0 || version > CONFIG_VERSION {
+        return Err(DebyError::UnsupportedConfigVersion(version.to_string()));
+    }
+
+    let mut deprecations = vec![];
+    if version < 2 {
+        deprecations.extend(migrate_v1(value)?);
+    }
+
+    Ok(deprecations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_legacy_layout() {
+        let mut fake_config = json!({
+            "package": "foo",
+            "maintainer": {"name": "John", "email": "john@example.com"},
+            "changelog": {"update": true, "package": "bar"},
+            "control": {"update": true, "sourceControl": {}},
+        });
+
+        let actual = migrate(&mut fake_config).unwrap();
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(
+            fake_config,
+            json!({
+                "changelog": {
+                    "update": true,
+                    "package": "bar",
+                    "maintainer": {"name": "John", "email": "john@example.com"}
+                },
+                "control": {"update": true, "sourceControl": {
+                    "source": "foo",
+                    "maintainer": {"name": "John", "email": "john@example.com"}
+                }},
+            })
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_layout_one_section() {
+        let mut fake_config = json!({
+            "package": "foo",
+            "changelog": {"update": true},
+        });
+
+        let actual = migrate(&mut fake_config).unwrap();
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(
+            fake_config,
+            json!({"changelog": {"update": true, "package": "foo"}})
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_layout_without_sections() {
+        let mut fake_config = json!({
+            "package": "foo",
+            "control": {"update": false},
+        });
+
+        let actual = migrate(&mut fake_config);
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::LegacyConfigKey(k)) if k == "package"
+        ));
+    }
+
+    #[test]
+    fn test_migrate_current_version() {
+        let mut fake_config = json!({"configVersion": 2, "package": "foo"});
+
+        let actual = migrate(&mut fake_config).unwrap();
+
+        assert!(actual.is_empty());
+        assert_eq!(fake_config, json!({"package": "foo"}));
+    }
+
+    #[test]
+    fn test_migrate_unsupported_version() {
+        let mut fake_config = json!({"configVersion": 3});
+
+        let actual = migrate(&mut fake_config);
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::UnsupportedConfigVersion(v)) if v == "3"
+        ));
+    }
+}
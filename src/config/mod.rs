@@ -1,89 +1,1102 @@
 use serde::Deserialize;
+use serde_json::{json, Value};
 
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::env;
 use std::error::Error;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
+mod arch;
+mod autopkgtest;
+mod builder;
 mod changelog;
 mod control;
+mod cross;
+mod drift;
+mod edit;
+pub(crate) mod encoding;
+mod error;
+mod fragments;
+pub(crate) mod git;
+pub(crate) mod graph;
+mod history;
+mod init;
+mod jsonc;
+mod markdown;
+mod merge;
+mod migrate;
+mod packages;
+mod profiles;
+mod relation;
+mod sign;
+mod validate;
+mod wrap;
 
-use changelog::Changelog;
-use control::Control;
+use crate::lint::Lint;
+use crate::template;
+use crate::DebyError;
+use autopkgtest::{Autopkgtest, TESTS_CONTROL_FILE};
+pub use builder::ConfigBuilder;
+pub(crate) use changelog::Changelog;
+use changelog::CHANGELOG_FILE;
+pub use changelog::{ChangelogOptions, Upload, Urgency};
+use control::{Control, CONTROL_FILE};
+pub use control::{ControlOptions, CustomField, Paragraphs, Placement};
+pub use drift::{ControlDrift, DriftKind};
+pub use error::ConfigParseError;
+use history::History;
+use sign::Sign;
+pub use validate::ValidationIssue;
 
-#[derive(Deserialize, Debug)]
+/// Maintainer of a package, both name and email are required in _control_ file
+#[derive(Deserialize, Debug, Default)]
 struct Maintainer {
     name: String,
     email: String,
 }
 
+/// Settings of `.debyrc` config file, can be built from an in-memory string with `str::parse`
 #[derive(Deserialize, Debug)]
-pub(crate) struct Config {
+pub struct Config {
     #[serde(default = "Changelog::default")]
     changelog: Changelog,
     #[serde(default = "Control::default")]
     control: Control,
+    #[serde(default = "Autopkgtest::default")]
+    tests: Vec<Autopkgtest>,
+    #[serde(default = "Sign::default")]
+    sign: Sign,
+    #[serde(default = "History::default")]
+    history: History,
+    #[serde(default = "Config::default_context")]
+    context: BTreeMap<String, Value>,
+    /// Reject unknown keys instead of ignoring them
+    #[serde(default = "Config::default_strict")]
+    strict: bool,
+    /// Deprecation messages of settings upgraded from older config layouts
+    #[serde(skip)]
+    deprecations: Vec<String>,
+    /// Directory config file was found in, generated files are resolved relative to it
+    #[serde(skip)]
+    root: PathBuf,
 }
 
 const CONFIG_FILE: &str = ".debyrc";
+const TOML_CONFIG_FILE: &str = ".debyrc.toml";
+const CONFIG_DIR: &str = ".debyrc.d";
+const CARGO_MANIFEST_FILE: &str = "Cargo.toml";
+const SOURCE_FORMAT_FILE: &str = "debian/source/format";
+
+impl FromStr for Config {
+    type Err = DebyError;
+
+    /// Parses config contents in the same *JSON* format as `.debyrc`
+    ///
+    /// # Arguments
+    ///
+    /// - `s` - config contents
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = serde_json::from_str(&jsonc::strip(s))
+            .map_err(|e| DebyError::ConfigParse(ConfigParseError::syntax(&e)))?;
+
+        Self::from_value(value).map_err(|e| match e {
+            DebyError::ConfigParse(err) => DebyError::ConfigParse(err.locate(s)),
+            e => e,
+        })
+    }
+}
 
 impl Config {
-    pub(crate) fn new() -> Result<Self, std::io::Error> {
-        let config = Self::parse()?;
+    pub(crate) fn new() -> Result<Self, Box<dyn Error>> {
+        Self::load(None)
+    }
+
+    /// Creates configuration of every package from `packages` array, each package
+    /// writes its files into its own output directory. Configuration without `packages` array
+    /// is treated as a single package
+    pub(crate) fn packages() -> Result<Vec<Self>, Box<dyn Error>> {
+        let root = Self::find_root()?;
+        let mut value = Self::read_merged(&root)?;
+        let packages = packages::take(&mut value);
+        if packages.is_empty() {
+            return Ok(vec![Self::load(None)?]);
+        }
+
+        let mut configs = vec![];
+        for (i, package) in packages.into_iter().enumerate() {
+            let (dir, package_value) = packages::split(&value, i, package)?;
+            let mut config = Self::from_value(package_value)?;
+            config.root = root.join(dir);
+            configs.push(config);
+        }
+
+        Ok(configs)
+    }
+
+    /// Reads configuration from config file found in current or parent directories
+    ///
+    /// # Arguments
+    ///
+    /// - `profile` - profile to be applied
+    fn load(profile: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let root = Self::find_root()?;
+        let config = Self::parse(&root, profile)?;
 
         Ok(Self {
             changelog: config.changelog,
             control: config.control,
+            tests: config.tests,
+            sign: config.sign,
+            history: config.history,
+            context: config.context,
+            strict: config.strict,
+            deprecations: config.deprecations,
+            root,
+        })
+    }
+
+    /// Finds the nearest parent directory with config file when current directory has none.
+    /// Current directory is used when no parent directory has config file either
+    pub(crate) fn find_root() -> Result<PathBuf, Box<dyn Error>> {
+        if Self::has_config(Path::new("")) {
+            return Ok(PathBuf::new());
+        }
+
+        let cwd = env::current_dir()?;
+        let root = cwd
+            .ancestors()
+            .skip(1)
+            .find(|dir| Self::has_config(dir))
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        Ok(root)
+    }
+
+    /// Checks that a directory contains `.debyrc`, `.debyrc.toml` or `.debyrc.d`
+    ///
+    /// # Arguments
+    ///
+    /// - `dir` - directory to check
+    fn has_config(dir: &Path) -> bool {
+        [CONFIG_FILE, TOML_CONFIG_FILE, CONFIG_DIR]
+            .iter()
+            .any(|f| dir.join(f).exists())
+    }
+
+    /// Resolves a path relative to config file directory
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - path relative to project root, e.g. `debian/changelog`
+    pub(crate) fn path(&self, path: &str) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// Creates configuration selected by update options: a config file at a custom path
+    /// or config file found in current or parent directories, with a profile applied.
+    /// Context values override `context` from config file
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - path to config file
+    /// - `profile` - profile to be applied
+    /// - `context` - free-form context values
+    pub(crate) fn select(
+        path: Option<&Path>,
+        profile: Option<&str>,
+        context: &BTreeMap<String, String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut config = match path {
+            Some(path) => Self::from_path(path, profile)?,
+            None => Self::load(profile)?,
+        };
+        config.extend_context(context);
+
+        Ok(config)
+    }
+
+    /// Creates configuration from a config file at a custom path,
+    /// `*.toml` files are parsed as TOML, any other file as JSON
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - path to config file
+    /// - `profile` - profile to be applied
+    fn from_path(path: &Path, profile: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let contents = Self::read_file(path)?;
+        let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+        if !is_toml && profile.is_none() {
+            return Ok(contents.parse()?);
+        }
+
+        let mut value = match is_toml {
+            true => Self::parse_toml(&contents)?,
+            false => serde_json::from_str(&jsonc::strip(&contents))
+                .map_err(|e| DebyError::ConfigParse(ConfigParseError::syntax(&e)))?,
+        };
+        if let Some(profile) = profile {
+            profiles::apply(&mut value, profile)?;
+        }
+
+        Ok(Self::from_value(value)?)
+    }
+
+    /// Deserializes configuration, unknown keys are rejected if `strict` is set.
+    /// Profiles which were not applied and `packages` array are dropped
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - config contents
+    fn from_value(mut value: Value) -> Result<Self, DebyError> {
+        let variants = Self::variants(&value)?;
+        profiles::take(&mut value);
+        packages::take(&mut value);
+        let config = Self::from_shared(value)?;
+
+        for (path, variant) in variants {
+            Self::from_shared(variant).map_err(|e| match e {
+                DebyError::ConfigParse(err) => DebyError::ConfigParse(err.prefixed(&path)),
+                DebyError::UnknownConfigKeys(keys) => DebyError::UnknownConfigKeys(
+                    keys.iter().map(|k| format!("{}.{}", path, k)).collect(),
+                ),
+                e => e,
+            })?;
+        }
+
+        Ok(config)
+    }
+
+    /// Returns config contents of every profile and every package merged into shared settings,
+    /// together with their key paths, e.g. `profiles.bookworm` or `packages[0]`
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - config contents
+    fn variants(value: &Value) -> Result<Vec<(String, Value)>, DebyError> {
+        let mut shared = value.clone();
+        let profiles = profiles::take(&mut shared);
+        let packages = packages::take(&mut shared);
+
+        let mut variants = vec![];
+        match profiles {
+            None => {}
+            Some(Value::Object(profiles)) => {
+                for (name, overrides) in profiles {
+                    let mut variant = shared.clone();
+                    fragments::merge(&mut variant, overrides);
+                    variants.push((format!("profiles.{}", name), variant));
+                }
+            }
+            Some(_) => {
+                return Err(DebyError::ConfigParse(ConfigParseError::new(
+                    "profiles",
+                    "expected a map of profile names to field overrides",
+                )))
+            }
+        }
+        for (i, package) in packages.into_iter().enumerate() {
+            let (_, variant) = packages::split(&shared, i, package)?;
+            variants.push((format!("packages[{}]", i), variant));
+        }
+
+        Ok(variants)
+    }
+
+    /// Parses config contents without `profiles` and `packages`
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - config contents
+    fn from_shared(mut value: Value) -> Result<Self, DebyError> {
+        let deprecations = migrate::migrate(&mut value)?;
+        let strict = value
+            .get("strict")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let mut unknown_keys = vec![];
+        let mut config: Config = serde_ignored::deserialize(value.clone(), |path| {
+            unknown_keys.push(path.to_string());
         })
+        .map_err(|_| DebyError::ConfigParse(ConfigParseError::invalid(value)))?;
+
+        if strict && !unknown_keys.is_empty() {
+            return Err(DebyError::UnknownConfigKeys(unknown_keys));
+        }
+        config.deprecations = deprecations;
+        config.control.rewrite_extra_priority();
+
+        Ok(config)
+    }
+
+    fn default_strict() -> bool {
+        false
+    }
+
+    fn default_context() -> BTreeMap<String, Value> {
+        BTreeMap::new()
+    }
+
+    /// Adds context values, values provided here override values from config file
+    ///
+    /// # Arguments
+    ///
+    /// - `context` - free-form context values
+    pub(crate) fn extend_context(&mut self, context: &BTreeMap<String, String>) {
+        for (k, v) in context {
+            self.context.insert(k.clone(), Value::String(v.clone()));
+        }
+    }
+
+    /// Replaces `{context.<key>}` placeholders with context values
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - generated file contents
+    pub(crate) fn interpolate(&self, contents: &str) -> String {
+        let context: BTreeMap<String, String> = self
+            .context
+            .iter()
+            .map(|(k, v)| match v {
+                Value::String(s) => (k.clone(), s.clone()),
+                v => (k.clone(), v.to_string()),
+            })
+            .collect();
+
+        template::render_context(contents, &context)
     }
 
-    fn parse() -> Result<Config, std::io::Error> {
-        let config_data = fs::read_to_string(CONFIG_FILE)?;
-        let config: Config = serde_json::from_str(&config_data)?;
+    fn parse(root: &Path, profile: Option<&str>) -> Result<Config, Box<dyn Error>> {
+        let mut value = Self::read_merged(root)?;
+        if let Some(profile) = profile {
+            profiles::apply(&mut value, profile)?;
+        }
+
+        let config = Self::from_value(value).map_err(|e| match e {
+            // position is only known when config is read from a single JSON file
+            DebyError::ConfigParse(err)
+                if root.join(CONFIG_FILE).exists() && !root.join(CONFIG_DIR).is_dir() =>
+            {
+                match Self::read_raw(root) {
+                    Ok(contents) => DebyError::ConfigParse(err.locate(&contents)),
+                    Err(_) => DebyError::ConfigParse(err),
+                }
+            }
+            e => e,
+        })?;
 
         Ok(config)
     }
 
+    /// Reads config file merged with fragments from `.debyrc.d` directory.
+    /// Config file can be omitted when fragments directory exists
+    ///
+    /// # Arguments
+    ///
+    /// - `root` - directory config file is read from
+    fn read_merged(root: &Path) -> Result<Value, Box<dyn Error>> {
+        let dir = root.join(CONFIG_DIR);
+        if !dir.is_dir() {
+            return match Self::read_base(root)? {
+                Some(value) => Ok(value),
+                None => Ok(serde_json::from_str(&Self::read_raw(root)?)?),
+            };
+        }
+
+        let mut value = Self::read_base(root)?.unwrap_or_else(|| json!({}));
+        fragments::merge_dir(&mut value, &dir)?;
+
+        Ok(value)
+    }
+
+    /// Reads `.debyrc` config file, falls back to `.debyrc.toml`
+    /// and then to `[package.metadata.deby]` table of `Cargo.toml` when it's missing
+    ///
+    /// # Arguments
+    ///
+    /// - `root` - directory config file is read from
+    fn read_base(root: &Path) -> Result<Option<Value>, Box<dyn Error>> {
+        if root.join(CONFIG_FILE).exists() {
+            let value = serde_json::from_str(&jsonc::strip(&Self::read_raw(root)?))
+                .map_err(|e| DebyError::ConfigParse(ConfigParseError::syntax(&e)))?;
+            return Ok(Some(value));
+        }
+        if root.join(TOML_CONFIG_FILE).exists() {
+            let contents = Self::read_file(&root.join(TOML_CONFIG_FILE))?;
+            return Ok(Some(Self::parse_toml(&contents)?));
+        }
+        if root.join(CARGO_MANIFEST_FILE).exists() {
+            let contents = Self::read_file(&root.join(CARGO_MANIFEST_FILE))?;
+            return Self::cargo_metadata(&contents);
+        }
+
+        Ok(None)
+    }
+
+    /// Returns `[package.metadata.deby]` table of Cargo manifest if it's present
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - contents of `Cargo.toml` file
+    fn cargo_metadata(contents: &str) -> Result<Option<Value>, Box<dyn Error>> {
+        let manifest = Self::parse_toml(contents)?;
+
+        Ok(manifest.pointer("/package/metadata/deby").cloned())
+    }
+
+    /// Parses TOML config contents into the same JSON value `.debyrc` is read into,
+    /// so field names are shared between both formats
+    ///
+    /// # Arguments
+    ///
+    /// - `contents` - contents of `.debyrc.toml` file
+    fn parse_toml(contents: &str) -> Result<Value, Box<dyn Error>> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    fn read_raw(root: &Path) -> Result<String, Box<dyn Error>> {
+        Self::read_file(&root.join(CONFIG_FILE))
+    }
+
+    fn read_file(path: &Path) -> Result<String, Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+        let config_data = encoding::decode(path, &bytes)?;
+
+        Ok(config_data)
+    }
+
+    /// Returns a config source other than `.debyrc` settings are read from:
+    /// `.debyrc.d` fragments, `.debyrc.toml` or `Cargo.toml` metadata
+    ///
+    /// # Arguments
+    ///
+    /// - `root` - directory config file is read from
+    fn other_source(root: &Path) -> Result<Option<&'static str>, Box<dyn Error>> {
+        if root.join(CONFIG_DIR).is_dir() {
+            return Ok(Some(CONFIG_DIR));
+        }
+        if root.join(CONFIG_FILE).exists() {
+            return Ok(None);
+        }
+        if root.join(TOML_CONFIG_FILE).exists() {
+            return Ok(Some(TOML_CONFIG_FILE));
+        }
+        match Self::read_base(root)? {
+            Some(_) => Ok(Some(CARGO_MANIFEST_FILE)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads `.debyrc` contents to be edited, a missing file is read as an empty object.
+    /// A file with comments is refused since rewriting it would drop the comments,
+    /// so is config read from another source, which an edit of `.debyrc` would miss or shadow
+    ///
+    /// # Arguments
+    ///
+    /// - `root` - directory config file is read from
+    fn read_editable(root: &Path) -> Result<Value, Box<dyn Error>> {
+        if let Some(source) = Self::other_source(root)? {
+            return Err(DebyError::ConfigNotEditable(format!(
+                "settings are read from {}, edit it by hand",
+                source
+            ))
+            .into());
+        }
+        if !root.join(CONFIG_FILE).exists() {
+            return Ok(json!({}));
+        }
+
+        let contents = Self::read_raw(root)?;
+        if jsonc::has_comments(&contents) {
+            return Err(DebyError::ConfigNotEditable(format!(
+                "{} has comments which rewriting would drop, edit it by hand",
+                CONFIG_FILE
+            ))
+            .into());
+        }
+
+        let value = serde_json::from_str(&jsonc::strip(&contents))
+            .map_err(|e| DebyError::ConfigParse(ConfigParseError::syntax(&e)))?;
+
+        Ok(value)
+    }
+
+    /// Validates edited config contents and writes them to config file
+    ///
+    /// # Arguments
+    ///
+    /// - `root` - directory config file is written to
+    /// - `value` - edited contents of config file
+    fn write_raw(root: &Path, value: &Value) -> Result<(), Box<dyn Error>> {
+        let mut migrated = value.clone();
+        migrate::migrate(&mut migrated)?;
+        let mut issues = validate::check(&migrated);
+        issues.extend(Self::parsed_issues(migrated));
+        if !issues.is_empty() {
+            return Err(DebyError::ConfigInvalid(issues).into());
+        }
+
+        let mut contents = serde_json::to_string_pretty(value)?;
+        contents.push('\n');
+        fs::write(root.join(CONFIG_FILE), contents)?;
+
+        Ok(())
+    }
+
+    /// Adds a binary package definition to config file
+    ///
+    /// # Arguments
+    ///
+    /// - `package` - binary package name
+    /// - `description` - binary package description
+    /// - `section` - binary package section
+    /// - `architecture` - binary package architecture
+    pub(crate) fn add_binary(
+        package: &str,
+        description: &str,
+        section: &str,
+        architecture: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let root = Self::find_root()?;
+        let mut value = Self::read_editable(&root)?;
+
+        let binary = json!({
+            "package": package,
+            "description": description,
+            "section": section,
+            "priority": "optional",
+            "architecture": architecture,
+        });
+        edit::add_binary(&mut value, binary)?;
+
+        Self::write_raw(&root, &value)
+    }
+
+    /// Imports existing _control_ file into `control` section of config file,
+    /// config file is created when missing. Returns imported packages
+    pub(crate) fn import_control() -> Result<Control, Box<dyn Error>> {
+        let root = Self::find_root()?;
+        let path = root.join(CONTROL_FILE);
+        let imported = Control::import_file(&path)?;
+
+        let mut value = Self::read_editable(&root)?;
+        edit::import_control(&mut value, imported)?;
+        Self::write_raw(&root, &value)?;
+
+        Control::from_file(&path)
+    }
+
+    /// Adds a dependency to a relationship field of config file
+    ///
+    /// # Arguments
+    ///
+    /// - `field` - relationship field name, e.g. `buildDepends`
+    /// - `dependency` - dependency string value, e.g. `libfoo (>= 1.2)`
+    /// - `package` - binary package name for binary relationship fields
+    pub(crate) fn add_dependency(
+        field: &str,
+        dependency: &str,
+        package: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let root = Self::find_root()?;
+        let mut value = Self::read_editable(&root)?;
+
+        edit::add_dependency(&mut value, field, dependency, package)?;
+
+        Self::write_raw(&root, &value)
+    }
+
+    /// Returns paths of files written by a run according to config file settings
+    ///
+    /// # Arguments
+    ///
+    /// - `changelog` - run updates _changelog_ file
+    /// - `control` - run updates _control_ file
+    fn targets(&self, changelog: bool, control: bool) -> Vec<&'static str> {
+        let mut targets = vec![];
+        if changelog && self.changelog.is_enabled() {
+            targets.push(CHANGELOG_FILE);
+        }
+        if control && self.control.is_enabled() {
+            targets.push(CONTROL_FILE);
+            if !self.tests.is_empty() {
+                targets.push(TESTS_CONTROL_FILE);
+            }
+        }
+
+        targets
+    }
+
+    /// Writes _control_ file and `debian/tests/control` file if tests are configured
+    ///
+    /// # Arguments
+    ///
+    /// - `user_defined_fields` - dynamic field values provided by a user
+    /// - `paragraphs` - paragraphs to be generated, config file setting is used for `None`
+    fn write_control(
+        &self,
+        custom_fields: &[CustomField],
+        paragraphs: Option<&Paragraphs>,
+    ) -> Result<String, Box<dyn Error>> {
+        let mut msg = Control::update(self, custom_fields, paragraphs)?;
+        if self.control.is_enabled() {
+            if let Some(tests_msg) = Autopkgtest::update(self)? {
+                msg.push('\n');
+                msg.push_str(tests_msg);
+            }
+        }
+
+        Ok(msg)
+    }
+
+    /// Parses raw user-defined fields, fields without `XS-` prefix are placed
+    /// into `package` paragraph or the last binary paragraph without it
+    ///
+    /// # Arguments
+    ///
+    /// - `user_defined_fields` - dynamic fields defined by a user
+    /// - `package` - binary package name
+    fn custom_fields(
+        &self,
+        user_defined_fields: Vec<&str>,
+        package: Option<&str>,
+    ) -> Result<Vec<CustomField>, DebyError> {
+        let package = package.or_else(|| self.control.binary_names().last().copied());
+
+        CustomField::parse(user_defined_fields, package)
+    }
+
+    /// Creates `debian` directory next to config file if it's missing
+    fn create_debian_dir(&self) -> Result<(), Box<dyn Error>> {
+        let dir = self.path("debian");
+        if !dir.exists() {
+            fs::create_dir_all(dir)?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn update(
         &self,
         version: &str,
         changes: &str,
         user_defined_fields: Vec<&str>,
-    ) -> Result<(&str, &str), Box<dyn Error>> {
-        if !Path::new("debian").exists() {
-            fs::create_dir("debian")?;
-        }
+        options: &ChangelogOptions,
+    ) -> Result<(String, String), Box<dyn Error>> {
+        self.create_debian_dir()?;
 
-        let changelog_msg = Changelog::update(&self, &version, &changes)?;
-        let control_msg = Control::update(&self, user_defined_fields)?;
+        let custom_fields = self.custom_fields(user_defined_fields, None)?;
+
+        let changelog_msg = Changelog::update(self, version, changes, options)?;
+        let control_msg = self.write_control(&custom_fields, None)?;
         let msg = (changelog_msg, control_msg);
 
+        History::record(self, Some(version), &self.targets(true, true))?;
+
         Ok(msg)
     }
 
+    /// Updates _control_ file, placed user-defined fields of options are written
+    /// after raw ones and only paragraphs of options are generated if they are set
+    ///
+    /// # Arguments
+    ///
+    /// - `user_defined_fields` - dynamic fields defined by a user
+    /// - `options` - options of the update
     pub(crate) fn update_control(
         &self,
         user_defined_fields: Vec<&str>,
-    ) -> Result<&str, Box<dyn Error>> {
-        if !Path::new("debian").exists() {
-            fs::create_dir("debian")?;
-        }
+        options: &ControlOptions,
+    ) -> Result<String, Box<dyn Error>> {
+        let mut custom_fields = self.custom_fields(user_defined_fields, None)?;
+        custom_fields.extend(options.fields.iter().cloned());
 
-        let msg = Control::update(&self, user_defined_fields)?;
+        self.create_debian_dir()?;
+
+        let msg = self.write_control(&custom_fields, options.paragraphs.as_ref())?;
+
+        History::record(self, None, &self.targets(false, true))?;
 
         Ok(msg)
     }
 
+    /// Compares _control_ file contents rendered from config file with `debian/control` file,
+    /// every generated field is reported as added when the file is missing
+    ///
+    /// # Arguments
+    ///
+    /// - `user_defined_fields` - dynamic fields passed on update
+    pub(crate) fn check_control(
+        &self,
+        user_defined_fields: Vec<&str>,
+    ) -> Result<Vec<ControlDrift>, Box<dyn Error>> {
+        let custom_fields = self.custom_fields(user_defined_fields, None)?;
+        let generated = Control::render(self, &custom_fields, self.control.paragraphs())?;
+
+        let control_file = self.path(CONTROL_FILE);
+        let current = match control_file.exists() {
+            true => encoding::read_to_string(&control_file)?,
+            false => String::new(),
+        };
+
+        Ok(drift::diff(&current, &generated)?)
+    }
+
     pub(crate) fn update_changelog(
         &self,
         version: &str,
         changes: &str,
+        options: &ChangelogOptions,
+    ) -> Result<String, Box<dyn Error>> {
+        self.create_debian_dir()?;
+
+        let msg = Changelog::update(self, version, changes, options)?;
+
+        History::record(self, Some(version), &self.targets(true, false))?;
+
+        Ok(msg)
+    }
+
+    /// Backfills _changelog_ file with releases of a keep-a-changelog formatted `CHANGELOG.md`
+    ///
+    /// # Arguments
+    ///
+    /// - `markdown_file` - path to `CHANGELOG.md`
+    pub(crate) fn import_changelog(&self, markdown_file: &Path) -> Result<String, Box<dyn Error>> {
+        self.create_debian_dir()?;
+
+        let markdown_contents = encoding::read_to_string(markdown_file)?;
+        let msg = Changelog::import(self, &markdown_contents)?;
+
+        History::record(self, None, &self.targets(true, false))?;
+
+        Ok(msg)
+    }
+
+    /// Releases the latest entry of _changelog_ file
+    ///
+    /// # Arguments
+    ///
+    /// - `distribution` - distribution of a release, e.g. `unstable`
+    /// - `options` - options of the release
+    pub(crate) fn finalize_changelog(
+        &self,
+        distribution: &str,
+        options: &ChangelogOptions,
     ) -> Result<&str, Box<dyn Error>> {
-        if !Path::new("debian").exists() {
-            fs::create_dir("debian")?;
+        let msg = Changelog::finalize(self, distribution, options)?;
+
+        History::record(self, None, &self.targets(true, false))?;
+
+        Ok(msg)
+    }
+
+    /// Runs all lint checks of config file settings
+    ///
+    /// # Arguments
+    ///
+    /// - `version` - version to be written to _changelog_ file
+    pub(crate) fn lint(&self, version: Option<&str>) -> Result<Vec<Lint>, Box<dyn Error>> {
+        let source_format_file = self.path(SOURCE_FORMAT_FILE);
+        let format = match source_format_file.exists() {
+            true => Some(cross::SourceFormat::parse(&encoding::read_to_string(
+                source_format_file,
+            )?)),
+            false => None,
+        };
+
+        let mut lints = cross::check(self, version, format.as_ref());
+        lints.extend(
+            self.dependency_issues()
+                .iter()
+                .map(|i| Lint::error("dependency", i.to_string())),
+        );
+        lints.extend(self.control_lints());
+        lints.extend(
+            self.deprecations
+                .iter()
+                .map(|d| Lint::warning("deprecated-config", d.clone())),
+        );
+        lints.sort_by_key(|l| Reverse(l.severity));
+
+        Ok(lints)
+    }
+
+    /// Writes a starter config file into current directory
+    ///
+    /// # Arguments
+    ///
+    /// - `create_debian_dir` - create an empty `debian` directory as well
+    pub(crate) fn init(create_debian_dir: bool) -> Result<(), Box<dyn Error>> {
+        init::init(create_debian_dir)
+    }
+
+    /// Validates config file settings, all problems are reported at once
+    /// instead of failing on the first one
+    pub(crate) fn validate() -> Result<Vec<ValidationIssue>, Box<dyn Error>> {
+        let root = Self::find_root()?;
+        let mut value = Self::read_merged(&root)?;
+
+        let deprecations = migrate::migrate(&mut value)?;
+
+        let variants = Self::variants(&value);
+        profiles::take(&mut value);
+        packages::take(&mut value);
+
+        let mut issues = validate::check(&value);
+        issues.extend(deprecations.iter().map(|d| ValidationIssue::new("", d)));
+        issues.extend(Self::parsed_issues(value));
+
+        match variants {
+            Ok(variants) => {
+                // profiles and packages only report problems shared settings don't have
+                let mut variant_issues = vec![];
+                for (path, variant) in variants {
+                    let found = validate::check(&variant)
+                        .into_iter()
+                        .chain(Self::parsed_issues(variant));
+                    for issue in found.filter(|i| !issues.contains(i)) {
+                        let key = match issue.path.is_empty() {
+                            true => path.clone(),
+                            false => format!("{}.{}", path, issue.path),
+                        };
+                        variant_issues.push(ValidationIssue::new(&key, &issue.message));
+                    }
+                }
+                issues.extend(variant_issues);
+            }
+            Err(DebyError::ConfigParse(e)) => {
+                issues.push(ValidationIssue::new(&e.path, &e.message))
+            }
+            Err(e) => issues.push(ValidationIssue::new("", &e.to_string())),
+        }
+
+        Ok(issues)
+    }
+
+    /// Returns problems found when config contents are parsed: unknown keys,
+    /// invalid values, sections and dependencies
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - contents of config file
+    fn parsed_issues(value: Value) -> Vec<ValidationIssue> {
+        let mut issues = vec![];
+        match Self::from_value(value) {
+            Ok(config) => {
+                issues.extend(config.control.section_issues());
+                issues.extend(config.control.url_issues());
+                issues.extend(
+                    config
+                        .dependency_issues()
+                        .iter()
+                        .map(|i| ValidationIssue::new("control.binaryControl", &i.to_string())),
+                )
+            }
+            Err(DebyError::UnknownConfigKeys(keys)) => {
+                issues.extend(keys.iter().map(|k| ValidationIssue::new(k, "unknown key")))
+            }
+            Err(DebyError::ConfigParse(e)) => {
+                issues.push(ValidationIssue::new(&e.path, &e.message))
+            }
+            Err(e) => issues.push(ValidationIssue::new("", &e.to_string())),
         }
 
-        let msg = Changelog::update(&self, &version, &changes)?;
+        issues
+    }
+
+    /// Returns warnings about written _control_ fields: standards version,
+    /// priorities and description synopses of binary packages
+    pub(crate) fn control_lints(&self) -> Vec<Lint> {
+        let mut lints = Control::standards_version_lints(self);
+        lints.extend(Control::priority_lints(self));
+        lints.extend(Control::description_lints(self));
+
+        lints
+    }
+
+    pub(crate) fn dependency_issues(&self) -> Vec<graph::DependencyIssue> {
+        Control::dependency_issues(self)
+    }
+
+    pub(crate) fn append_control_binary(
+        &self,
+        package: &str,
+        user_defined_fields: Vec<&str>,
+    ) -> Result<String, Box<dyn Error>> {
+        let custom_fields = self.custom_fields(user_defined_fields, Some(package))?;
+        let msg = Control::append_binary(self, package, &custom_fields)?;
+
+        History::record(self, None, &[CONTROL_FILE])?;
 
         Ok(msg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        let fake_config = r#"{"changelog": {"update": true, "package": "foo", "maintainer": {"name": "John Doe", "email": "john@example.com"}}}"#;
+
+        let actual: Config = fake_config.parse().unwrap();
+
+        assert_eq!(actual.changelog.package(), "foo");
+        assert!(actual.changelog.is_enabled());
+        assert!(!actual.control.is_enabled());
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        let actual = "{".parse::<Config>();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigParse(e)) if e.position == Some((1, 1))
+        ));
+    }
+
+    #[test]
+    fn test_from_str_invalid_value() {
+        let fake_config = "{\n  \"changelog\": {\"update\": \"yes\"}\n}";
+
+        let actual = fake_config.parse::<Config>();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigParse(e)) if e.path == "changelog.update" && e.position == Some((2, 31))
+        ));
+    }
+
+    #[test]
+    fn test_from_str_maintainer() {
+        let fake_config = r#"{"changelog": {"update": true, "package": "foo", "maintainer": {"name": "John Doe"}}}"#;
+        assert!(fake_config.parse::<Config>().is_ok());
+
+        let fake_config = r#"{"control": {"update": true, "sourceControl": {"source": "foo", "priority": "optional", "maintainer": {}}, "binaryControl": []}}"#;
+        let actual = fake_config.parse::<Config>();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigParse(e)) if e.path == "control.sourceControl.maintainer"
+        ));
+    }
+
+    #[test]
+    fn test_from_str_invalid_variants() {
+        let fake_config = r#"{"profiles": {"bookworm": {"changelog": {"update": "yes"}}}}"#;
+        let actual = fake_config.parse::<Config>();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigParse(e)) if e.path == "profiles.bookworm.changelog.update"
+        ));
+
+        let fake_config =
+            r#"{"packages": [{"dir": "foo", "changelog": {"update": true, "urgency": "urgent"}}]}"#;
+        let actual = fake_config.parse::<Config>();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::ConfigParse(e)) if e.path.starts_with("packages[0].changelog")
+        ));
+    }
+
+    #[test]
+    fn test_from_str_strict() {
+        let fake_config = r#"{"strict": true, "changelog": {"update": false, "package": "foo", "pakage": "foo", "maintainer": {"name": "", "email": ""}}, "binarycontrol": {}}"#;
+
+        let actual = fake_config.parse::<Config>();
+
+        assert!(matches!(
+            actual,
+            Err(DebyError::UnknownConfigKeys(keys)) if keys == vec!["changelog.pakage", "binarycontrol"]
+        ));
+    }
+
+    #[test]
+    fn test_from_str_not_strict() {
+        let fake_config = r#"{"binarycontrol": {}}"#;
+
+        assert!(fake_config.parse::<Config>().is_ok());
+    }
+
+    #[test]
+    fn test_parsed_issues() {
+        let fake_config = json!({"control": {"binaryControl": []}});
+
+        let actual: Vec<String> = Config::parsed_issues(fake_config)
+            .iter()
+            .map(|i| i.to_string())
+            .collect();
+
+        assert_eq!(actual, vec!["control: missing field `update`"]);
+    }
+
+    #[test]
+    fn test_cargo_metadata() {
+        let fake_manifest = r#"
+[package]
+name = "foo"
+version = "1.0.0"
+
+[package.metadata.deby.changelog]
+update = true
+package = "foo"
+maintainer = { name = "John Doe", email = "john@example.com" }
+"#;
+
+        let actual = Config::cargo_metadata(fake_manifest).unwrap().unwrap();
+        let actual: Config = serde_json::from_value(actual).unwrap();
+
+        assert_eq!(actual.changelog.package(), "foo");
+        assert!(Config::cargo_metadata("[package]\nname = \"foo\"\n")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let fake_toml = r#"
+[changelog]
+update = true
+package = "foo"
+distribution = "experimental"
+urgency = "high"
+
+[changelog.maintainer]
+name = "John Doe"
+email = "john@example.com"
+
+[control]
+update = true
+
+[control.sourceControl]
+source = "foo"
+priority = "optional"
+buildDepends = ["debhelper"]
+
+[control.sourceControl.maintainer]
+name = "John Doe"
+email = "john@example.com"
+
+[[control.binaryControl]]
+package = "foo"
+priority = "optional"
+architecture = "any"
+"#;
+
+        let actual: Config =
+            serde_json::from_value(Config::parse_toml(fake_toml).unwrap()).unwrap();
+
+        assert_eq!(actual.changelog.package(), "foo");
+        assert_eq!(actual.control.source_name(), "foo");
+        assert_eq!(actual.control.binary_names(), vec!["foo"]);
+    }
+}
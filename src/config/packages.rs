@@ -0,0 +1,110 @@
+use serde_json::Value;
+
+use std::path::{Component, Path};
+
+use super::{fragments, ConfigParseError};
+use crate::DebyError;
+
+const PACKAGES: &str = "packages";
+const DIR: &str = "dir";
+
+/// Removes `packages` array from config contents and returns its items
+///
+/// # Arguments
+///
+/// - `config` - config contents
+pub(crate) fn take(config: &mut Value) -> Vec<Value> {
+    match config.as_object_mut().and_then(|c| c.remove(PACKAGES)) {
+        Some(Value::Array(packages)) => packages,
+        Some(package) => vec![package],
+        None => vec![],
+    }
+}
+
+/// Builds config contents of a single package: settings of the package
+/// are merged into shared settings. Returns output directory of the package and its config contents
+///
+/// # Arguments
+///
+/// - `shared` - config contents without `packages` array
+/// - `index` - index of the package in `packages` array
+/// - `package` - settings of the package
+pub(crate) fn split(
+    shared: &Value,
+    index: usize,
+    mut package: Value,
+) -> Result<(String, Value), DebyError> {
+    let dir = package
+        .as_object_mut()
+        .and_then(|p| p.remove(DIR))
+        .and_then(|d| d.as_str().map(|d| d.to_string()))
+        .filter(|d| !d.trim().is_empty())
+        .ok_or_else(|| {
+            DebyError::ConfigParse(ConfigParseError::new(
+                &format!("{}[{}].{}", PACKAGES, index, DIR),
+                "output directory of a package is required",
+            ))
+        })?;
+    let path = Path::new(&dir);
+    if path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return Err(DebyError::ConfigParse(ConfigParseError::new(
+            &format!("{}[{}].{}", PACKAGES, index, DIR),
+            "output directory must be inside config directory, absolute paths and `..` are not allowed",
+        )));
+    }
+
+    let mut config = shared.clone();
+    fragments::merge(&mut config, package);
+
+    Ok((dir, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_take_and_split() {
+        let mut fake_config = json!({
+            "changelog": {"update": true, "urgency": "medium"},
+            "packages": [
+                {"dir": "foo", "changelog": {"package": "foo"}},
+                {"dir": "bar", "changelog": {"package": "bar", "urgency": "high"}}
+            ]
+        });
+
+        let packages = take(&mut fake_config);
+        let actual: Vec<(String, Value)> = packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| split(&fake_config, i, p).unwrap())
+            .collect();
+
+        assert_eq!(actual[0].0, "foo");
+        assert_eq!(
+            actual[0].1,
+            json!({"changelog": {"update": true, "urgency": "medium", "package": "foo"}})
+        );
+        assert_eq!(actual[1].0, "bar");
+        assert_eq!(actual[1].1["changelog"]["urgency"], "high");
+    }
+
+    #[test]
+    fn test_split_missing_dir() {
+        let actual = split(&json!({}), 1, json!({"changelog": {}}));
+
+        assert!(matches!(actual, Err(DebyError::ConfigParse(e)) if e.path == "packages[1].dir"));
+    }
+
+    #[test]
+    fn test_split_outside_dir() {
+        for fake_dir in ["/tmp/foo", "../foo", "foo/../../bar"] {
+            let actual = split(&json!({}), 0, json!({ "dir": fake_dir }));
+
+            assert!(
+                matches!(actual, Err(DebyError::ConfigParse(e)) if e.path == "packages[0].dir")
+            );
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use serde_json::Value;
+
+use super::fragments;
+use crate::DebyError;
+
+const PROFILES: &str = "profiles";
+
+/// Removes `profiles` map from config contents and returns it
+///
+/// # Arguments
+///
+/// - `config` - config contents
+pub(crate) fn take(config: &mut Value) -> Option<Value> {
+    config.as_object_mut().and_then(|c| c.remove(PROFILES))
+}
+
+/// Merges field overrides of a profile into config contents,
+/// objects are merged key by key, any other value replaces the configured one
+///
+/// # Arguments
+///
+/// - `config` - config contents
+/// - `profile` - profile name, e.g. `bookworm`
+pub(crate) fn apply(config: &mut Value, profile: &str) -> Result<(), DebyError> {
+    let overrides = take(config)
+        .and_then(|mut profiles| profiles.get_mut(profile).map(Value::take))
+        .ok_or_else(|| DebyError::UnknownProfile(profile.to_string()))?;
+
+    fragments::merge(config, overrides);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply() {
+        let mut fake_config = json!({
+            "changelog": {"package": "foo", "distribution": "unstable"},
+            "profiles": {"experimental": {"changelog": {"distribution": "experimental"}}}
+        });
+
+        apply(&mut fake_config, "experimental").unwrap();
+
+        assert_eq!(
+            fake_config,
+            json!({"changelog": {"package": "foo", "distribution": "experimental"}})
+        );
+    }
+
+    #[test]
+    fn test_apply_unknown_profile() {
+        let mut fake_config = json!({"profiles": {"focal": {}}});
+
+        let actual = apply(&mut fake_config, "bookworm");
+
+        assert!(matches!(actual, Err(DebyError::UnknownProfile(p)) if p == "bookworm"));
+    }
+}
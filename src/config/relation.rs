@@ -0,0 +1,464 @@
+use std::fmt::Display;
+
+use crate::DebyError;
+
+const OPERATORS: [&str; 5] = ["<<", "<=", ">=", ">>", "="];
+
+/// A syntax error in a relationship field
+#[derive(Debug, PartialEq)]
+pub(crate) struct SyntaxError {
+    /// 1-based column of the problem
+    pub(crate) column: usize,
+    pub(crate) message: String,
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+/// A single package relation, e.g. `libfoo (>= 1.2) [amd64] <!nocheck>`
+#[derive(Debug, PartialEq)]
+pub(crate) struct Relation {
+    pub(crate) name: String,
+    /// Architecture qualifier, e.g. `any` of `python3:any`
+    pub(crate) qualifier: Option<String>,
+    pub(crate) constraint: Option<(String, String)>,
+    /// Architecture restriction, e.g. `amd64` or `!i386`
+    pub(crate) architectures: Vec<String>,
+    /// Build profile restrictions, one list of terms per `<...>` group
+    pub(crate) profiles: Vec<Vec<String>>,
+}
+
+/// Reads relation strings keeping track of the current position
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self { s, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.s[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn is_end(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Consumes `c` if it's the next char
+    ///
+    /// # Arguments
+    ///
+    /// - `c` - expected char
+    fn eat(&mut self, c: char) -> bool {
+        match self.peek() == Some(c) {
+            true => {
+                self.pos += c.len_utf8();
+                true
+            }
+            false => false,
+        }
+    }
+
+    /// Consumes chars while they match a predicate
+    ///
+    /// # Arguments
+    ///
+    /// - `f` - predicate
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !f(c))
+            .unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.s[start..self.pos]
+    }
+
+    /// Creates an error at a position
+    ///
+    /// # Arguments
+    ///
+    /// - `pos` - byte position of the problem
+    /// - `message` - description of the problem
+    fn error_at(&self, pos: usize, message: &str) -> SyntaxError {
+        SyntaxError {
+            column: self.s[..pos].chars().count() + 1,
+            message: message.to_string(),
+        }
+    }
+
+    /// Creates an error at the current position, the next char is described when it's present
+    ///
+    /// # Arguments
+    ///
+    /// - `expected` - description of what was expected
+    fn expected(&self, expected: &str) -> SyntaxError {
+        let message = match self.peek() {
+            Some(c) => format!("expected {}, found `{}`", expected, c),
+            None => format!("expected {}, found end of value", expected),
+        };
+        self.error_at(self.pos, &message)
+    }
+
+    /// Reads a space separated list of optionally negated words, e.g. `amd64 !i386`,
+    /// up to a closing char
+    ///
+    /// # Arguments
+    ///
+    /// - `close` - closing char, e.g. `]`
+    /// - `what` - description of a single word
+    fn restriction(&mut self, close: char, what: &str) -> Result<Vec<String>, SyntaxError> {
+        let mut words = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.eat(close) {
+                break;
+            }
+            let start = self.pos;
+            let negated = self.eat('!');
+            let word = self.take_while(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.'
+            });
+            if word.is_empty() {
+                return Err(match self.is_end() && !negated {
+                    true => self.expected(&format!("`{}`", close)),
+                    false => self.expected(what),
+                });
+            }
+            words.push(self.s[start..self.pos].to_string());
+        }
+        if words.is_empty() {
+            return Err(self.error_at(self.pos - 1, &format!("expected {}", what)));
+        }
+
+        Ok(words)
+    }
+
+    /// Reads a substitution variable, e.g. `${shlibs:Depends}`, the leading `$` is consumed
+    fn substvar(&mut self) -> Result<Relation, SyntaxError> {
+        let start = self.pos - 1;
+        if !self.eat('{') {
+            return Err(self.expected("`{`"));
+        }
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == ':' || c == '-');
+        if !name.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            return Err(self.expected("substitution variable name"));
+        }
+        if !self.eat('}') {
+            return Err(self.expected("`}`"));
+        }
+
+        Ok(Relation {
+            name: self.s[start..self.pos].to_string(),
+            qualifier: None,
+            constraint: None,
+            architectures: vec![],
+            profiles: vec![],
+        })
+    }
+
+    /// Reads a single relation or a substitution variable, surrounding whitespace is consumed
+    fn relation(&mut self) -> Result<Relation, SyntaxError> {
+        self.skip_whitespace();
+        if self.eat('$') {
+            let substvar = self.substvar()?;
+            self.skip_whitespace();
+            return Ok(substvar);
+        }
+        let start = self.pos;
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+        if name.is_empty() {
+            return Err(self.expected("package name"));
+        }
+        if !Relation::is_valid_name(name) {
+            return Err(self.error_at(start, &format!("invalid package name `{}`", name)));
+        }
+
+        let qualifier = match self.eat(':') {
+            true => {
+                let q = self.take_while(|c| c.is_ascii_alphanumeric() || c == '-');
+                if q.is_empty() {
+                    return Err(self.expected("architecture qualifier"));
+                }
+                Some(q.to_string())
+            }
+            false => None,
+        };
+
+        self.skip_whitespace();
+        let parenthesized = self.eat('(');
+        if parenthesized {
+            self.skip_whitespace();
+        }
+        let op = OPERATORS.iter().find(|op| self.rest().starts_with(*op));
+        let constraint = match op {
+            Some(op) => {
+                self.pos += op.len();
+                self.skip_whitespace();
+                let version = self.take_while(|c| !c.is_whitespace() && !"()[]<>,|".contains(c));
+                if version.is_empty() {
+                    return Err(self.expected("version"));
+                }
+                Some((op.to_string(), version.to_string()))
+            }
+            None if parenthesized => return Err(self.expected("version operator")),
+            None => None,
+        };
+        if parenthesized {
+            self.skip_whitespace();
+            if !self.eat(')') {
+                return Err(self.expected("`)`"));
+            }
+        }
+
+        self.skip_whitespace();
+        let architectures = match self.eat('[') {
+            true => {
+                let open = self.pos - 1;
+                let archs = self.restriction(']', "architecture")?;
+                let negated = archs.iter().filter(|a| a.starts_with('!')).count();
+                if negated != 0 && negated != archs.len() {
+                    return Err(self.error_at(open, "negated and plain architectures are mixed"));
+                }
+                archs
+            }
+            false => vec![],
+        };
+
+        let mut profiles = vec![];
+        loop {
+            self.skip_whitespace();
+            if !self.eat('<') {
+                break;
+            }
+            profiles.push(self.restriction('>', "build profile")?);
+        }
+
+        Ok(Relation {
+            name: name.to_string(),
+            qualifier,
+            constraint,
+            architectures,
+            profiles,
+        })
+    }
+}
+
+impl Relation {
+    /// Parses a relation string, both `libfoo (>= 1.2)` and `libfoo >= 1.2` forms are accepted,
+    /// optionally followed by architecture and build profile restrictions
+    ///
+    /// # Arguments
+    ///
+    /// - `relation` - relation string value
+    pub(crate) fn parse(relation: &str) -> Result<Self, DebyError> {
+        let mut parser = Parser::new(relation);
+        match parser.relation() {
+            Ok(r) if parser.is_end() => Ok(r),
+            _ => Err(DebyError::InvalidDependency(relation.to_string())),
+        }
+    }
+
+    /// Checks syntax of a whole relationship field value: comma separated relations,
+    /// alternatives are separated by `|`, a trailing comma is allowed
+    ///
+    /// # Arguments
+    ///
+    /// - `value` - relationship field value, e.g. `libc6 (>= 2.31), foo | bar [amd64]`
+    pub(crate) fn check(value: &str) -> Result<(), SyntaxError> {
+        let mut parser = Parser::new(value);
+        parser.skip_whitespace();
+        while !parser.is_end() {
+            parser.relation()?;
+            if parser.eat('|') {
+                continue;
+            }
+            if parser.eat(',') {
+                parser.skip_whitespace();
+                continue;
+            }
+            if !parser.is_end() {
+                return Err(parser.expected("`,` or `|`"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if relation applies to some architectures or build profiles only
+    pub(crate) fn is_restricted(&self) -> bool {
+        !self.architectures.is_empty() || !self.profiles.is_empty()
+    }
+
+    /// Checks package name according to Debian policy:
+    /// lower case letters, digits and `+`, `-`, `.` signs, starting with an alphanumeric character
+    ///
+    /// # Arguments
+    ///
+    /// - `name` - package name
+    fn is_valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(c) if c.is_ascii_lowercase() || c.is_ascii_digit() => {}
+            _ => return false,
+        }
+        name.len() >= 2
+            && chars.all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || c == '+' || c == '-' || c == '.'
+            })
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(q) = &self.qualifier {
+            write!(f, ":{}", q)?;
+        }
+        if let Some((op, version)) = &self.constraint {
+            write!(f, " ({} {})", op, version)?;
+        }
+        if !self.architectures.is_empty() {
+            write!(f, " [{}]", self.architectures.join(" "))?;
+        }
+        for terms in &self.profiles {
+            write!(f, " <{}>", terms.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name() {
+        let actual = Relation::parse(" libfoo ").unwrap();
+
+        assert_eq!(actual.name, "libfoo");
+        assert_eq!(actual.constraint, None);
+        assert_eq!(actual.to_string(), "libfoo");
+    }
+
+    #[test]
+    fn test_parse_constraint() {
+        let actual = Relation::parse("libfoo (>= 1.2)").unwrap();
+        let bare = Relation::parse("libfoo>=1.2").unwrap();
+
+        assert_eq!(actual, bare);
+        assert_eq!(actual.to_string(), "libfoo (>= 1.2)");
+    }
+
+    #[test]
+    fn test_parse_restrictions() {
+        let actual = Relation::parse(
+            "python3:any (>= 3.9) [!hurd-i386 !kfreebsd-amd64] <!nocheck> <stage1 !cross>",
+        )
+        .unwrap();
+
+        assert_eq!(actual.name, "python3");
+        assert_eq!(actual.qualifier, Some("any".to_string()));
+        assert_eq!(actual.architectures, vec!["!hurd-i386", "!kfreebsd-amd64"]);
+        assert_eq!(
+            actual.profiles,
+            vec![vec!["!nocheck"], vec!["stage1", "!cross"]]
+        );
+        assert!(actual.is_restricted());
+        assert_eq!(
+            actual.to_string(),
+            "python3:any (>= 3.9) [!hurd-i386 !kfreebsd-amd64] <!nocheck> <stage1 !cross>"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(Relation::check("").is_ok());
+        assert!(Relation::check("libc6 (>= 2.31), foo | bar [amd64] <!nocheck>,").is_ok());
+        assert!(Relation::check("libc6(>=2.31),foo|bar").is_ok());
+        assert!(Relation::check("${shlibs:Depends}, ${misc:Depends}, foo").is_ok());
+    }
+
+    #[test]
+    fn test_check_errors() {
+        let cases = [
+            (
+                "libfoo (>= 1.2",
+                "expected `)`, found end of value at column 15",
+            ),
+            (
+                "libfoo (~ 1.2)",
+                "expected version operator, found `~` at column 9",
+            ),
+            (
+                "libfoo, , bar",
+                "expected package name, found `,` at column 9",
+            ),
+            ("libfoo bar", "expected `,` or `|`, found `b` at column 8"),
+            (
+                "libfoo [amd64 !i386]",
+                "negated and plain architectures are mixed at column 8",
+            ),
+            (
+                "libfoo [amd64",
+                "expected `]`, found end of value at column 14",
+            ),
+            (
+                "libfoo <!>",
+                "expected build profile, found `>` at column 10",
+            ),
+            ("libfoo | Bar", "invalid package name `Bar` at column 10"),
+            (
+                "${misc:Depends",
+                "expected `}`, found end of value at column 15",
+            ),
+            ("$misc:Depends", "expected `{`, found `m` at column 2"),
+            (
+                "${}",
+                "expected substitution variable name, found `}` at column 3",
+            ),
+            (
+                "${misc:Depends} (>= 1)",
+                "expected `,` or `|`, found `(` at column 17",
+            ),
+        ];
+
+        for (value, expected) in cases {
+            let actual = Relation::check(value).unwrap_err();
+
+            assert_eq!(actual.to_string(), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_parse_substvar() {
+        let actual = Relation::parse(" ${shlibs:Depends} ").unwrap();
+
+        assert_eq!(actual.name, "${shlibs:Depends}");
+        assert_eq!(actual.to_string(), "${shlibs:Depends}");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Relation::parse("").is_err());
+        assert!(Relation::parse("LibFoo").is_err());
+        assert!(Relation::parse("libfoo (>= 1.2").is_err());
+        assert!(Relation::parse("libfoo (~ 1.2)").is_err());
+        assert!(Relation::parse("libfoo (>=)").is_err());
+        assert!(Relation::parse("libfoo, bar").is_err());
+    }
+}
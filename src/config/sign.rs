@@ -0,0 +1,127 @@
+use serde::Deserialize;
+
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+
+use super::Config;
+use crate::DebyError;
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Sign {
+    enabled: bool,
+    #[serde(default = "Sign::default_key")]
+    key: String,
+    #[serde(default = "Sign::default_program")]
+    program: String,
+}
+
+impl Sign {
+    /// Formats arguments of a signing program to produce an armored detached signature
+    ///
+    /// # Arguments
+    ///
+    /// - `path` - path to a file to be signed
+    fn args(&self, path: &str) -> Vec<String> {
+        let mut args = vec![
+            "--batch".to_string(),
+            "--yes".to_string(),
+            "--armor".to_string(),
+            "--detach-sign".to_string(),
+        ];
+        if !self.key.is_empty() {
+            args.push("--local-user".to_string());
+            args.push(self.key.clone());
+        }
+        args.push("--output".to_string());
+        args.push(format!("{}.asc", path));
+        args.push(path.to_string());
+
+        args
+    }
+
+    /// Creates a detached signature `<path>.asc` for a generated file if signing is enabled
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - data from config file `.debyrc`
+    /// - `path` - path to a generated file
+    pub(crate) fn sign_file(config: &Config, path: &Path) -> Result<(), Box<dyn Error>> {
+        let sign = &config.sign;
+        if !sign.enabled {
+            return Ok(());
+        }
+        let path = path.to_string_lossy();
+
+        let output = Command::new(&sign.program)
+            .args(sign.args(&path))
+            .output()
+            .map_err(|e| DebyError::Sign(path.to_string(), e.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(DebyError::Sign(path.to_string(), stderr).into());
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn default() -> Self {
+        Self {
+            enabled: false,
+            key: Sign::default_key(),
+            program: Sign::default_program(),
+        }
+    }
+
+    fn default_key() -> String {
+        "".to_string()
+    }
+
+    fn default_program() -> String {
+        "gpg".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let default = Sign::default();
+
+        assert!(!default.enabled);
+        assert_eq!(default.key, "");
+        assert_eq!(default.program, "gpg");
+    }
+
+    #[test]
+    fn test_args() {
+        let fake_sign = Sign {
+            enabled: true,
+            key: "ABCD1234".to_string(),
+            program: "gpg".to_string(),
+        };
+        let actual = fake_sign.args("debian/control");
+        let expected = vec![
+            "--batch",
+            "--yes",
+            "--armor",
+            "--detach-sign",
+            "--local-user",
+            "ABCD1234",
+            "--output",
+            "debian/control.asc",
+            "debian/control",
+        ];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_args_default_key() {
+        let actual = Sign::default().args("debian/changelog");
+
+        assert!(!actual.contains(&"--local-user".to_string()));
+    }
+}
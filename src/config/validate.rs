@@ -0,0 +1,448 @@
+use chrono::NaiveDate;
+use regex::Regex;
+use serde_json::Value;
+
+use std::fmt::Display;
+
+use super::changelog::Changelog;
+use super::edit::snake_case;
+use crate::version;
+
+/// A single problem found in config file settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// Path to a config key, e.g. `control.sourceControl.standardsVersion`
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(crate) fn new(path: &str, message: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.path.is_empty() {
+            true => write!(f, "{}", self.message),
+            false => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+/// Returns a value stored under a camelCase key or its snake_case alias
+///
+/// # Arguments
+///
+/// - `value` - parent JSON object
+/// - `key` - camelCase key
+fn field<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.get(key).or_else(|| value.get(snake_case(key)))
+}
+
+/// Returns a string stored under a key, missing and non-string values are empty
+///
+/// # Arguments
+///
+/// - `value` - parent JSON object
+/// - `key` - camelCase key
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    field(value, key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// Checks that a person has a name and a valid email
+///
+/// # Arguments
+///
+/// - `person` - object with `name` and `email`
+/// - `path` - path to the object
+/// - `role` - role of the person used in messages, e.g. `maintainer`
+fn check_person(person: &Value, path: &str, role: &str) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+
+    if str_field(person, "name").trim().is_empty() {
+        issues.push(ValidationIssue::new(
+            &format!("{}.name", path),
+            &format!("{} name is empty", role),
+        ));
+    }
+    let email = str_field(person, "email").trim();
+    let re = Regex::new(r"^[^<>\s@]+@[^<>\s@]+$").unwrap();
+    if email.is_empty() {
+        issues.push(ValidationIssue::new(
+            &format!("{}.email", path),
+            &format!("{} email is empty", role),
+        ));
+    } else if !re.is_match(email) {
+        issues.push(ValidationIssue::new(
+            &format!("{}.email", path),
+            &format!("{} email is malformed", role),
+        ));
+    }
+
+    issues
+}
+
+/// Checks that maintainer of a section has a name and a valid email
+///
+/// # Arguments
+///
+/// - `section` - config section with `maintainer` object
+/// - `path` - path to the section
+fn check_maintainer(section: &Value, path: &str) -> Vec<ValidationIssue> {
+    let maintainer = field(section, "maintainer").unwrap_or(&Value::Null);
+
+    check_person(maintainer, &format!("{}.maintainer", path), "maintainer")
+}
+
+/// Checks `changelog` section of config
+///
+/// # Arguments
+///
+/// - `changelog` - `changelog` section
+fn check_changelog(changelog: &Value) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+    let update = field(changelog, "update").and_then(Value::as_bool) == Some(true);
+
+    if update && str_field(changelog, "package").trim().is_empty() {
+        issues.push(ValidationIssue::new(
+            "changelog.package",
+            "package name is required when update is enabled",
+        ));
+    }
+    issues.extend(check_maintainer(changelog, "changelog"));
+
+    let distribution: Vec<&str> = match field(changelog, "distribution") {
+        Some(Value::Array(d)) => d.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(d)) => d.split_whitespace().collect(),
+        _ if str_field(changelog, "vendor") == "ubuntu" => vec![version::latest_ubuntu_series()],
+        _ => vec!["unstable"],
+    };
+    let allowed = field(changelog, "allowedDistributions").and_then(Value::as_array);
+    if let Some(allowed) = allowed.filter(|a| !a.is_empty()) {
+        let is_allowed = distribution
+            .iter()
+            .all(|d| allowed.iter().any(|a| a.as_str() == Some(d)));
+        if !is_allowed {
+            issues.push(ValidationIssue::new(
+                "changelog.distribution",
+                &format!(
+                    "distribution {} is not in allowedDistributions",
+                    distribution.join(" ")
+                ),
+            ));
+        }
+    }
+
+    for key in ["closesPattern", "launchpadPattern"] {
+        let pattern = str_field(changelog, key);
+        if !pattern.is_empty() && Regex::new(pattern).is_err() {
+            issues.push(ValidationIssue::new(
+                &format!("changelog.{}", key),
+                &format!("invalid pattern {}", pattern),
+            ));
+        }
+    }
+
+    let ticket = field(changelog, "ticketPattern").unwrap_or(&Value::Null);
+    let ticket_pattern = str_field(ticket, "pattern");
+    if !ticket.is_null() && Regex::new(ticket_pattern).is_err() {
+        issues.push(ValidationIssue::new(
+            "changelog.ticketPattern.pattern",
+            &format!("invalid pattern {}", ticket_pattern),
+        ));
+    }
+
+    // the same parser as on update, so a reported timezone is the one an update rejects
+    let timezone = field(changelog, "timezone").and_then(Value::as_str);
+    if let Some(timezone) = timezone.filter(|tz| Changelog::parse_timezone(tz).is_err()) {
+        issues.push(ValidationIssue::new(
+            "changelog.timezone",
+            &format!("unsupported timezone {}", timezone),
+        ));
+    }
+
+    let rotate = field(changelog, "rotate").unwrap_or(&Value::Null);
+    if field(rotate, "keepEntries").and_then(Value::as_u64) == Some(0) {
+        issues.push(ValidationIssue::new(
+            "changelog.rotate.keepEntries",
+            "at least one entry must be kept",
+        ));
+    }
+    let before = str_field(rotate, "before");
+    if !before.is_empty() && NaiveDate::parse_from_str(before, "%Y-%m-%d").is_err() {
+        issues.push(ValidationIssue::new(
+            "changelog.rotate.before",
+            &format!("malformed date {}, expected YYYY-MM-DD", before),
+        ));
+    }
+
+    let rules = field(changelog, "urgencyRules").and_then(Value::as_array);
+    for (i, rule) in rules.into_iter().flatten().enumerate() {
+        let pattern = str_field(rule, "pattern");
+        if Regex::new(pattern).is_err() {
+            issues.push(ValidationIssue::new(
+                &format!("changelog.urgencyRules[{}].pattern", i),
+                &format!("invalid pattern {}", pattern),
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Checks `control` section of config
+///
+/// # Arguments
+///
+/// - `control` - `control` section
+fn check_control(control: &Value) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+    let update = field(control, "update").and_then(Value::as_bool) == Some(true);
+
+    let source = field(control, "sourceControl").unwrap_or(&Value::Null);
+    if update && str_field(source, "source").trim().is_empty() {
+        issues.push(ValidationIssue::new(
+            "control.sourceControl.source",
+            "source package name is required when update is enabled",
+        ));
+    }
+    issues.extend(check_maintainer(source, "control.sourceControl"));
+    let uploaders = field(source, "uploaders").and_then(Value::as_array);
+    for (i, uploader) in uploaders.into_iter().flatten().enumerate() {
+        let path = format!("control.sourceControl.uploaders[{}]", i);
+        issues.extend(check_person(uploader, &path, "uploader"));
+    }
+
+    let standards_version = str_field(source, "standardsVersion");
+    let re = Regex::new(r"^\d+\.\d+\.\d+(\.\d+)?$").unwrap();
+    if !standards_version.is_empty() && !re.is_match(standards_version) {
+        issues.push(ValidationIssue::new(
+            "control.sourceControl.standardsVersion",
+            &format!("malformed standards version {}", standards_version),
+        ));
+    }
+
+    let vcs_git = str_field(source, "vcsGit");
+    let re = Regex::new(r"^\S+( -b \S+)?( \[\S+\])?$").unwrap();
+    if !vcs_git.is_empty() && !re.is_match(vcs_git) {
+        issues.push(ValidationIssue::new(
+            "control.sourceControl.vcsGit",
+            &format!(
+                "malformed Vcs-Git {}, expected `url [-b branch] [[path]]`",
+                vcs_git
+            ),
+        ));
+    }
+
+    let binaries: Vec<(String, &Value)> = match field(control, "binaryControl") {
+        Some(Value::Array(v)) => v
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (format!("control.binaryControl[{}]", i), b))
+            .collect(),
+        Some(b) => vec![("control.binaryControl".to_string(), b)],
+        None => vec![],
+    };
+    issues.extend(check_custom_fields(source, "control.sourceControl"));
+    for (path, binary) in binaries {
+        if update && str_field(binary, "package").trim().is_empty() {
+            issues.push(ValidationIssue::new(
+                &format!("{}.package", path),
+                "binary package name is required when update is enabled",
+            ));
+        }
+        issues.extend(check_custom_fields(binary, &path));
+    }
+
+    issues
+}
+
+/// Checks `customFields` or `extraFields` of a source or binary section: names must be valid
+/// control field names and values must be strings
+///
+/// # Arguments
+///
+/// - `section` - source or binary section
+/// - `path` - path to the section
+fn check_custom_fields(section: &Value, path: &str) -> Vec<ValidationIssue> {
+    let re = Regex::new(r"^[!-9;-~&&[^#-]][!-9;-~]*$").unwrap();
+    let mut issues = vec![];
+    for key in ["customFields", "extraFields"] {
+        let fields = field(section, key).and_then(Value::as_object);
+        for (name, value) in fields.into_iter().flatten() {
+            let path = format!("{}.{}.{}", path, key, name);
+            if !re.is_match(name) {
+                issues.push(ValidationIssue::new(&path, "malformed control field name"));
+            }
+            if !value.is_string() {
+                issues.push(ValidationIssue::new(&path, "field value must be a string"));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Checks `tests` section of config, every stanza runs either test scripts or a command
+///
+/// # Arguments
+///
+/// - `tests` - `tests` section
+fn check_tests(tests: &Value) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+    for (i, test) in tests.as_array().into_iter().flatten().enumerate() {
+        let has_tests = field(test, "tests")
+            .and_then(Value::as_array)
+            .is_some_and(|t| !t.is_empty());
+        let has_command = !str_field(test, "testCommand").trim().is_empty();
+        if has_tests == has_command {
+            issues.push(ValidationIssue::new(
+                &format!("tests[{}]", i),
+                "exactly one of tests and testCommand is required",
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Checks settings of config contents, all problems found are returned at once
+///
+/// # Arguments
+///
+/// - `value` - contents of config file
+pub(crate) fn check(value: &Value) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+    if let Some(changelog) = value.get("changelog") {
+        issues.extend(check_changelog(changelog));
+    }
+    if let Some(control) = value.get("control") {
+        issues.extend(check_control(control));
+    }
+    if let Some(tests) = value.get("tests") {
+        issues.extend(check_tests(tests));
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_valid() {
+        let fake_config = json!({
+            "changelog": {"update": true, "package": "foo", "maintainer": {"name": "John", "email": "john@example.com"}},
+            "control": {
+                "update": true,
+                "sourceControl": {"source": "foo", "standardsVersion": "4.5.1", "vcsGit": "https://salsa.debian.org/foo.git -b debian/latest [sub/dir]", "maintainer": {"name": "John", "email": "john@example.com"}},
+                "binaryControl": {"package": "foo", "customFields": {"XB-Build-Id": "${BUILD_ID}"}}
+            }
+        });
+
+        assert!(check(&fake_config).is_empty());
+    }
+
+    #[test]
+    fn test_check_reports_all_issues() {
+        let fake_config = json!({
+            "changelog": {"update": true, "maintainer": {"name": "John", "email": ""}, "urgencyRules": [{"pattern": "("}], "allowedDistributions": ["focal"], "timezone": "Mars/Olympus", "rotate": {"keepEntries": 0}},
+            "control": {
+                "update": true,
+                "source_control": {"source": "foo", "standards_version": "4.5", "vcs_git": "https://salsa.debian.org/foo.git --branch main", "maintainer": {"name": "John", "email": "john"}, "uploaders": [{"name": "", "email": "jane@example.com"}]},
+                "binaryControl": [{"package": "foo", "custom_fields": {"Bad Name": "a"}, "extraFields": {"X-Count": 1}}, {"package": ""}]
+            },
+            "tests": [{"tests": ["smoke"]}, {"depends": "@"}]
+        });
+
+        let actual: Vec<String> = check(&fake_config).iter().map(|i| i.path.clone()).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                "changelog.package",
+                "changelog.maintainer.email",
+                "changelog.distribution",
+                "changelog.timezone",
+                "changelog.rotate.keepEntries",
+                "changelog.urgencyRules[0].pattern",
+                "control.sourceControl.maintainer.email",
+                "control.sourceControl.uploaders[0].name",
+                "control.sourceControl.standardsVersion",
+                "control.sourceControl.vcsGit",
+                "control.binaryControl[0].customFields.Bad Name",
+                "control.binaryControl[0].extraFields.X-Count",
+                "control.binaryControl[1].package",
+                "tests[1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_changelog_timezone() {
+        for timezone in ["local", "UTC", "utc", "+03:00", "-0530", "Europe/Berlin"] {
+            let fake_changelog = json!({"timezone": timezone});
+            let actual = check_changelog(&fake_changelog);
+
+            assert!(
+                actual.iter().all(|i| i.path != "changelog.timezone"),
+                "{}",
+                timezone
+            );
+        }
+
+        for timezone in ["Local", "+99:99", "", "Mars/Olympus"] {
+            let fake_changelog = json!({"timezone": timezone});
+            let actual = check_changelog(&fake_changelog);
+
+            assert!(
+                actual.iter().any(|i| i.path == "changelog.timezone"),
+                "{}",
+                timezone
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let fake_issue = ValidationIssue::new("changelog.package", "package name is required");
+
+        assert_eq!(
+            fake_issue.to_string(),
+            "changelog.package: package name is required"
+        );
+    }
+
+    #[test]
+    fn test_check_person_email() {
+        for email in ["john@example.com", "john+deb@localhost"] {
+            let fake_person = json!({"name": "John", "email": email});
+
+            assert!(check_person(&fake_person, "maintainer", "maintainer").is_empty());
+        }
+
+        for email in [
+            "john",
+            "@example.com",
+            "john@",
+            "john doe@example.com",
+            "<john@example.com>",
+            "a@b@c",
+        ] {
+            let fake_person = json!({"name": "John", "email": email});
+            let actual = check_person(&fake_person, "maintainer", "maintainer");
+
+            assert_eq!(actual.len(), 1, "{}", email);
+            assert_eq!(actual[0].path, "maintainer.email");
+        }
+    }
+}
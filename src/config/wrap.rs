@@ -0,0 +1,152 @@
+use crate::deb822::Paragraph;
+
+/// Returns sort key of a field, known fields are ranked by the canonical order,
+/// unknown fields go right before `Description` or to the end of a paragraph without it
+///
+/// # Arguments
+///
+/// - `order` - canonical order of field names
+/// - `name` - field name
+fn rank(order: &[&str], name: &str) -> usize {
+    let position = |n: &str| order.iter().position(|o| o.eq_ignore_ascii_case(n));
+
+    match position(name) {
+        Some(p) => p * 2 + 1,
+        None => position("Description").unwrap_or(order.len()) * 2,
+    }
+}
+
+/// Sorts relations like `wrap-and-sort`: package names go first,
+/// substitution variables and other special values go last
+///
+/// # Arguments
+///
+/// - `value` - comma separated relations
+fn sort_relations(value: &str) -> Vec<&str> {
+    let mut relations: Vec<&str> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .collect();
+    relations.sort_by_key(|r| {
+        (
+            !r.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit()),
+            *r,
+        )
+    });
+    relations.dedup();
+
+    relations
+}
+
+/// Fills words of text into lines of at most `width` characters,
+/// a word longer than `width` takes a line of its own
+///
+/// # Arguments
+///
+/// - `text` - text to be wrapped, whitespace between words is collapsed
+/// - `width` - max width of a line
+pub(crate) fn fill(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(current);
+            current = String::new();
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Orders fields of a paragraph canonically and writes list fields sorted,
+/// one item per line with a trailing comma, the same way `wrap-and-sort -ast` does
+///
+/// # Arguments
+///
+/// - `paragraph` - a single paragraph of _control_ file
+/// - `order` - canonical order of field names
+/// - `lists` - names of comma separated list fields
+pub(crate) fn wrap_and_sort(mut paragraph: Paragraph, order: &[&str], lists: &[&str]) -> Paragraph {
+    paragraph.fields.sort_by_key(|f| rank(order, &f.name));
+
+    for field in paragraph.fields.iter_mut() {
+        if lists.iter().any(|l| l.eq_ignore_ascii_case(&field.name)) {
+            field.value = sort_relations(&field.value.replace('\n', " "))
+                .iter()
+                .map(|r| format!("\n{},", r))
+                .collect();
+        }
+    }
+
+    paragraph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deb822;
+
+    const FAKE_ORDER: &[&str] = &["Package", "Architecture", "Depends", "Description"];
+
+    #[test]
+    fn test_wrap_and_sort() {
+        let fake_paragraph = deb822::parse(
+            "Package: foo
+Depends: ${misc:Depends}, zlib1g,
+ libc6 (>= 2.31) | libc6.1, zlib1g
+Description: foo tool
+ Long description.
+ .
+ More.
+X-Custom: yes
+Architecture: any
+",
+        )
+        .unwrap()
+        .remove(0);
+
+        let actual = wrap_and_sort(fake_paragraph, FAKE_ORDER, &["Depends"]);
+
+        assert_eq!(
+            actual.to_string(),
+            "Package: foo
+Architecture: any
+Depends:
+ libc6 (>= 2.31) | libc6.1,
+ zlib1g,
+ ${misc:Depends},
+X-Custom: yes
+Description: foo tool
+ Long description.
+ .
+ More.
+"
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill("Ça gère les  fichiers\nécrits", 12),
+            vec!["Ça gère les", "fichiers", "écrits"]
+        );
+        assert_eq!(fill("  ", 12), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_rank_unknown_field() {
+        assert_eq!(rank(FAKE_ORDER, "depends"), 5);
+        assert!(rank(FAKE_ORDER, "X-Custom") < rank(FAKE_ORDER, "Description"));
+        assert!(
+            rank(&["Source", "Homepage"], "X-Custom") > rank(&["Source", "Homepage"], "Homepage")
+        );
+    }
+}
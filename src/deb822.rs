@@ -0,0 +1,317 @@
+use std::fmt::Display;
+
+use crate::DebyError;
+
+/// Blank line of a folded multi-line value
+const BLANK_LINE: &str = ".";
+
+/// A single field of deb822 paragraph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// Field name, e.g. `Package`
+    pub name: String,
+    /// Field value, lines of a multi-line value are separated by `\n`
+    /// and stored without their leading space
+    pub value: String,
+    /// `#` comment lines written before the field, comments found between
+    /// continuation lines of the field are kept here as well
+    pub comments: Vec<String>,
+}
+
+impl Field {
+    pub fn new(name: &str, value: &str) -> Self {
+        Field {
+            name: name.to_string(),
+            value: value.to_string(),
+            comments: vec![],
+        }
+    }
+}
+
+impl Display for Field {
+    /// Folds a field: continuation lines are indented with a single space
+    /// and blank ones are written as ` .`, comments go before the field
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "{}", comment)?;
+        }
+        let mut lines = self.value.lines();
+        match lines.next().map(str::trim) {
+            Some(first) if !first.is_empty() => write!(f, "{}: {}", self.name, first)?,
+            _ => write!(f, "{}:", self.name)?,
+        }
+        for line in lines {
+            match line.trim().is_empty() {
+                true => write!(f, "\n {}", BLANK_LINE)?,
+                false => write!(f, "\n {}", line.trim_end())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A paragraph of deb822 file, fields keep their order
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Paragraph {
+    pub fields: Vec<Field>,
+    /// `#` comment lines after the last field, a comment-only block
+    /// is a paragraph without fields
+    pub comments: Vec<String>,
+}
+
+impl Paragraph {
+    pub fn new() -> Self {
+        Paragraph {
+            fields: vec![],
+            comments: vec![],
+        }
+    }
+
+    /// Returns a value of a field, field names are case-insensitive
+    ///
+    /// ## Arguments
+    ///
+    /// - `name` - field name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+            .map(|f| f.value.as_str())
+    }
+
+    /// Replaces a value of an existing field or appends a new field
+    ///
+    /// ## Arguments
+    ///
+    /// - `name` - field name
+    /// - `value` - field value
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self
+            .fields
+            .iter_mut()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+        {
+            Some(field) => field.value = value.to_string(),
+            None => self.fields.push(Field::new(name, value)),
+        }
+    }
+
+    /// Removes a field, its value is returned and its comments
+    /// are moved to the next field
+    ///
+    /// ## Arguments
+    ///
+    /// - `name` - field name
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self
+            .fields
+            .iter()
+            .position(|f| f.name.eq_ignore_ascii_case(name))?;
+
+        let field = self.fields.remove(index);
+        let next = match self.fields.get_mut(index) {
+            Some(next) => &mut next.comments,
+            None => &mut self.comments,
+        };
+        next.splice(0..0, field.comments);
+
+        Some(field.value)
+    }
+}
+
+impl Display for Paragraph {
+    /// Writes every field on its own line, a paragraph ends with a line break
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for field in &self.fields {
+            writeln!(f, "{}", field)?;
+        }
+        for comment in &self.comments {
+            writeln!(f, "{}", comment)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses deb822 contents, e.g. `debian/control` or `debian/tests/control` file,
+/// paragraphs are separated by blank lines, `#` comment lines are kept with the field
+/// that follows them or at the end of a paragraph
+///
+/// ## Arguments
+///
+/// - `contents` - deb822 contents
+pub fn parse(contents: &str) -> Result<Vec<Paragraph>, DebyError> {
+    let mut paragraphs = vec![];
+    let mut current = Paragraph::new();
+    let mut comments: Vec<String> = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        let malformed = |message: &str| DebyError::Deb822Parse(i + 1, message.to_string());
+
+        if line.starts_with('#') {
+            comments.push(line.to_string());
+            continue;
+        }
+        if line.trim().is_empty() {
+            current.comments.append(&mut comments);
+            if !current.fields.is_empty() || !current.comments.is_empty() {
+                paragraphs.push(current);
+                current = Paragraph::new();
+            }
+            continue;
+        }
+        if line.starts_with([' ', '\t']) {
+            let field = current
+                .fields
+                .last_mut()
+                .ok_or_else(|| malformed("continuation line without a field"))?;
+            field.comments.append(&mut comments);
+            let line = &line[1..];
+            field.value.push('\n');
+            field.value.push_str(match line.trim() == BLANK_LINE {
+                true => "",
+                false => line.trim_end(),
+            });
+            continue;
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| malformed("expected `Name: value` field"))?;
+        if name.trim().is_empty() || name.contains(char::is_whitespace) {
+            return Err(malformed("malformed field name"));
+        }
+        if current.get(name).is_some() {
+            return Err(malformed(&format!("duplicate field {}", name)));
+        }
+        let mut field = Field::new(name, value.trim());
+        field.comments.append(&mut comments);
+        current.fields.push(field);
+    }
+    current.comments.append(&mut comments);
+    if !current.fields.is_empty() || !current.comments.is_empty() {
+        paragraphs.push(current);
+    }
+
+    Ok(paragraphs)
+}
+
+/// Writes paragraphs separated by blank lines
+///
+/// ## Arguments
+///
+/// - `paragraphs` - deb822 paragraphs
+pub fn to_string(paragraphs: &[Paragraph]) -> String {
+    paragraphs
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let fake_contents = "# comment
+Source: foo
+Build-Depends:
+ cargo,
+ debhelper-compat (= 13),
+
+Package: foo
+Description: foo tool
+ Long description.
+ .
+   verbatim line
+";
+
+        let actual = parse(fake_contents).unwrap();
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0].get("source"), Some("foo"));
+        assert_eq!(
+            actual[0].get("Build-Depends"),
+            Some("\ncargo,\ndebhelper-compat (= 13),")
+        );
+        assert_eq!(
+            actual[1].get("Description"),
+            Some("foo tool\nLong description.\n\n  verbatim line")
+        );
+        assert_eq!(to_string(&actual), fake_contents);
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let fake_contents = "# header
+
+Package: foo
+# keep b
+Depends: a,
+# inside
+ b
+# trailing
+";
+
+        let actual = parse(fake_contents).unwrap();
+
+        assert_eq!(actual.len(), 2);
+        assert!(actual[0].fields.is_empty());
+        assert_eq!(actual[0].comments, vec!["# header"]);
+        assert_eq!(actual[1].get("Depends"), Some("a,\nb"));
+        assert_eq!(actual[1].fields[1].comments, vec!["# keep b", "# inside"]);
+        assert_eq!(actual[1].comments, vec!["# trailing"]);
+        assert_eq!(
+            to_string(&actual),
+            "# header
+
+Package: foo
+# keep b
+# inside
+Depends: a,
+ b
+# trailing
+"
+        );
+
+        let mut fake_paragraph = actual[1].clone();
+        fake_paragraph.remove("Depends");
+        assert_eq!(
+            fake_paragraph.comments,
+            vec!["# keep b", "# inside", "# trailing"]
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let actual = parse("Source: foo\nno colon\n");
+        assert!(matches!(actual, Err(DebyError::Deb822Parse(2, _))));
+
+        let actual = parse(" orphan\n");
+        assert!(matches!(actual, Err(DebyError::Deb822Parse(1, _))));
+
+        let actual = parse("Source: foo\nsource: bar\n");
+        assert!(matches!(actual, Err(DebyError::Deb822Parse(2, _))));
+    }
+
+    #[test]
+    fn test_paragraph() {
+        let mut fake_paragraph = Paragraph::new();
+        fake_paragraph.set("Package", "foo");
+        fake_paragraph.set("Depends", "libc6");
+        fake_paragraph.set("depends", "libc6, zlib1g");
+        fake_paragraph.set("Description", "foo tool\nfirst\n\nsecond");
+
+        assert_eq!(
+            fake_paragraph.to_string(),
+            "Package: foo\nDepends: libc6, zlib1g\nDescription: foo tool\n first\n .\n second\n"
+        );
+        assert_eq!(
+            fake_paragraph.remove("DEPENDS"),
+            Some("libc6, zlib1g".to_string())
+        );
+        assert_eq!(fake_paragraph.get("Depends"), None);
+    }
+}
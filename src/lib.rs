@@ -1,7 +1,19 @@
+pub mod changelog;
 mod config;
+pub mod deb822;
+pub mod lint;
+mod template;
+pub mod version;
 
-use config::Config;
+pub use config::graph::{DependencyIssue, DependencyIssueKind};
+pub use config::{
+    ChangelogOptions, Config, ConfigBuilder, ConfigParseError, ControlDrift, ControlOptions,
+    CustomField, DriftKind, Paragraphs, Placement, Upload, Urgency, ValidationIssue,
+};
+
+use std::error::Error;
 use std::fmt;
+use std::path::Path;
 
 #[derive(Debug)]
 pub enum DebyError {
@@ -9,6 +21,55 @@ pub enum DebyError {
     Update,
     ChangelogUpdate,
     ControlUpdate,
+    UnsupportedEncoding(String, String),
+    InvalidUtf8(String, usize),
+    EncodingConvert(String),
+    ControlMissing,
+    KeptParagraphsMissing(String),
+    ChangelogMissing,
+    PartialUpdate(Vec<String>, String, Box<DebyError>),
+    UnknownBinaryPackage(String),
+    BinaryPackageExists(String),
+    ConfigWrite,
+    ConfigNotEditable(String),
+    ConfigInvalid(Vec<ValidationIssue>),
+    UnknownField(String),
+    InvalidDependency(String),
+    InvalidRelationSyntax(String, String, String),
+    InvalidTestStanza(usize),
+    DuplicateField(String),
+    FieldNotAppended(String),
+    Deb822Parse(usize, String),
+    ControlImport(String),
+    DependencyExists(String),
+    ChangelogRead,
+    InvalidPattern(String),
+    VersionNotFound(String),
+    InvalidVersionRange(String, String),
+    ChangelogEmpty,
+    InvalidDate(String),
+    Sign(String, String),
+    EmptyChanges,
+    ChangelogArchive,
+    DependencyGraph(Vec<DependencyIssue>),
+    UnknownConfigKeys(Vec<String>),
+    ConfigParse(ConfigParseError),
+    UnsupportedConfigVersion(String),
+    LegacyConfigKey(String),
+    UnknownProfile(String),
+    ConfigExists,
+    VersionExists(String),
+    VersionNotIncreasing(String, String),
+    InvalidVersion(String, String),
+    UnknownDebianRelease(u32),
+    UnknownUbuntuRelease(String),
+    Git(String),
+    UnknownDistribution(String),
+    InvalidMaintainer(String),
+    MaintainerMissing,
+    InvalidTimezone(String),
+    InvalidLayout(String),
+    ChangelogVerify(String),
 }
 
 impl fmt::Display for DebyError {
@@ -20,10 +81,184 @@ impl fmt::Display for DebyError {
             DebyError::Update => write!(f, "Could not update your files"),
             DebyError::ChangelogUpdate => write!(f, "Could not update debian changelog file"),
             DebyError::ControlUpdate => write!(f, "Could not update debian control file"),
+            DebyError::UnsupportedEncoding(path, encoding) => write!(
+                f,
+                "File {} is encoded as {}, convert it to UTF-8 first",
+                path, encoding
+            ),
+            DebyError::InvalidUtf8(path, offset) => write!(
+                f,
+                "File {} is not valid UTF-8 at byte {}, convert it with convert_latin1 if it's Latin-1 encoded",
+                path, offset
+            ),
+            DebyError::EncodingConvert(path) => write!(f, "Could not convert {} to UTF-8", path),
+            DebyError::ControlMissing => write!(
+                f,
+                "Could not find debian control file, create it with update_control_file first"
+            ),
+            DebyError::KeptParagraphsMissing(kept) => write!(
+                f,
+                "debian control file has no {} paragraph to keep, update the whole file first",
+                kept
+            ),
+            DebyError::ChangelogMissing => write!(f, "Could not find debian changelog file"),
+            DebyError::PartialUpdate(updated, failed, e) => write!(
+                f,
+                "Updated packages in {}, but package in {} failed, later packages were not updated: {}",
+                updated.join(", "),
+                failed,
+                e
+            ),
+            DebyError::UnknownBinaryPackage(name) => {
+                write!(f, "Binary package {} is not defined in .debyrc", name)
+            }
+            DebyError::ConfigWrite => write!(f, "Could not update config file .debyrc"),
+            DebyError::UnknownField(field) => write!(f, "Unknown field {}", field),
+            DebyError::InvalidDependency(dep) => write!(f, "Invalid dependency {}", dep),
+            DebyError::InvalidTestStanza(index) => write!(
+                f,
+                "Test stanza tests[{}] needs exactly one of tests and testCommand",
+                index
+            ),
+            DebyError::DuplicateField(field) => write!(
+                f,
+                "Field {} is already written to debian control file",
+                field
+            ),
+            DebyError::Deb822Parse(line, message) => {
+                write!(f, "Malformed deb822 contents at line {}: {}", line, message)
+            }
+            DebyError::ControlImport(message) => {
+                write!(f, "Could not import debian control file: {}", message)
+            }
+            DebyError::FieldNotAppended(field) => write!(
+                f,
+                "Field {} doesn't belong to appended binary package paragraph",
+                field
+            ),
+            DebyError::InvalidRelationSyntax(field, value, error) => {
+                write!(f, "Invalid {} value `{}`: {}", field, value, error)
+            }
+            DebyError::ChangelogRead => write!(f, "Could not read debian changelog file"),
+            DebyError::InvalidPattern(pattern) => write!(f, "Invalid pattern {}", pattern),
+            DebyError::ChangelogEmpty => write!(f, "Debian changelog file has no entries"),
+            DebyError::InvalidDate(date) => write!(f, "Invalid RFC 2822 date {}", date),
+            DebyError::ChangelogArchive => write!(f, "Could not archive debian changelog entries"),
+            DebyError::ConfigInvalid(issues) => {
+                let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+                write!(f, "Invalid config: {}", issues.join("; "))
+            }
+            DebyError::DependencyGraph(issues) => {
+                let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+                write!(f, "Invalid dependencies: {}", issues.join("; "))
+            }
+            DebyError::ChangelogVerify(reason) => {
+                write!(
+                    f,
+                    "dpkg-parsechangelog rejected debian/changelog: {}",
+                    reason
+                )
+            }
+            DebyError::InvalidLayout(line) => write!(
+                f,
+                "Changelog entry doesn't match dpkg-parsechangelog layout at line: {}",
+                line
+            ),
+            DebyError::InvalidTimezone(timezone) => write!(
+                f,
+                "Invalid timezone {}, expected local, UTC, an offset like +03:00 or a name like Europe/Berlin",
+                timezone
+            ),
+            DebyError::MaintainerMissing => write!(
+                f,
+                "Could not find changelog maintainer, set it in .debyrc, DEBFULLNAME and DEBEMAIL or maintainer option"
+            ),
+            DebyError::InvalidMaintainer(maintainer) => write!(
+                f,
+                "Invalid maintainer {}, expected `Name <email>`",
+                maintainer
+            ),
+            DebyError::UnknownDistribution(distribution) => write!(
+                f,
+                "Distribution {} is not allowed by config file .debyrc",
+                distribution
+            ),
+            DebyError::Git(reason) => write!(f, "Could not read git metadata: {}", reason),
+            DebyError::UnknownDebianRelease(release) => {
+                write!(f, "Unknown Debian release {}", release)
+            }
+            DebyError::UnknownUbuntuRelease(release) => {
+                write!(f, "Unknown Ubuntu release {}", release)
+            }
+            DebyError::InvalidVersion(version, reason) => {
+                write!(f, "Invalid version {}: {}", version, reason)
+            }
+            DebyError::VersionNotIncreasing(version, latest) => write!(
+                f,
+                "Version {} is not higher than the latest version {} in debian changelog file",
+                version, latest
+            ),
+            DebyError::VersionExists(version) => write!(
+                f,
+                "Version {} already exists in debian changelog file",
+                version
+            ),
+            DebyError::ConfigExists => write!(f, "Config file .debyrc already exists"),
+            DebyError::ConfigNotEditable(reason) => {
+                write!(f, "Could not edit config file: {}", reason)
+            }
+            DebyError::UnknownProfile(profile) => {
+                write!(f, "Profile {} is not defined in .debyrc", profile)
+            }
+            DebyError::UnsupportedConfigVersion(version) => {
+                write!(f, "Unsupported config version {}", version)
+            }
+            DebyError::LegacyConfigKey(key) => write!(
+                f,
+                "Top-level `{}` of config file has no changelog or control.sourceControl section to be moved into",
+                key
+            ),
+            DebyError::ConfigParse(err) => write!(f, "Invalid config file: {}", err),
+            DebyError::UnknownConfigKeys(keys) => {
+                write!(f, "Unknown keys in config file: {}", keys.join(", "))
+            }
+            DebyError::EmptyChanges => write!(f, "Changes of a changelog entry are empty"),
+            DebyError::Sign(path, reason) => write!(f, "Could not sign {}: {}", path, reason),
+            DebyError::VersionNotFound(version) => {
+                write!(f, "Version {} not found in debian changelog file", version)
+            }
+            DebyError::InvalidVersionRange(from, to) => write!(
+                f,
+                "Version {} is not older than {} in debian changelog file",
+                from, to
+            ),
+            DebyError::DependencyExists(name) => {
+                write!(f, "Dependency on {} already exists", name)
+            }
+            DebyError::BinaryPackageExists(name) => write!(
+                f,
+                "Binary package {} already exists in debian control file",
+                name
+            ),
         }
     }
 }
 
+impl Error for DebyError {}
+
+/// Keeps structured errors produced internally, other errors are replaced with `fallback`
+///
+/// ## Arguments
+///
+/// - `err` - internal error
+/// - `fallback` - error to be used when `err` is not a `DebyError`
+fn map_error(err: Box<dyn Error>, fallback: DebyError) -> DebyError {
+    match err.downcast::<DebyError>() {
+        Ok(e) => *e,
+        Err(_) => fallback,
+    }
+}
+
 /// Updates `changelog` and `control` files
 ///
 /// ## Arguments
@@ -36,28 +271,174 @@ pub fn update(
     changes: &str,
     user_defined_fields: Vec<&str>,
 ) -> Result<(String, String), DebyError> {
-    let config = Config::new().map_err(|_| DebyError::ConfigNew)?;
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
 
     let msg = config
-        .update(version, changes, user_defined_fields)
-        .map_err(|_| DebyError::Update)?;
+        .update(
+            version,
+            changes,
+            user_defined_fields,
+            &ChangelogOptions::default(),
+        )
+        .map_err(|e| map_error(e, DebyError::Update))?;
 
     let (changelog_msg, control_msg) = msg;
 
     Ok((changelog_msg.to_string(), control_msg.to_string()))
 }
 
+/// Updates `changelog` and `control` files with options overriding config file settings
+/// for a single changelog entry, options select config file as well
+///
+/// ## Arguments
+///
+/// - `version` - an updated version string
+/// - `changes` - changes to be included in your files
+/// - `user_defined_fields` - user defined fields to be included in your files
+/// - `options` - options of the changelog entry, e.g. its urgency
+pub fn update_with_options(
+    version: &str,
+    changes: &str,
+    user_defined_fields: Vec<&str>,
+    options: &ChangelogOptions,
+) -> Result<(String, String), DebyError> {
+    let config = Config::select(
+        options.config_path.as_deref(),
+        options.profile.as_deref(),
+        &options.context,
+    )
+    .map_err(|e| map_error(e, DebyError::ConfigNew))?;
+    let (version, changes, options) = options.entry(&config, version, changes)?;
+
+    let (changelog_msg, control_msg) = config
+        .update(&version, &changes, user_defined_fields, &options)
+        .map_err(|e| map_error(e, DebyError::Update))?;
+
+    Ok((changelog_msg.to_string(), control_msg.to_string()))
+}
+
+/// Updates `changelog` and `control` files using an already parsed config
+///
+/// ## Arguments
+///
+/// - `config` - config, e.g. parsed from a string with `str::parse`
+/// - `version` - an updated version string
+/// - `changes` - changes to be included in your files
+/// - `user_defined_fields` - additional dynamic fields to be included in `control` file
+pub fn update_with_config(
+    config: &Config,
+    version: &str,
+    changes: &str,
+    user_defined_fields: Vec<&str>,
+) -> Result<(String, String), DebyError> {
+    let (changelog_msg, control_msg) = config
+        .update(
+            version,
+            changes,
+            user_defined_fields,
+            &ChangelogOptions::default(),
+        )
+        .map_err(|e| map_error(e, DebyError::Update))?;
+
+    Ok((changelog_msg.to_string(), control_msg.to_string()))
+}
+
+/// Updates `changelog` and `control` files of every package defined in `packages` array of config file.
+/// Returns messages of every package in the order of the array.
+/// Packages are updated one by one and the update stops at the first failing package,
+/// files of packages updated before it are kept and reported with `PartialUpdate` error
+///
+/// ## Arguments
+///
+/// - `version` - an updated version string
+/// - `changes` - changes to be included in your files
+/// - `user_defined_fields` - additional dynamic fields to be included in `control` files
+pub fn update_all(
+    version: &str,
+    changes: &str,
+    user_defined_fields: Vec<&str>,
+) -> Result<Vec<(String, String)>, DebyError> {
+    let configs = Config::packages().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    let mut messages = vec![];
+    let mut updated = vec![];
+    for config in configs {
+        let dir = config.path("").display().to_string();
+        let (changelog_msg, control_msg) = match config.update(
+            version,
+            changes,
+            user_defined_fields.clone(),
+            &ChangelogOptions::default(),
+        ) {
+            Ok(msgs) => msgs,
+            Err(e) if updated.is_empty() => return Err(map_error(e, DebyError::Update)),
+            Err(e) => {
+                let e = Box::new(map_error(e, DebyError::Update));
+                return Err(DebyError::PartialUpdate(updated, dir, e));
+            }
+        };
+        messages.push((changelog_msg.to_string(), control_msg.to_string()));
+        updated.push(dir);
+    }
+
+    Ok(messages)
+}
+
 /// Updates debian control file
 ///
 /// ## Arguments
 ///
 /// - `user_defined_fields` - dynamic fields to be included in binary section of control file
 pub fn update_control_file(user_defined_fields: Vec<&str>) -> Result<String, DebyError> {
-    let config = Config::new().map_err(|_| DebyError::ConfigNew)?;
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
 
     let msg = config
-        .update_control(user_defined_fields)
-        .map_err(|_| DebyError::ControlUpdate)?;
+        .update_control(user_defined_fields, &ControlOptions::default())
+        .map_err(|e| map_error(e, DebyError::ControlUpdate))?;
+
+    Ok(msg.to_string())
+}
+
+/// Updates debian control file with options overriding config file settings, e.g. placed
+/// user-defined fields or paragraphs to be written. Additionally returns warnings about
+/// written fields, e.g. very old standards version or description synopsis
+/// which lintian would complain about
+///
+/// ## Arguments
+///
+/// - `user_defined_fields` - dynamic fields to be included in binary section of control file
+/// - `options` - options of the update
+pub fn update_control_file_with_options(
+    user_defined_fields: Vec<&str>,
+    options: &ControlOptions,
+) -> Result<(String, Vec<lint::Lint>), DebyError> {
+    let config = Config::select(
+        options.config_path.as_deref(),
+        options.profile.as_deref(),
+        &options.context,
+    )
+    .map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    let msg = config
+        .update_control(user_defined_fields, options)
+        .map_err(|e| map_error(e, DebyError::ControlUpdate))?;
+
+    Ok((msg, config.control_lints()))
+}
+
+/// Updates debian control file using an already parsed config
+///
+/// ## Arguments
+///
+/// - `config` - config, e.g. parsed from a string with `str::parse`
+/// - `user_defined_fields` - dynamic fields to be included in binary section of control file
+pub fn update_control_file_with_config(
+    config: &Config,
+    user_defined_fields: Vec<&str>,
+) -> Result<String, DebyError> {
+    let msg = config
+        .update_control(user_defined_fields, &ControlOptions::default())
+        .map_err(|e| map_error(e, DebyError::ControlUpdate))?;
 
     Ok(msg.to_string())
 }
@@ -69,11 +450,282 @@ pub fn update_control_file(user_defined_fields: Vec<&str>) -> Result<String, Deb
 /// - `version` - version string to be included in changelog file
 /// - `changes` - changes to be included in changelog file
 pub fn update_changelog_file(version: &str, changes: &str) -> Result<String, DebyError> {
-    let config = Config::new().map_err(|_| DebyError::ConfigNew)?;
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    let msg = config
+        .update_changelog(version, changes, &ChangelogOptions::default())
+        .map_err(|e| map_error(e, DebyError::ChangelogUpdate))?;
+
+    Ok(msg.to_string())
+}
+
+/// Updates debian changelog file with options overriding config file settings for a single entry,
+/// e.g. a backport or a security upload, options select config file as well
+///
+/// ## Arguments
+///
+/// - `version` - version string to be included in changelog file
+/// - `changes` - changes to be included in changelog file
+/// - `options` - options of the entry, e.g. its distributions
+pub fn update_changelog_file_with_options(
+    version: &str,
+    changes: &str,
+    options: &ChangelogOptions,
+) -> Result<String, DebyError> {
+    let config = Config::select(
+        options.config_path.as_deref(),
+        options.profile.as_deref(),
+        &options.context,
+    )
+    .map_err(|e| map_error(e, DebyError::ConfigNew))?;
+    let (version, changes, options) = options.entry(&config, version, changes)?;
 
     let msg = config
-        .update_changelog(&version, &changes)
-        .map_err(|_| DebyError::ChangelogUpdate)?;
+        .update_changelog(&version, &changes, &options)
+        .map_err(|e| map_error(e, DebyError::ChangelogUpdate))?;
 
     Ok(msg.to_string())
 }
+
+/// Updates debian changelog file using an already parsed config
+///
+/// ## Arguments
+///
+/// - `config` - config, e.g. parsed from a string with `str::parse`
+/// - `version` - version string to be included in changelog file
+/// - `changes` - changes to be included in changelog file
+pub fn update_changelog_file_with_config(
+    config: &Config,
+    version: &str,
+    changes: &str,
+) -> Result<String, DebyError> {
+    let msg = config
+        .update_changelog(version, changes, &ChangelogOptions::default())
+        .map_err(|e| map_error(e, DebyError::ChangelogUpdate))?;
+
+    Ok(msg.to_string())
+}
+
+/// Backfills debian changelog file with releases of a keep-a-changelog formatted markdown file,
+/// releases newer than the latest entry are added. Sections like `Added` become headings of changes
+///
+/// ## Arguments
+///
+/// - `markdown_file` - path to markdown changelog, e.g. `CHANGELOG.md`
+pub fn import_changelog_md(markdown_file: &Path) -> Result<String, DebyError> {
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    config
+        .import_changelog(markdown_file)
+        .map_err(|e| map_error(e, DebyError::ChangelogUpdate))
+}
+
+/// Releases the latest entry of debian changelog file written with `UNRELEASED` distribution,
+/// mirroring `dch -r`: distribution is replaced, maintainer and date are refreshed
+///
+/// ## Arguments
+///
+/// - `distribution` - distribution of a release, e.g. `unstable`
+pub fn finalize_changelog(distribution: &str) -> Result<String, DebyError> {
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    let msg = config
+        .finalize_changelog(distribution, &ChangelogOptions::default())
+        .map_err(|e| map_error(e, DebyError::ChangelogUpdate))?;
+
+    Ok(msg.to_string())
+}
+
+/// Releases the latest entry of debian changelog file like `finalize_changelog`, maintainer and date
+/// of options are used in trailer line, e.g. to sign the entry by a person cutting the release
+///
+/// ## Arguments
+///
+/// - `distribution` - distribution of a release, e.g. `unstable`
+/// - `options` - options of the release, only `maintainer`, `date` and config file selection are used
+pub fn finalize_changelog_with_options(
+    distribution: &str,
+    options: &ChangelogOptions,
+) -> Result<String, DebyError> {
+    let config = Config::select(
+        options.config_path.as_deref(),
+        options.profile.as_deref(),
+        &options.context,
+    )
+    .map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    let msg = config
+        .finalize_changelog(distribution, options)
+        .map_err(|e| map_error(e, DebyError::ChangelogUpdate))?;
+
+    Ok(msg.to_string())
+}
+
+/// Appends a binary package paragraph to debian control file.
+/// Existing paragraphs are left untouched
+///
+/// ## Arguments
+///
+/// - `package` - name of a binary package defined in config file
+/// - `user_defined_fields` - dynamic fields to be included in appended paragraph
+pub fn append_control_binary(
+    package: &str,
+    user_defined_fields: Vec<&str>,
+) -> Result<String, DebyError> {
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    let msg = config
+        .append_control_binary(package, user_defined_fields)
+        .map_err(|e| map_error(e, DebyError::ControlUpdate))?;
+
+    Ok(msg.to_string())
+}
+
+/// Adds a binary package definition to config file `.debyrc`
+///
+/// ## Arguments
+///
+/// - `package` - binary package name
+/// - `description` - binary package description
+/// - `section` - binary package section
+/// - `architecture` - binary package architecture
+/// - `update_control` - regenerate debian control file right away
+pub fn add_binary(
+    package: &str,
+    description: &str,
+    section: &str,
+    architecture: &str,
+    update_control: bool,
+) -> Result<String, DebyError> {
+    Config::add_binary(package, description, section, architecture)
+        .map_err(|e| map_error(e, DebyError::ConfigWrite))?;
+
+    let msg = format!("Successfully added binary package {} to .debyrc", package);
+    if !update_control {
+        return Ok(msg);
+    }
+
+    let control_msg = update_control_file(vec![])?;
+
+    Ok(format!("{}\n{}", msg, control_msg))
+}
+
+/// Imports an existing debian control file into `control` section of config file `.debyrc`,
+/// so that a package with a hand-written control file can be maintained with deby
+pub fn import_control() -> Result<String, DebyError> {
+    let control = Config::import_control().map_err(|e| map_error(e, DebyError::ConfigWrite))?;
+
+    Ok(format!(
+        "Successfully imported debian/control file to .debyrc: source package {}, binary packages {}",
+        control.source_name(),
+        control.binary_names().join(", ")
+    ))
+}
+
+/// Adds a dependency to a relationship field of config file `.debyrc`
+///
+/// ## Arguments
+///
+/// - `field` - relationship field name, e.g. `buildDepends`, `preDepends` or `depends`
+/// - `dependency` - dependency string value, e.g. `libfoo (>= 1.2)`
+/// - `package` - binary package name, can be omitted for source fields or a single binary package
+pub fn add_dependency(
+    field: &str,
+    dependency: &str,
+    package: Option<&str>,
+) -> Result<String, DebyError> {
+    Config::add_dependency(field, dependency, package)
+        .map_err(|e| map_error(e, DebyError::ConfigWrite))?;
+
+    Ok(format!(
+        "Successfully added dependency {} to {} field in .debyrc",
+        dependency, field
+    ))
+}
+
+/// Returns version and distribution of the latest entry of existing `debian/changelog` file
+pub fn latest_version() -> Result<(String, String), DebyError> {
+    let latest = changelog::parse()?
+        .next()
+        .ok_or(DebyError::ChangelogEmpty)?;
+
+    Ok((latest.version, latest.distribution))
+}
+
+/// Returns subjects of commits since the previous tag, one per line, to be passed as `changes`.
+/// When the repository has no tags, commits since the latest change of `debian/changelog` are used
+pub fn changes_from_git() -> Result<String, DebyError> {
+    let root = Config::find_root().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    Ok(config::git::subjects_since_release(&root)?.join("\n"))
+}
+
+/// Validates relationship fields of binary packages defined in config file `.debyrc`:
+/// self-references, duplicates, impossible version constraints and circular `Pre-Depends`
+pub fn validate_dependencies() -> Result<Vec<DependencyIssue>, DebyError> {
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    Ok(config.dependency_issues())
+}
+
+/// Renders debian control file from config file `.debyrc` in memory and compares it with
+/// the existing one, nothing is written. An empty report means the file is up to date,
+/// so it can serve as a CI gate against hand edits of debian control file
+///
+/// ## Arguments
+///
+/// - `user_defined_fields` - dynamic fields passed to `update_control_file`
+pub fn check(user_defined_fields: Vec<&str>) -> Result<Vec<ControlDrift>, DebyError> {
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    config
+        .check_control(user_defined_fields)
+        .map_err(|e| map_error(e, DebyError::ControlUpdate))
+}
+
+/// Writes a commented starter config file `.debyrc` into current directory.
+/// Package name is taken from directory name, maintainer and `vcsBrowser` from git config.
+/// An existing `.debyrc` is never overwritten
+///
+/// ## Arguments
+///
+/// - `create_debian_dir` - create an empty `debian` directory as well
+pub fn init(create_debian_dir: bool) -> Result<String, DebyError> {
+    Config::init(create_debian_dir).map_err(|e| map_error(e, DebyError::ConfigWrite))?;
+
+    Ok("Successfully created config file .debyrc".to_string())
+}
+
+/// Validates config file `.debyrc` and reports all problems at once: empty maintainer,
+/// missing package names, malformed standards version and URLs, unknown sections, invalid values and dependencies
+pub fn validate_config() -> Result<Vec<ValidationIssue>, DebyError> {
+    Config::validate().map_err(|e| map_error(e, DebyError::ConfigNew))
+}
+
+/// Runs lint checks of config file `.debyrc`: changelog and control settings agree with each other,
+/// a version agrees with `debian/source/format`, dependencies of binary packages are valid,
+/// description synopses follow lintian rules.
+/// Errors go first
+///
+/// ## Arguments
+///
+/// - `version` - version to be written to changelog file, version checks are skipped for `None`
+pub fn lint(version: Option<&str>) -> Result<Vec<lint::Lint>, DebyError> {
+    let config = Config::new().map_err(|e| map_error(e, DebyError::ConfigNew))?;
+
+    config
+        .lint(version)
+        .map_err(|e| map_error(e, DebyError::ConfigNew))
+}
+
+/// Rewrites a legacy Latin-1 encoded file, e.g. an old `debian/changelog`, as UTF-8.
+/// Other functions reject non-UTF-8 files instead of transcoding them behind your back.
+/// Returns `false` when the file is valid UTF-8 already and is left untouched
+///
+/// ## Arguments
+///
+/// - `path` - path to a file to be converted
+pub fn convert_latin1(path: &Path) -> Result<bool, DebyError> {
+    config::encoding::convert_latin1(path)
+        .map_err(|e| map_error(e, DebyError::EncodingConvert(path.display().to_string())))
+}
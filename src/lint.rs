@@ -0,0 +1,61 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found by lint checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub severity: Severity,
+    /// Short stable identifier of a check, e.g. `package-name-mismatch`
+    pub code: String,
+    pub message: String,
+}
+
+impl Lint {
+    pub(crate) fn warning(code: &str, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            code: code.to_string(),
+            message,
+        }
+    }
+
+    pub(crate) fn error(code: &str, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let fake_lint = Lint::error("fake-code", "fake message".to_string());
+
+        assert_eq!(fake_lint.to_string(), "error [fake-code]: fake message");
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+/// Replaces `{name}` placeholders of a template with variable values.
+/// Unknown placeholders are left as is
+///
+/// # Arguments
+///
+/// - `template` - template string
+/// - `vars` - variable names and their values
+pub(crate) fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let end = match tail.find('}') {
+            Some(end) => end,
+            None => {
+                rest = tail;
+                break;
+            }
+        };
+        let name = &tail[1..end];
+        match vars.iter().find(|(k, _)| *k == name) {
+            Some((_, v)) => rendered.push_str(v),
+            None => rendered.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+/// Formats context values as `{context.<key>}` template variables
+///
+/// # Arguments
+///
+/// - `context` - free-form context values
+pub(crate) fn context_vars(context: &BTreeMap<String, String>) -> Vec<(String, String)> {
+    context
+        .iter()
+        .map(|(k, v)| (format!("context.{}", k), v.to_string()))
+        .collect()
+}
+
+/// Replaces `{context.<key>}` placeholders of a template with context values
+///
+/// # Arguments
+///
+/// - `template` - template string
+/// - `context` - free-form context values
+pub(crate) fn render_context(template: &str, context: &BTreeMap<String, String>) -> String {
+    if context.is_empty() {
+        return template.to_string();
+    }
+    let vars = context_vars(context);
+    let vars: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+
+    render(template, &vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_context() {
+        let mut fake_context = BTreeMap::new();
+        fake_context.insert("team".to_string(), "Platform".to_string());
+
+        let actual = render_context("{context.team} {team} ${misc:Depends}", &fake_context);
+
+        assert_eq!(actual, "Platform {team} ${misc:Depends}");
+    }
+
+    #[test]
+    fn test_render() {
+        let actual = render("{a} and {b}", &[("a", "1"), ("b", "{a}")]);
+
+        assert_eq!(actual, "1 and {a}");
+    }
+
+    #[test]
+    fn test_render_unknown_placeholder() {
+        let actual = render("{a} {unknown} {", &[("a", "1")]);
+
+        assert_eq!(actual, "1 {unknown} {");
+    }
+}
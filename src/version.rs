@@ -0,0 +1,548 @@
+use regex::Regex;
+
+use std::cmp::Ordering;
+
+use crate::changelog;
+use crate::config::{git, Config};
+use crate::DebyError;
+
+/// Part of a version to be bumped
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bump {
+    /// Increments debian revision, `1.2.3-1` becomes `1.2.3-2`.
+    /// The last number of native versions is incremented, `1.2.3` becomes `1.2.4`
+    Revision,
+    /// Sets a new upstream version, debian revision is reset to `1`, epoch is kept
+    Upstream(String),
+    /// Increments epoch, `1.2.3-1` becomes `1:1.2.3-1`
+    Epoch,
+    /// Binary-only rebuild, `1.2.3-1` becomes `1.2.3-1+b1`, `1.2.3-1+b1` becomes `1.2.3-1+b2`
+    BinNmu,
+}
+
+/// Splits a version into epoch, upstream version and debian revision
+///
+/// # Arguments
+///
+/// - `version` - version string, e.g. `1:2.0-3`
+fn split(version: &str) -> (u64, &str, &str) {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((e, r)) => (e.parse().unwrap_or(0), r),
+        None => (0, version),
+    };
+    let (upstream, revision) = match rest.rfind('-') {
+        Some(i) => (&rest[..i], &rest[i + 1..]),
+        None => (rest, ""),
+    };
+
+    (epoch, upstream, revision)
+}
+
+/// Returns sort weight of a non-digit character: `~` sorts before anything,
+/// even the end of a part, letters sort before non-letters
+///
+/// # Arguments
+///
+/// - `c` - character of a version part
+fn order(c: Option<char>) -> i32 {
+    match c {
+        None => 0,
+        Some('~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// Compares upstream versions or debian revisions the way dpkg does
+///
+/// # Arguments
+///
+/// - `a` - version part
+/// - `b` - version part to compare with
+fn compare_part(a: &str, b: &str) -> Ordering {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let ac = order(a.get(i).copied());
+            let bc = order(b.get(j).copied());
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            i += 1;
+            j += 1;
+        }
+
+        while i < a.len() && a[i] == '0' {
+            i += 1;
+        }
+        while j < b.len() && b[j] == '0' {
+            j += 1;
+        }
+
+        let mut first_diff = Ordering::Equal;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if i < a.len() && a[i].is_ascii_digit() {
+            return Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Compares two versions according to Debian version ordering
+///
+/// # Arguments
+///
+/// - `a` - version string
+/// - `b` - version string to compare with
+pub(crate) fn compare(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_upstream, a_revision) = split(a);
+    let (b_epoch, b_upstream, b_revision) = split(b);
+
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| compare_part(a_upstream, b_upstream))
+        .then_with(|| compare_part(a_revision, b_revision))
+}
+
+/// Checks a version against Debian policy grammar `[epoch:]upstream_version[-debian_revision]`
+///
+/// ## Arguments
+///
+/// - `version` - version string, e.g. `2:1.4.0-1`
+pub fn validate(version: &str) -> Result<(), DebyError> {
+    let invalid = |reason: &str| {
+        Err(DebyError::InvalidVersion(
+            version.to_string(),
+            reason.to_string(),
+        ))
+    };
+
+    let rest = match version.split_once(':') {
+        Some((epoch, _)) if epoch.is_empty() || !epoch.chars().all(|c| c.is_ascii_digit()) => {
+            return invalid("epoch must be an unsigned integer")
+        }
+        Some((_, rest)) => rest,
+        None => version,
+    };
+    let (upstream, revision) = match rest.rfind('-') {
+        Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+        None => (rest, None),
+    };
+
+    if !upstream.starts_with(|c: char| c.is_ascii_digit()) {
+        return invalid("upstream version must start with a digit");
+    }
+    let allowed = |c: char| c.is_ascii_alphanumeric() || ".+~".contains(c);
+    if !upstream.chars().all(|c| allowed(c) || c == '-') {
+        return invalid("upstream version may contain only alphanumerics and . + - ~");
+    }
+    match revision {
+        Some("") => invalid("debian revision is empty"),
+        Some(r) if !r.chars().all(allowed) => {
+            invalid("debian revision may contain only alphanumerics and . + ~")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Codenames of Debian releases which have backports suites
+const DEBIAN_RELEASES: [(u32, &str); 6] = [
+    (9, "stretch"),
+    (10, "buster"),
+    (11, "bullseye"),
+    (12, "bookworm"),
+    (13, "trixie"),
+    (14, "forky"),
+];
+
+/// Returns codename of a Debian release
+///
+/// # Arguments
+///
+/// - `release` - major version of a Debian release, e.g. `12`
+fn codename(release: u32) -> Result<&'static str, DebyError> {
+    DEBIAN_RELEASES
+        .iter()
+        .find(|(r, _)| *r == release)
+        .map(|(_, c)| *c)
+        .ok_or(DebyError::UnknownDebianRelease(release))
+}
+
+/// Checks that a version of a security upload follows `+deb<release>u<n>` convention
+/// and returns its distribution, e.g. `bookworm-security` for `1.2.3-1+deb12u1`
+///
+/// ## Arguments
+///
+/// - `version` - version of a security upload
+/// - `release` - major version of a target Debian release, e.g. `12`
+pub fn security(version: &str, release: u32) -> Result<String, DebyError> {
+    let codename = codename(release)?;
+
+    let re = Regex::new(&format!(r"\+deb{}u\d+$", release)).unwrap();
+    if !re.is_match(version) {
+        return Err(DebyError::InvalidVersion(
+            version.to_string(),
+            format!("security upload version must end with +deb{}u<n>", release),
+        ));
+    }
+
+    Ok(format!("{}-security", codename))
+}
+
+/// Derives a backport version and its distribution, `1.2.3-1` becomes `1.2.3-1~bpo12+1`
+/// uploaded to `bookworm-backports` for Debian 12
+///
+/// ## Arguments
+///
+/// - `version` - version of a package being backported
+/// - `release` - major version of a target Debian release, e.g. `12`
+pub fn backport(version: &str, release: u32) -> Result<(String, String), DebyError> {
+    let codename = codename(release)?;
+
+    Ok((
+        format!("{}~bpo{}+1", version, release),
+        format!("{}-backports", codename),
+    ))
+}
+
+/// Ubuntu releases and their series names, the newest release goes last
+const UBUNTU_SERIES: [(&str, &str); 7] = [
+    ("20.04", "focal"),
+    ("22.04", "jammy"),
+    ("24.04", "noble"),
+    ("24.10", "oracular"),
+    ("25.04", "plucky"),
+    ("25.10", "questing"),
+    ("26.04", "resolute"),
+];
+
+/// Pockets of Ubuntu series, e.g. `jammy-updates`
+const UBUNTU_POCKETS: [&str; 4] = ["proposed", "updates", "security", "backports"];
+
+/// Returns series name of the newest known Ubuntu release
+pub(crate) fn latest_ubuntu_series() -> &'static str {
+    UBUNTU_SERIES[UBUNTU_SERIES.len() - 1].1
+}
+
+/// Checks that a distribution is a known Ubuntu series, optionally with a pocket like `jammy-updates`
+///
+/// # Arguments
+///
+/// - `distribution` - a single distribution
+pub(crate) fn is_ubuntu_series(distribution: &str) -> bool {
+    let (series, pocket) = match distribution.split_once('-') {
+        Some((s, p)) => (s, Some(p)),
+        None => (distribution, None),
+    };
+
+    UBUNTU_SERIES.iter().any(|(_, s)| *s == series)
+        && pocket.is_none_or(|p| UBUNTU_POCKETS.contains(&p))
+}
+
+/// Derives the next Ubuntu version of a package: `1.2.3-1` becomes `1.2.3-1ubuntu1`,
+/// `1.2.3-1ubuntu1` becomes `1.2.3-1ubuntu2`
+///
+/// ## Arguments
+///
+/// - `version` - current version of a package
+pub fn ubuntu(version: &str) -> String {
+    let re = Regex::new(r"ubuntu[\d.]*\d$").unwrap();
+    match re.is_match(version) {
+        true => increment(version),
+        false => format!("{}ubuntu1", version),
+    }
+}
+
+/// Derives a stable release update version of an Ubuntu release and its distribution:
+/// `1.2.3-1` becomes `1.2.3-1ubuntu0.22.04.1` and `1.2.3-1ubuntu2` becomes `1.2.3-1ubuntu2.22.04.1`
+/// uploaded to `jammy` for Ubuntu 22.04, further updates increment the last number
+///
+/// ## Arguments
+///
+/// - `version` - current version of a package
+/// - `release` - target Ubuntu release, e.g. `22.04`
+pub fn ubuntu_update(version: &str, release: &str) -> Result<(String, String), DebyError> {
+    let series = UBUNTU_SERIES
+        .iter()
+        .find(|(r, _)| *r == release)
+        .map(|(_, s)| s.to_string())
+        .ok_or_else(|| DebyError::UnknownUbuntuRelease(release.to_string()))?;
+
+    let update = Regex::new(&format!(r"ubuntu[\d.]*\.{}\.\d+$", regex::escape(release))).unwrap();
+    let modified = Regex::new(r"ubuntu\d+$").unwrap();
+    let version = if update.is_match(version) {
+        increment(version)
+    } else if modified.is_match(version) {
+        format!("{}.{}.1", version, release)
+    } else {
+        format!("{}ubuntu0.{}.1", version, release)
+    };
+
+    Ok((version, series))
+}
+
+/// Formats a snapshot version, debian revision is always `1`
+///
+/// # Arguments
+///
+/// - `upstream` - upstream version the snapshot is based on
+/// - `date` - commit date as `YYYYMMDD`
+/// - `sha` - short commit hash
+fn format_snapshot(upstream: &str, date: &str, sha: &str) -> String {
+    format!("{}+git{}.{}-1", upstream, date, sha)
+}
+
+/// Derives a snapshot version from the latest commit of git repo of the project,
+/// `1.2.3` becomes `1.2.3+git20240511.abc1234-1`
+///
+/// ## Arguments
+///
+/// - `upstream` - upstream version the snapshot is based on, e.g. `1.2.3`
+pub fn snapshot(upstream: &str) -> Result<String, DebyError> {
+    let root = Config::find_root().map_err(|e| crate::map_error(e, DebyError::ConfigNew))?;
+    let stdout = git::run(
+        &root,
+        &["log", "-1", "--format=%cd %h", "--date=format:%Y%m%d"],
+    )?;
+
+    match stdout.split_once(' ') {
+        Some((date, sha)) => Ok(format_snapshot(upstream, date, sha)),
+        None => Err(DebyError::Git(format!(
+            "unexpected git log output {}",
+            stdout
+        ))),
+    }
+}
+
+/// Increments the trailing number of a version part, `1` is appended when there's none
+///
+/// # Arguments
+///
+/// - `part` - upstream version or debian revision
+fn increment(part: &str) -> String {
+    let digits = part.len() - part.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let (prefix, number) = part.split_at(part.len() - digits);
+    match number.parse::<u64>() {
+        Ok(n) => format!("{}{}", prefix, n + 1),
+        Err(_) => format!("{}1", prefix),
+    }
+}
+
+/// Joins epoch, upstream version and debian revision, zero epoch and empty revision are omitted
+///
+/// # Arguments
+///
+/// - `epoch` - epoch
+/// - `upstream` - upstream version
+/// - `revision` - debian revision
+fn join(epoch: u64, upstream: &str, revision: &str) -> String {
+    let mut version = match epoch {
+        0 => upstream.to_string(),
+        e => format!("{}:{}", e, upstream),
+    };
+    if !revision.is_empty() {
+        version.push('-');
+        version.push_str(revision);
+    }
+
+    version
+}
+
+/// Computes the next version
+///
+/// ## Arguments
+///
+/// - `current` - current version, e.g. `1.2.3-1`
+/// - `bump` - part of a version to be bumped
+pub fn next(current: &str, bump: &Bump) -> String {
+    let (epoch, upstream, revision) = split(current);
+
+    match bump {
+        Bump::Revision if revision.is_empty() => join(epoch, &increment(upstream), ""),
+        Bump::Revision => join(epoch, upstream, &increment(revision)),
+        Bump::Upstream(new) if revision.is_empty() => join(epoch, new, ""),
+        Bump::Upstream(new) => join(epoch, new, "1"),
+        Bump::Epoch => join(epoch + 1, upstream, revision),
+        Bump::BinNmu if Regex::new(r"\+b\d+$").unwrap().is_match(current) => increment(current),
+        Bump::BinNmu => format!("{}+b1", current),
+    }
+}
+
+/// Computes the next version from the version of the latest entry of `debian/changelog` file
+///
+/// ## Arguments
+///
+/// - `bump` - part of a version to be bumped
+pub fn bump(bump: Bump) -> Result<String, DebyError> {
+    let latest = changelog::parse()?
+        .next()
+        .ok_or(DebyError::ChangelogEmpty)?;
+
+    Ok(next(&latest.version, &bump))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("1:2.0-3"), (1, "2.0", "3"));
+        assert_eq!(split("2.0-1-2"), (0, "2.0-1", "2"));
+        assert_eq!(split("2.0"), (0, "2.0", ""));
+    }
+
+    #[test]
+    fn test_compare() {
+        let fake_pairs = [
+            ("1.0", "1.0", Ordering::Equal),
+            ("1.0", "1.1", Ordering::Less),
+            ("1.10", "1.9", Ordering::Greater),
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.0", "1.0+b1", Ordering::Less),
+            ("1:0.1", "9.9", Ordering::Greater),
+            ("1.0-1", "1.0-2", Ordering::Less),
+            ("1.0-10", "1.0-9", Ordering::Greater),
+            ("1.0a", "1.0+", Ordering::Less),
+            ("1.01", "1.1", Ordering::Equal),
+            ("1.2.3-1~bpo12+1", "1.2.3-1", Ordering::Less),
+        ];
+
+        for (a, b, expected) in fake_pairs {
+            assert_eq!(compare(a, b), expected, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        for fake_version in [
+            "1.0",
+            "2:1.4.0-1",
+            "1.0~rc1+dfsg-1ubuntu0.1",
+            "1.0-1-2",
+            "0",
+        ] {
+            assert!(validate(fake_version).is_ok(), "{}", fake_version);
+        }
+        for fake_version in [
+            "", "a1.0", "x:1.0", ":1.0", "1.0-", "1.0_1", "1:1.0:2", "1.0-1_2",
+        ] {
+            assert!(
+                matches!(validate(fake_version), Err(DebyError::InvalidVersion(v, _)) if v == fake_version),
+                "{}",
+                fake_version
+            );
+        }
+    }
+
+    #[test]
+    fn test_backport() {
+        assert_eq!(
+            backport("1.2.3-1", 12).unwrap(),
+            (
+                "1.2.3-1~bpo12+1".to_string(),
+                "bookworm-backports".to_string()
+            )
+        );
+        assert!(compare("1.2.3-1~bpo12+1", "1.2.3-1") == Ordering::Less);
+        assert!(matches!(
+            backport("1.2.3-1", 3),
+            Err(DebyError::UnknownDebianRelease(3))
+        ));
+    }
+
+    #[test]
+    fn test_security() {
+        assert_eq!(
+            security("1.2.3-1+deb12u1", 12).unwrap(),
+            "bookworm-security"
+        );
+        assert!(matches!(
+            security("1.2.3-1+deb11u1", 12),
+            Err(DebyError::InvalidVersion(v, _)) if v == "1.2.3-1+deb11u1"
+        ));
+        assert!(security("1.2.3-1", 12).is_err());
+    }
+
+    #[test]
+    fn test_ubuntu() {
+        assert_eq!(ubuntu("1.2.3-1"), "1.2.3-1ubuntu1");
+        assert_eq!(ubuntu("1.2.3-1ubuntu1"), "1.2.3-1ubuntu2");
+        assert!(compare("1.2.3-1ubuntu1", "1.2.3-1") == Ordering::Greater);
+        assert!(compare("1.2.3-1ubuntu1", "1.2.3-2") == Ordering::Less);
+    }
+
+    #[test]
+    fn test_ubuntu_update() {
+        let jammy = |v: &str| ubuntu_update(v, "22.04").unwrap();
+
+        assert_eq!(
+            jammy("1.2.3-1"),
+            ("1.2.3-1ubuntu0.22.04.1".to_string(), "jammy".to_string())
+        );
+        assert_eq!(jammy("1.2.3-1ubuntu0.22.04.1").0, "1.2.3-1ubuntu0.22.04.2");
+        assert_eq!(jammy("1.2.3-1ubuntu2").0, "1.2.3-1ubuntu2.22.04.1");
+        assert!(matches!(
+            ubuntu_update("1.2.3-1", "21.04"),
+            Err(DebyError::UnknownUbuntuRelease(r)) if r == "21.04"
+        ));
+    }
+
+    #[test]
+    fn test_is_ubuntu_series() {
+        assert!(is_ubuntu_series("noble"));
+        assert!(is_ubuntu_series("jammy-updates"));
+        assert!(!is_ubuntu_series("unstable"));
+        assert!(!is_ubuntu_series("jammy-unstable"));
+    }
+
+    #[test]
+    fn test_format_snapshot() {
+        let actual = format_snapshot("1.2.3", "20240511", "abc1234");
+
+        assert_eq!(actual, "1.2.3+git20240511.abc1234-1");
+        assert!(validate(&actual).is_ok());
+        assert_eq!(compare(&actual, "1.2.3-1"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_next() {
+        let fake_cases = [
+            ("1.2.3-1", Bump::Revision, "1.2.3-2"),
+            ("1.2.3-1ubuntu9", Bump::Revision, "1.2.3-1ubuntu10"),
+            ("1.2.3-1~bpo", Bump::Revision, "1.2.3-1~bpo1"),
+            ("1.2.3", Bump::Revision, "1.2.4"),
+            (
+                "2:1.2.3-4",
+                Bump::Upstream("1.3.0".to_string()),
+                "2:1.3.0-1",
+            ),
+            ("1.2.3", Bump::Upstream("1.3.0".to_string()), "1.3.0"),
+            ("1.2.3-4", Bump::Epoch, "1:1.2.3-4"),
+            ("1:1.2.3", Bump::Epoch, "2:1.2.3"),
+            ("1.2.3-1", Bump::BinNmu, "1.2.3-1+b1"),
+            ("1.2.3-1+b1", Bump::BinNmu, "1.2.3-1+b2"),
+        ];
+
+        for (current, bump, expected) in fake_cases {
+            assert_eq!(next(current, &bump), expected, "{} {:?}", current, bump);
+        }
+    }
+}